@@ -1,10 +1,24 @@
+use crate::dart::{dart_post_int, DartPort};
+use crate::query::{JsonBytes, JsonLen};
 use crate::raw_object_set::{RawObject, RawObjectSet};
 use crate::txn::IsarDartTxn;
-use crate::{from_c_str, BoolSend, UintSend};
-use isar_core::collection::IsarCollection;
+use crate::{from_c_str, BoolSend, LongSend, UintSend};
+use isar_core::collection::{IsarCollection, OnConflict};
+use isar_core::error::IsarError;
 use isar_core::index::index_key::IndexKey;
 use serde_json::Value;
+use std::io::{self, Read};
 use std::os::raw::c_char;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+fn on_conflict_from_index(index: u8) -> OnConflict {
+    match index {
+        0 => OnConflict::Replace,
+        1 => OnConflict::Abort,
+        2 => OnConflict::Ignore,
+        _ => unreachable!(),
+    }
+}
 
 #[no_mangle]
 pub unsafe extern "C" fn isar_get(
@@ -20,6 +34,36 @@ pub unsafe extern "C" fn isar_get(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_exists(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    id: i64,
+    exists: &'static mut bool,
+) -> i64 {
+    let exists = BoolSend(exists);
+    isar_try_txn!(txn, move |txn| {
+        *exists.0 = collection.exists(txn, id)?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_exists_by_index(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    index_index: u32,
+    key: *mut IndexKey,
+    exists: &'static mut bool,
+) -> i64 {
+    let exists = BoolSend(exists);
+    let key = *Box::from_raw(key);
+    isar_try_txn!(txn, move |txn| {
+        *exists.0 = collection.exists_by_index(txn, index_index as usize, &key)?;
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_get_by_index(
     collection: &'static IsarCollection,
@@ -41,6 +85,29 @@ pub unsafe extern "C" fn isar_get_by_index(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_all_by_index_key(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    index_index: u32,
+    key: *mut IndexKey,
+    result: &'static mut RawObjectSet,
+) -> i64 {
+    let key = *Box::from_raw(key);
+    isar_try_txn!(txn, move |txn| {
+        let mut objects = vec![];
+        collection.get_all_by_index_key(txn, index_index as usize, &key, |id, object| {
+            let mut raw_obj = RawObject::new();
+            raw_obj.set_id(id);
+            raw_obj.set_object(Some(object));
+            objects.push(raw_obj);
+            true
+        })?;
+        result.fill_from_vec(objects);
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_get_all(
     collection: &'static IsarCollection,
@@ -48,12 +115,16 @@ pub unsafe extern "C" fn isar_get_all(
     objects: &'static mut RawObjectSet,
 ) -> i64 {
     isar_try_txn!(txn, move |txn| {
-        for object in objects.get_objects() {
-            let id = object.get_id();
-            let result = collection.get(txn, id)?;
-            object.set_object(result);
-        }
-        Ok(())
+        let ids: Vec<i64> = objects
+            .get_objects()
+            .iter_mut()
+            .map(|o| o.get_id())
+            .collect();
+        let mut objects_iter = objects.get_objects().iter_mut();
+        collection.get_all(txn, &ids, |_, result| {
+            objects_iter.next().unwrap().set_object(result);
+            true
+        })
     })
 }
 
@@ -86,35 +157,102 @@ pub unsafe extern "C" fn isar_put(
     collection: &'static mut IsarCollection,
     txn: &mut IsarDartTxn,
     object: &'static mut RawObject,
-    replace_on_conflict: bool,
+    on_conflict: u8,
 ) -> i64 {
+    let on_conflict = on_conflict_from_index(on_conflict);
     isar_try_txn!(txn, move |txn| {
         let id = if object.get_id() != i64::MIN {
             Some(object.get_id())
         } else {
             None
         };
-        let id = collection.put(txn, id, object.get_object(), replace_on_conflict)?;
+        let id = collection.put(txn, id, object.get_object(), on_conflict)?;
         object.set_id(id);
         Ok(())
     })
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn isar_put_all(
+pub unsafe extern "C" fn isar_put_by_index(
     collection: &'static IsarCollection,
     txn: &mut IsarDartTxn,
-    objects: &'static mut RawObjectSet,
-    replace_on_conflict: bool,
+    index_index: u32,
+    object: &'static mut RawObject,
+    on_conflict: u8,
 ) -> i64 {
+    let on_conflict = on_conflict_from_index(on_conflict);
     isar_try_txn!(txn, move |txn| {
-        for object in objects.get_objects() {
-            let id = if object.get_id() != i64::MIN {
+        let id =
+            collection.put_by_index(txn, index_index as usize, object.get_object(), on_conflict)?;
+        object.set_id(id);
+        Ok(())
+    })
+}
+
+fn get_put_all_batch(raw_objects: &mut [RawObject]) -> Vec<(Option<i64>, &[u8])> {
+    let ids: Vec<Option<i64>> = raw_objects
+        .iter_mut()
+        .map(|object| {
+            if object.get_id() != i64::MIN {
                 Some(object.get_id())
             } else {
                 None
-            };
-            let id = collection.put(txn, id, object.get_object(), replace_on_conflict)?;
+            }
+        })
+        .collect();
+    raw_objects
+        .iter()
+        .zip(ids)
+        .map(|(object, id)| (id, object.get_object().as_bytes()))
+        .collect()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_put_all(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    objects: &'static mut RawObjectSet,
+    on_conflict: u8,
+) -> i64 {
+    let on_conflict = on_conflict_from_index(on_conflict);
+    isar_try_txn!(txn, move |txn| {
+        let raw_objects = objects.get_objects();
+        let batch = get_put_all_batch(raw_objects);
+        let ids = collection.put_all(txn, &batch, on_conflict)?;
+        for (object, id) in raw_objects.iter_mut().zip(ids) {
+            object.set_id(id)
+        }
+        Ok(())
+    })
+}
+
+/// Posts the number of written objects to `progress_port` after every `chunk_size` objects. The
+/// operation is cancelled and the transaction fails if `cancel` is set between two chunks.
+#[no_mangle]
+pub unsafe extern "C" fn isar_put_all_async_progress(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    objects: &'static mut RawObjectSet,
+    on_conflict: u8,
+    chunk_size: u32,
+    progress_port: DartPort,
+    cancel: &'static AtomicBool,
+) -> i64 {
+    let on_conflict = on_conflict_from_index(on_conflict);
+    isar_try_txn!(txn, move |txn| {
+        let raw_objects = objects.get_objects();
+        let batch = get_put_all_batch(raw_objects);
+        let ids = collection.put_all_with_progress(
+            txn,
+            &batch,
+            on_conflict,
+            chunk_size as usize,
+            |count| {
+                dart_post_int(progress_port, count as i64);
+                !cancel.load(Ordering::SeqCst)
+            },
+        )?;
+        for (object, id) in raw_objects.iter_mut().zip(ids) {
             object.set_id(id)
         }
         Ok(())
@@ -197,12 +335,79 @@ pub unsafe extern "C" fn isar_delete_all_by_index(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_collection_count(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    count: &'static mut i64,
+) -> i64 {
+    let count = LongSend(count);
+    isar_try_txn!(txn, move |txn| {
+        *count.0 = collection.count(txn)? as i64;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_collection_get_size(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    include_indexes: bool,
+    include_links: bool,
+    size: &'static mut i64,
+) -> i64 {
+    let size = LongSend(size);
+    isar_try_txn!(txn, move |txn| {
+        *size.0 = collection.get_size(txn, include_indexes, include_links)? as i64;
+        Ok(())
+    })
+}
+
+struct IdsSend(*mut *mut i64);
+
+unsafe impl Send for IdsSend {}
+
+struct IdsLenSend(*mut u32);
+
+unsafe impl Send for IdsLenSend {}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_collection_iter_ids(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    ids: *mut *mut i64,
+    length: *mut u32,
+) -> i64 {
+    let ids = IdsSend(ids);
+    let length = IdsLenSend(length);
+    isar_try_txn!(txn, move |txn| {
+        let ids = ids;
+        let length = length;
+        let mut result = vec![];
+        collection.iter_ids(txn, |id| {
+            result.push(id);
+            Ok(true)
+        })?;
+        let mut result = result.into_boxed_slice();
+        length.0.write(result.len() as u32);
+        ids.0.write(result.as_mut_ptr());
+        std::mem::forget(result);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_free_ids(ids: *mut i64, length: u32) {
+    Vec::from_raw_parts(ids, length as usize, length as usize);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_clear(
     collection: &'static IsarCollection,
     txn: &mut IsarDartTxn,
+    reset_ids: bool,
 ) -> i64 {
-    isar_try_txn!(txn, move |txn| collection.clear(txn))
+    isar_try_txn!(txn, move |txn| collection.clear(txn, reset_ids))
 }
 
 #[no_mangle]
@@ -213,11 +418,117 @@ pub unsafe extern "C" fn isar_json_import(
     json_bytes: *const u8,
     json_length: u32,
     replace_on_conflict: bool,
+    inserted: &'static mut i64,
+    replaced: &'static mut i64,
 ) -> i64 {
     let id_name = from_c_str(id_name).unwrap();
     let bytes = std::slice::from_raw_parts(json_bytes, json_length as usize);
     let json: Value = serde_json::from_slice(bytes).unwrap();
+    let inserted = LongSend(inserted);
+    let replaced = LongSend(replaced);
     isar_try_txn!(txn, move |txn| {
-        collection.import_json(txn, id_name, json, replace_on_conflict)
+        let on_conflict = if replace_on_conflict {
+            OnConflict::Replace
+        } else {
+            OnConflict::Abort
+        };
+        let stats = collection.import_json(txn, id_name, json, on_conflict)?;
+        *inserted.0 = stats.inserted as i64;
+        *replaced.0 = stats.replaced as i64;
+        Ok(())
     })
 }
+
+/// Fills `buffer` with at most `capacity` bytes and returns the number of bytes written, `0` at
+/// the end of the input or a negative value if reading failed.
+pub type ReadChunkFn = extern "C" fn(buffer: *mut u8, capacity: u32) -> i64;
+
+struct ChunkReader(ReadChunkFn);
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let capacity = buf.len().min(u32::MAX as usize);
+        let read = (self.0)(buf.as_mut_ptr(), capacity as u32);
+        if read < 0 || read as usize > capacity {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Could not read chunk.",
+            ))
+        } else {
+            Ok(read as usize)
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_json_import_stream(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    id_name: *const c_char,
+    read_chunk: ReadChunkFn,
+    replace_on_conflict: bool,
+    inserted: &'static mut i64,
+    replaced: &'static mut i64,
+) -> i64 {
+    let id_name = from_c_str(id_name).unwrap();
+    let inserted = LongSend(inserted);
+    let replaced = LongSend(replaced);
+    isar_try_txn!(txn, move |txn| {
+        let on_conflict = if replace_on_conflict {
+            OnConflict::Replace
+        } else {
+            OnConflict::Abort
+        };
+        let reader = ChunkReader(read_chunk);
+        let stats = collection.import_json_stream(txn, id_name, reader, on_conflict)?;
+        *inserted.0 = stats.inserted as i64;
+        *replaced.0 = stats.replaced as i64;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_json_export(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    id_name: *const c_char,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) -> i64 {
+    let id_name = from_c_str(id_name).unwrap().unwrap_or("id");
+    let json = JsonBytes(json_bytes);
+    let json_length = JsonLen(json_length);
+    isar_try_txn!(txn, move |txn| {
+        let json = json;
+        let json_length = json_length;
+        let mut bytes = vec![];
+        collection.export_json(txn, id_name, |chunk| {
+            bytes.extend_from_slice(chunk);
+            Ok(true)
+        })?;
+        let mut bytes = bytes.into_boxed_slice();
+        json_length.0.write(bytes.len() as u32);
+        json.0.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_object_to_json(
+    collection: &IsarCollection,
+    object: &mut RawObject,
+    id_name: *const c_char,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) -> i64 {
+    let id_name = from_c_str(id_name).unwrap().unwrap_or("id");
+    isar_try! {
+        let json = collection.object_to_json(object.get_object(), id_name, object.get_id());
+        let bytes = serde_json::to_vec(&json).map_err(|_| IsarError::InvalidJson {})?;
+        let mut bytes = bytes.into_boxed_slice();
+        json_length.write(bytes.len() as u32);
+        json_bytes.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+    }
+}
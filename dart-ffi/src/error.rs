@@ -1,5 +1,6 @@
 use isar_core::error::IsarError;
 use once_cell::sync::Lazy;
+use std::cell::RefCell;
 use std::ffi::CString;
 use std::os::raw::c_char;
 use std::sync::Mutex;
@@ -7,6 +8,14 @@ use std::sync::Mutex;
 type ErrCounter = (Vec<(i64, String)>, i64);
 static ERRORS: Lazy<Mutex<ErrCounter>> = Lazy::new(|| Mutex::new((vec![], 1)));
 
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Fixed error code for unique index violations. The message can still be retrieved using
+/// `isar_get_error`.
+pub const ERR_UNIQUE_VIOLATION: i64 = -1;
+
 pub trait DartErrCode {
     fn into_dart_err_code(self) -> i64;
 }
@@ -18,12 +27,20 @@ impl DartErrCode for IsarError {
         if errors.len() > 10 {
             errors.remove(0);
         }
-        let err_code = *counter;
-        errors.push((err_code, self.to_string()));
-        *counter = counter.wrapping_add(1);
-        if *counter == 0 {
-            *counter = 1
-        }
+        let err_code = if let IsarError::UniqueViolation { .. } = self {
+            errors.retain(|(code, _)| *code != ERR_UNIQUE_VIOLATION);
+            ERR_UNIQUE_VIOLATION
+        } else {
+            let err_code = *counter;
+            *counter = counter.wrapping_add(1);
+            if *counter <= 0 {
+                *counter = 1
+            }
+            err_code
+        };
+        let message = self.to_string();
+        LAST_ERROR.with(|last_error| last_error.replace(Some(message.clone())));
+        errors.push((err_code, message));
         err_code
     }
 }
@@ -67,7 +84,59 @@ pub unsafe extern "C" fn isar_get_error(err_code: i64) -> *mut c_char {
     }
 }
 
+/// Returns the message of the last error that occurred on the calling thread and clears it.
+/// Errors of async transactions occur on a different thread and have to be retrieved using
+/// [isar_get_error]. The message has to be freed using [isar_free_error].
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_last_error() -> *mut c_char {
+    let last_error = LAST_ERROR.with(|last_error| last_error.borrow_mut().take());
+    if let Some(err_msg) = last_error {
+        CString::new(err_msg).unwrap().into_raw()
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_free_error(error: *mut c_char) {
     let _ = CString::from_raw(error);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use isar_core::error::illegal_arg;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_get_last_error() {
+        let err_code = isar_try! {
+            illegal_arg::<()>("Invalid offset")?;
+        };
+        assert_ne!(err_code, 0);
+
+        unsafe {
+            let err_msg = isar_get_last_error();
+            assert_eq!(
+                CStr::from_ptr(err_msg).to_str().unwrap(),
+                "IllegalArg: Invalid offset."
+            );
+            isar_free_error(err_msg);
+            assert!(isar_get_last_error().is_null());
+        }
+
+        isar_try! {
+            Err::<(), _>(IsarError::DbCorrupted {
+                message: "Invalid index entry".to_string(),
+            })?;
+        };
+        unsafe {
+            let err_msg = isar_get_last_error();
+            assert_eq!(
+                CStr::from_ptr(err_msg).to_str().unwrap(),
+                "DbCorrupted: Invalid index entry"
+            );
+            isar_free_error(err_msg);
+        }
+    }
+}
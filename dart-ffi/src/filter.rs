@@ -57,18 +57,34 @@ pub unsafe extern "C" fn isar_filter_link(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_link_length(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    lower: u32,
+    upper: u32,
+    link_index: u32,
+) -> i64 {
+    isar_try! {
+        let query_filter = Filter::link_length(collection, link_index as usize, lower, upper)?;
+        let ptr = Box::into_raw(Box::new(query_filter));
+        filter.write(ptr);
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_filter_null(
     collection: &IsarCollection,
     filter: *mut *const Filter,
     property_index: u32,
     any_null: bool,
+    is_null: bool,
 ) -> i64 {
     let property = collection.properties.get(property_index as usize);
     isar_try! {
         if let Some((_, property)) = property {
             let query_filter = if !property.data_type.is_scalar() && any_null {
-                match property.data_type {
+                let any_null_filter = match property.data_type {
                     DataType::ByteList => {
                         Filter::byte(*property, IsarObject::NULL_BYTE, IsarObject::NULL_BYTE)?
                     },
@@ -86,9 +102,16 @@ pub unsafe extern "C" fn isar_filter_null(
                     },
                     DataType::StringList => Filter::string(*property, None, None, false)?,
                     _ => unreachable!()
+                };
+                if is_null {
+                    any_null_filter
+                } else {
+                    Filter::not(any_null_filter)
                 }
+            } else if is_null {
+                Filter::null(*property)
             } else {
-                 Filter::null(*property)
+                Filter::not_null(*property)
             };
             let ptr = Box::into_raw(Box::new(query_filter));
             filter.write(ptr);
@@ -150,6 +173,62 @@ pub unsafe extern "C" fn isar_filter_id(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_bool(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    value: bool,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = Filter::bool(*property, Some(value))?;
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn isar_filter_list_element(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    index: u32,
+    long_value: i64,
+    double_value: f64,
+    string_value: *const c_char,
+    case_sensitive: bool,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let value = match property.data_type {
+                DataType::ByteList => ListElementValue::Byte(long_value as u8),
+                DataType::IntList => ListElementValue::Int(long_value as i32),
+                DataType::LongList => ListElementValue::Long(long_value),
+                DataType::FloatList => ListElementValue::Float(double_value as f32),
+                DataType::DoubleList => ListElementValue::Double(double_value),
+                DataType::StringList => {
+                    let str = from_c_str(string_value)?;
+                    ListElementValue::String(str.map(|s| s.to_string()))
+                }
+                _ => return illegal_arg("Property does not support this filter."),
+            };
+            let query_filter =
+                Filter::list_element(*property, index as usize, value, case_sensitive)?;
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_filter_byte(
     collection: &IsarCollection,
@@ -189,6 +268,8 @@ pub unsafe extern "C" fn isar_filter_long(
                 let lower = lower.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
                 let upper = upper.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
                 num_filter!(int, property, lower, include_lower, upper, include_upper)
+            } else if property.data_type == DataType::Decimal {
+                num_filter!(decimal, property, lower, include_lower, upper, include_upper)
             } else {
                 num_filter!(long, property, lower, include_lower, upper, include_upper)
             };
@@ -200,12 +281,68 @@ pub unsafe extern "C" fn isar_filter_long(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_long_gt(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    value: i64,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = if property.data_type == DataType::Int || property.data_type == DataType::IntList {
+                if value < i32::MIN as i64 {
+                    Filter::int(*property, i32::MIN, i32::MAX)?
+                } else {
+                    Filter::int_gt(*property, value.min(i32::MAX as i64) as i32)?
+                }
+            } else {
+                Filter::long_gt(*property, value)?
+            };
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_long_lt(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    value: i64,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = if property.data_type == DataType::Int || property.data_type == DataType::IntList {
+                if value > i32::MAX as i64 {
+                    Filter::int(*property, i32::MIN, i32::MAX)?
+                } else {
+                    Filter::int_lt(*property, value.max(i32::MIN as i64) as i32)?
+                }
+            } else {
+                Filter::long_lt(*property, value)?
+            };
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_filter_double(
     collection: &IsarCollection,
     filter: *mut *const Filter,
     lower: f64,
+    include_lower: bool,
     upper: f64,
+    include_upper: bool,
     property_index: u32,
 ) -> i64 {
     let property = collection.properties.get(property_index as usize);
@@ -216,9 +353,9 @@ pub unsafe extern "C" fn isar_filter_double(
             } else if property.data_type == DataType::Float || property.data_type == DataType::FloatList {
                 let lower = lower as f32;
                 let upper = upper as f32;
-                Filter::float(*property, lower, upper)?
+                Filter::float_between(*property, lower, include_lower, upper, include_upper)?
             } else {
-                Filter::double(*property, lower, upper)?
+                Filter::double_between(*property, lower, include_lower, upper, include_upper)?
             };
             let ptr = Box::into_raw(Box::new(query_filter));
             filter.write(ptr);
@@ -228,6 +365,169 @@ pub unsafe extern "C" fn isar_filter_double(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_double_is_nan(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = if property.data_type == DataType::Float {
+                Filter::float_is_nan(*property)?
+            } else {
+                Filter::double_is_nan(*property)?
+            };
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_long_in(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    values: *const i64,
+    length: u32,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    let values = slice::from_raw_parts(values, length as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = if property.data_type == DataType::Int {
+                let values = values
+                    .iter()
+                    .filter(|v| **v >= i32::MIN as i64 && **v <= i32::MAX as i64)
+                    .map(|v| *v as i32)
+                    .collect();
+                Filter::int_in(*property, values)?
+            } else {
+                Filter::long_in(*property, values.to_vec())?
+            };
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_long_not_in(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    values: *const i64,
+    length: u32,
+    include_null: bool,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    let values = slice::from_raw_parts(values, length as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = if property.data_type == DataType::Int {
+                let values = values
+                    .iter()
+                    .filter(|v| **v >= i32::MIN as i64 && **v <= i32::MAX as i64)
+                    .map(|v| *v as i32)
+                    .collect();
+                Filter::int_not_in(*property, values, include_null)?
+            } else {
+                Filter::long_not_in(*property, values.to_vec(), include_null)?
+            };
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
+unsafe fn list_between_filter(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    lower: i64,
+    upper: i64,
+    all: bool,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = if property.data_type == DataType::IntList {
+                let lower = lower.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+                let upper = upper.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+                if all {
+                    Filter::int_list_all(*property, lower, upper)?
+                } else {
+                    Filter::int_list_any(*property, lower, upper)?
+                }
+            } else if all {
+                Filter::long_list_all(*property, lower, upper)?
+            } else {
+                Filter::long_list_any(*property, lower, upper)?
+            };
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_long_list_any_between(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    lower: i64,
+    upper: i64,
+    property_index: u32,
+) -> i64 {
+    list_between_filter(collection, filter, lower, upper, false, property_index)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_long_list_all_between(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    lower: i64,
+    upper: i64,
+    property_index: u32,
+) -> i64 {
+    list_between_filter(collection, filter, lower, upper, true, property_index)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_string_in(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    values: *const *const c_char,
+    length: u32,
+    case_sensitive: bool,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    let values = slice::from_raw_parts(values, length as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let mut strings = vec![];
+            for value in values {
+                strings.push(from_c_str(*value)?);
+            }
+            let query_filter = Filter::string_in(*property, strings, case_sensitive)?;
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
 unsafe fn get_lower_str(lower: Option<Vec<u8>>, include_lower: bool) -> Option<Vec<u8>> {
     if include_lower {
         lower
@@ -332,3 +632,4 @@ filter_string_ffi!(string_starts_with, isar_filter_string_starts_with);
 filter_string_ffi!(string_ends_with, isar_filter_string_ends_with);
 filter_string_ffi!(string_contains, isar_filter_string_contains);
 filter_string_ffi!(string_matches, isar_filter_string_matches);
+filter_string_ffi!(string_matches_regex, isar_filter_string_regex);
@@ -1,4 +1,8 @@
 use crate::from_c_str;
+use crate::query::{JsonBytes, JsonLen};
+use crate::txn::IsarDartTxn;
+use isar_core::collection::IsarCollection;
+use isar_core::index::checked_index_key::CheckedIndexKey;
 use isar_core::index::index_key::IndexKey;
 use isar_core::object::isar_object::IsarObject;
 use paste::paste;
@@ -104,3 +108,113 @@ macro_rules! hash_list {
 hash_list!(byte, u8);
 hash_list!(int, i32);
 hash_list!(long, i64);
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_index_create_key(
+    collection: &IsarCollection,
+    index_index: u32,
+    key: *mut *mut CheckedIndexKey,
+) -> i64 {
+    isar_try! {
+        let checked_key = collection.new_index_key(index_index as usize)?;
+        key.write(Box::into_raw(Box::new(checked_key)));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn isar_index_key_add_byte(key: &mut CheckedIndexKey, value: u8) -> i64 {
+    isar_try! {
+        key.add_byte(value)?;
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn isar_index_key_add_int(key: &mut CheckedIndexKey, value: i32) -> i64 {
+    isar_try! {
+        key.add_int(value)?;
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn isar_index_key_add_long(key: &mut CheckedIndexKey, value: i64) -> i64 {
+    isar_try! {
+        key.add_long(value)?;
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn isar_index_key_add_float(key: &mut CheckedIndexKey, value: f32) -> i64 {
+    isar_try! {
+        key.add_float(value)?;
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn isar_index_key_add_double(key: &mut CheckedIndexKey, value: f64) -> i64 {
+    isar_try! {
+        key.add_double(value)?;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_index_key_add_string(
+    key: &mut CheckedIndexKey,
+    value: *const c_char,
+) -> i64 {
+    isar_try! {
+        let value = from_c_str(value)?;
+        key.add_string(value)?;
+    }
+}
+
+/// Consumes the checked key and returns the [IndexKey] that can be passed to the index functions.
+#[no_mangle]
+pub unsafe extern "C" fn isar_index_key_finish(
+    key: *mut CheckedIndexKey,
+    index_key: *mut *mut IndexKey,
+) {
+    let key = Box::from_raw(key);
+    index_key.write(Box::into_raw(Box::new(key.finish())));
+}
+
+/// Returns the decoded keys of an index between `lower_key` and `upper_key` as a JSON array
+/// containing one array of values per key. Both keys are consumed.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn isar_index_keys_json(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    index_index: u32,
+    lower_key: *mut IndexKey,
+    upper_key: *mut IndexKey,
+    skip_duplicates: bool,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) -> i64 {
+    let lower_key = *Box::from_raw(lower_key);
+    let upper_key = *Box::from_raw(upper_key);
+    let json = JsonBytes(json_bytes);
+    let json_length = JsonLen(json_length);
+    isar_try_txn!(txn, move |txn| {
+        let json = json;
+        let json_length = json_length;
+        let mut keys = vec![];
+        collection.iter_index_keys(
+            txn,
+            index_index as usize,
+            &lower_key,
+            &upper_key,
+            skip_duplicates,
+            |key| {
+                keys.push(key);
+                Ok(true)
+            },
+        )?;
+        let bytes = serde_json::to_vec(&keys).unwrap();
+        let mut bytes = bytes.into_boxed_slice();
+        json_length.0.write(bytes.len() as u32);
+        json.0.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+        Ok(())
+    })
+}
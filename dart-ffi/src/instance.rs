@@ -1,11 +1,12 @@
 use crate::dart::{dart_post_int, DartPort};
 use crate::error::DartErrCode;
 use crate::from_c_str;
-use crate::txn::run_async;
+use crate::query::{JsonBytes, JsonLen};
+use crate::txn::{run_async, IsarDartTxn};
 use crate::CharsSend;
-use isar_core::collection::IsarCollection;
+use isar_core::collection::{DbStats, IsarCollection};
 use isar_core::error::{illegal_arg, Result};
-use isar_core::instance::IsarInstance;
+use isar_core::instance::{Durability, IsarInstance};
 use isar_core::schema::Schema;
 use std::os::raw::c_char;
 use std::sync::Arc;
@@ -14,12 +15,23 @@ struct IsarInstanceSend(*mut *const IsarInstance);
 
 unsafe impl Send for IsarInstanceSend {}
 
+pub(crate) fn durability_from_index(index: u8) -> Durability {
+    match index {
+        0 => Durability::SafeSync,
+        1 => Durability::NoMetaSync,
+        2 => Durability::NoSync,
+        _ => unreachable!(),
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_create_instance(
     isar: *mut *const IsarInstance,
     name: *const c_char,
     path: *const c_char,
-    relaxed_durability: bool,
+    durability: u8,
+    max_readers: u32,
+    max_size_limit: i64,
     schema_json: *const c_char,
 ) -> i64 {
     let open = || -> Result<()> {
@@ -28,7 +40,16 @@ pub unsafe extern "C" fn isar_create_instance(
         let schema_json = from_c_str(schema_json).unwrap().unwrap();
         let schema = Schema::from_json(schema_json.as_bytes())?;
 
-        let instance = IsarInstance::open(name, path, relaxed_durability, schema)?;
+        let max_readers = if max_readers != 0 {
+            Some(max_readers)
+        } else {
+            None
+        };
+        let durability = durability_from_index(durability);
+        let instance = IsarInstance::open(name, path, durability, max_readers, schema)?;
+        if max_size_limit > 0 {
+            instance.set_max_size_limit(max_size_limit as u64);
+        }
         isar.write(Arc::into_raw(instance));
         Ok(())
     };
@@ -44,7 +65,9 @@ pub unsafe extern "C" fn isar_create_instance_async(
     isar: *mut *const IsarInstance,
     name: *const c_char,
     path: *const c_char,
-    relaxed_durability: bool,
+    durability: u8,
+    max_readers: u32,
+    max_size_limit: i64,
     schema_json: *const c_char,
     port: DartPort,
 ) {
@@ -54,12 +77,53 @@ pub unsafe extern "C" fn isar_create_instance_async(
     let schema_json = CharsSend(schema_json);
     run_async(move || {
         let isar = isar;
-        let result =
-            isar_create_instance(isar.0, name.0, path.0, relaxed_durability, schema_json.0);
+        let result = isar_create_instance(
+            isar.0,
+            name.0,
+            path.0,
+            durability,
+            max_readers,
+            max_size_limit,
+            schema_json.0,
+        );
         dart_post_int(port, result);
     });
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_open_read_only(
+    isar: *mut *const IsarInstance,
+    name: *const c_char,
+    path: *const c_char,
+    max_readers: u32,
+) -> i64 {
+    isar_try! {
+        let name = from_c_str(name).unwrap().unwrap();
+        let path = from_c_str(path).unwrap().unwrap();
+        let max_readers = if max_readers != 0 {
+            Some(max_readers)
+        } else {
+            None
+        };
+        let instance = IsarInstance::open_read_only(name, path, max_readers)?;
+        isar.write(Arc::into_raw(instance));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_reader_check(isar: &IsarInstance, cleared: &mut u32) -> i64 {
+    isar_try! {
+        *cleared = isar.reader_check()?;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_sync(isar: &IsarInstance) -> i64 {
+    isar_try! {
+        isar.sync()?;
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_close_instance(
     isar: *const IsarInstance,
@@ -73,6 +137,22 @@ pub unsafe extern "C" fn isar_close_instance(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_compact(
+    isar: &IsarInstance,
+    target_path: *const c_char,
+    size: &mut i64,
+) -> i64 {
+    isar_try! {
+        let target_path = from_c_str(target_path)?;
+        if let Some(target_path) = target_path {
+            *size = isar.compact(target_path)? as i64;
+        } else {
+            illegal_arg("Target path is required.")?;
+        }
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_get_collection<'a>(
     isar: &'a IsarInstance,
@@ -102,3 +182,134 @@ pub unsafe extern "C" fn isar_get_static_size_and_offsets(
     let property = properties.iter().max_by_key(|(_, p)| p.offset);
     property.map_or(2, |(_, p)| p.offset + p.data_type.get_static_size()) as u32
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_collection_schema_hash(collection: &IsarCollection) -> i64 {
+    collection.schema_hash() as i64
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_collection_schema_changed(collection: &IsarCollection) -> bool {
+    collection.schema_changed()
+}
+
+/// Writes the schema of the collection as JSON. The bytes have to be freed using
+/// [isar_free_json](crate::query::isar_free_json).
+#[no_mangle]
+pub unsafe extern "C" fn isar_collection_get_schema(
+    collection: &IsarCollection,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) {
+    let bytes = serde_json::to_vec(collection.get_schema()).unwrap();
+    let mut bytes = bytes.into_boxed_slice();
+    json_length.write(bytes.len() as u32);
+    json_bytes.write(bytes.as_mut_ptr());
+    std::mem::forget(bytes);
+}
+
+#[repr(C)]
+pub struct RawDbStats {
+    pub entries: u64,
+    pub pages: u64,
+    pub collection_index: u32,
+    // 0 = data, 1 = index, 2 = link
+    pub db_type: u32,
+    pub db_index: u32,
+    pub depth: u32,
+}
+
+impl RawDbStats {
+    fn new(collection_index: usize, db_type: u32, db_index: usize, stats: DbStats) -> Self {
+        RawDbStats {
+            entries: stats.entries,
+            pages: stats.pages,
+            collection_index: collection_index as u32,
+            db_type,
+            db_index: db_index as u32,
+            depth: stats.depth,
+        }
+    }
+}
+
+struct RawDbStatsSend(*mut *mut RawDbStats);
+
+unsafe impl Send for RawDbStatsSend {}
+
+struct RawDbStatsLenSend(*mut u32);
+
+unsafe impl Send for RawDbStatsLenSend {}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_db_stats(
+    isar: &'static IsarInstance,
+    txn: &mut IsarDartTxn,
+    stats: *mut *mut RawDbStats,
+    length: *mut u32,
+) -> i64 {
+    let stats = RawDbStatsSend(stats);
+    let length = RawDbStatsLenSend(length);
+    isar_try_txn!(txn, move |txn| {
+        let stats = stats;
+        let length = length;
+        let mut raw_stats = vec![];
+        for (col_index, col_stats) in isar.get_db_stats(txn)?.into_iter().enumerate() {
+            raw_stats.push(RawDbStats::new(col_index, 0, 0, col_stats.data));
+            for (index_index, (_, index_stats)) in col_stats.indexes.into_iter().enumerate() {
+                raw_stats.push(RawDbStats::new(col_index, 1, index_index, index_stats));
+            }
+            for (link_index, (_, link_stats)) in col_stats.links.into_iter().enumerate() {
+                raw_stats.push(RawDbStats::new(col_index, 2, link_index, link_stats));
+            }
+        }
+        let mut raw_stats = raw_stats.into_boxed_slice();
+        length.0.write(raw_stats.len() as u32);
+        stats.0.write(raw_stats.as_mut_ptr());
+        std::mem::forget(raw_stats);
+        Ok(())
+    })
+}
+
+#[repr(C)]
+pub struct RawInstanceMetrics {
+    pub write_txns_committed: u64,
+    pub write_txns_aborted: u64,
+    pub read_txns_opened: u64,
+    pub write_lock_micros: u64,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_metrics(isar: &IsarInstance, metrics: &mut RawInstanceMetrics) {
+    let instance_metrics = isar.metrics();
+    metrics.write_txns_committed = instance_metrics.write_txns_committed;
+    metrics.write_txns_aborted = instance_metrics.write_txns_aborted;
+    metrics.read_txns_opened = instance_metrics.read_txns_opened;
+    metrics.write_lock_micros = instance_metrics.write_lock_micros;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_verify(
+    isar: &'static IsarInstance,
+    txn: &mut IsarDartTxn,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) -> i64 {
+    let json = JsonBytes(json_bytes);
+    let json_length = JsonLen(json_length);
+    isar_try_txn!(txn, move |txn| {
+        let json = json;
+        let json_length = json_length;
+        let issues = isar.verify(txn)?;
+        let bytes = serde_json::to_vec(&issues).unwrap();
+        let mut bytes = bytes.into_boxed_slice();
+        json_length.0.write(bytes.len() as u32);
+        json.0.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_free_db_stats(stats: *mut RawDbStats, length: u32) {
+    Vec::from_raw_parts(stats, length as usize, length as usize);
+}
@@ -36,6 +36,10 @@ pub struct UintSend(&'static mut u32);
 
 unsafe impl Send for UintSend {}
 
+pub struct LongSend(&'static mut i64);
+
+unsafe impl Send for LongSend {}
+
 pub struct BoolSend(&'static mut bool);
 
 unsafe impl Send for BoolSend {}
@@ -1,4 +1,6 @@
+use crate::raw_object_set::{RawObject, RawObjectSet};
 use crate::txn::IsarDartTxn;
+use crate::{LongSend, UintSend};
 use isar_core::collection::IsarCollection;
 use isar_core::error::Result;
 
@@ -16,6 +18,24 @@ pub unsafe extern "C" fn isar_link(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_link_all(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    link_index: u32,
+    id: i64,
+    target_ids: *const i64,
+    target_ids_length: u32,
+    count: &'static mut u32,
+) -> i64 {
+    let target_ids = std::slice::from_raw_parts(target_ids, target_ids_length as usize);
+    let count = UintSend(count);
+    isar_try_txn!(txn, move |txn| {
+        *count.0 = collection.link_all(txn, link_index as usize, id, target_ids)?;
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_link_unlink(
     collection: &'static IsarCollection,
@@ -43,11 +63,11 @@ pub unsafe extern "C" fn isar_link_update_all(
 ) -> i64 {
     let ids = std::slice::from_raw_parts(ids, (link_count + unlink_count) as usize);
     isar_try_txn!(txn, move |txn| {
+        let link_ids = &ids[..link_count as usize];
         if replace {
-            collection.unlink_all(txn, link_index as usize, id)?;
-        }
-        for target_id in ids.iter().take(link_count as usize) {
-            collection.link(txn, link_index as usize, id, *target_id)?;
+            collection.replace_links(txn, link_index as usize, id, link_ids)?;
+        } else {
+            collection.link_all(txn, link_index as usize, id, link_ids)?;
         }
         for target_id in ids
             .iter()
@@ -59,3 +79,41 @@ pub unsafe extern "C" fn isar_link_update_all(
         Ok(())
     })
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_link_count(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    link_index: u32,
+    backlink: bool,
+    id: i64,
+    count: &'static mut i64,
+) -> i64 {
+    let count = LongSend(count);
+    isar_try_txn!(txn, move |txn| {
+        *count.0 = collection.link_count(txn, link_index as usize, backlink, id)? as i64;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_backlinks(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    link_index: u32,
+    target_id: i64,
+    result: &'static mut RawObjectSet,
+) -> i64 {
+    isar_try_txn!(txn, move |txn| {
+        let mut objects = vec![];
+        collection.get_backlinks(txn, link_index as usize, target_id, |id, object| {
+            let mut raw_obj = RawObject::new();
+            raw_obj.set_id(id);
+            raw_obj.set_object(Some(object));
+            objects.push(raw_obj);
+            true
+        })?;
+        result.fill_from_vec(objects);
+        Ok(())
+    })
+}
@@ -1,13 +1,16 @@
 use super::raw_object_set::{RawObject, RawObjectSet};
 use crate::txn::IsarDartTxn;
-use crate::{from_c_str, UintSend};
+use crate::{from_c_str, BoolSend, LongSend, UintSend};
 use isar_core::collection::IsarCollection;
 use isar_core::error::illegal_arg;
 use isar_core::index::index_key::IndexKey;
+use isar_core::object::isar_object::IsarObject;
 use isar_core::query::filter::Filter;
 use isar_core::query::query_builder::QueryBuilder;
 use isar_core::query::{Query, Sort};
 use std::os::raw::c_char;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 #[no_mangle]
 pub extern "C" fn isar_qb_create(collection: &IsarCollection) -> *mut QueryBuilder {
@@ -26,6 +29,23 @@ pub unsafe extern "C" fn isar_qb_add_id_where_clause(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_add_sorted_id_where_clause(
+    builder: &mut QueryBuilder,
+    lower_id: i64,
+    upper_id: i64,
+    asc: bool,
+) -> i64 {
+    let sort = if asc {
+        Sort::Ascending
+    } else {
+        Sort::Descending
+    };
+    isar_try! {
+        builder.add_sorted_id_where_clause(lower_id, upper_id, sort)?;
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_add_index_where_clause(
     builder: &mut QueryBuilder,
@@ -50,6 +70,46 @@ pub unsafe extern "C" fn isar_qb_add_index_where_clause(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_add_index_prefix_where_clause(
+    builder: &mut QueryBuilder,
+    index_index: u32,
+    prefix: *mut IndexKey,
+    start_key: *mut IndexKey,
+    include_start: bool,
+    end_key: *mut IndexKey,
+    include_end: bool,
+    skip_duplicates: bool,
+) -> i64 {
+    let prefix = *Box::from_raw(prefix);
+    let start_key = *Box::from_raw(start_key);
+    let end_key = *Box::from_raw(end_key);
+    isar_try! {
+        builder.add_index_prefix_where_clause(
+            index_index as usize,
+            &prefix,
+            start_key,
+            include_start,
+            end_key,
+            include_end,
+            skip_duplicates,
+        )?;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_add_string_prefix_where_clause(
+    builder: &mut QueryBuilder,
+    index_index: u32,
+    prefix: *const c_char,
+    skip_duplicates: bool,
+) -> i64 {
+    isar_try! {
+        let prefix = from_c_str(prefix)?.unwrap_or("");
+        builder.add_string_prefix_where_clause(index_index as usize, prefix, skip_duplicates)?;
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_add_link_where_clause(
     builder: &mut QueryBuilder,
@@ -134,6 +194,16 @@ pub unsafe extern "C" fn isar_qb_set_offset_limit(
     builder.set_limit(limit);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_set_max_results(builder: &mut QueryBuilder, max_results: i64) {
+    let max_results = if max_results < 0 {
+        usize::MAX
+    } else {
+        max_results as usize
+    };
+    builder.set_max_results(max_results);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_build(builder: *mut QueryBuilder) -> *mut Query {
     let query = Box::from_raw(builder).build();
@@ -155,7 +225,7 @@ pub unsafe extern "C" fn isar_q_find(
     isar_try_txn!(txn, move |txn| {
         let mut objects = vec![];
         let mut count = 0;
-        query.find_while(txn, |id, object| {
+        query.find_all(txn, |id, object| {
             let mut raw_obj = RawObject::new();
             raw_obj.set_id(id);
             raw_obj.set_object(Some(object));
@@ -169,6 +239,176 @@ pub unsafe extern "C" fn isar_q_find(
     })
 }
 
+/// Like [isar_q_find] but writes a handle to `cancel_handle` that can be passed to
+/// [isar_q_cancel] while the query is running. The handle has to be freed using
+/// [isar_q_free_cancel_handle].
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_find_all_async(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    result: &'static mut RawObjectSet,
+    limit: u32,
+    cancel_handle: *mut *const AtomicBool,
+) -> i64 {
+    let cancel = Arc::new(AtomicBool::new(false));
+    cancel_handle.write(Arc::into_raw(cancel.clone()));
+    isar_try_txn!(txn, move |txn| {
+        let mut objects = vec![];
+        let mut count = 0;
+        query.find_all_cancellable(txn, &cancel, |id, object| {
+            let mut raw_obj = RawObject::new();
+            raw_obj.set_id(id);
+            raw_obj.set_object(Some(object));
+            objects.push(raw_obj);
+            count += 1;
+            count < limit
+        })?;
+
+        result.fill_from_vec(objects);
+        Ok(())
+    })
+}
+
+/// Receives a batch of `length` objects. The objects are only valid until the transaction is
+/// committed or aborted.
+pub type FindBatchFn = extern "C" fn(objects: *const RawObject, length: u32);
+
+/// Passes the query results to `callback` in batches of `batch_size` objects so they can be
+/// read with one call per batch. The callback is invoked on the thread of the transaction so
+/// this function must only be used with synchronous transactions.
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_find_all_batched(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    batch_size: u32,
+    callback: FindBatchFn,
+) -> i64 {
+    isar_try_txn!(txn, move |txn| {
+        query.find_all_batched(txn, batch_size as usize, |batch| {
+            let objects = batch
+                .iter()
+                .map(|(id, object)| {
+                    let mut raw_obj = RawObject::new();
+                    raw_obj.set_id(*id);
+                    raw_obj.set_object(Some(*object));
+                    raw_obj
+                })
+                .collect::<Vec<_>>();
+            callback(objects.as_ptr(), objects.len() as u32);
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_cancel(cancel_handle: *const AtomicBool) {
+    (*cancel_handle).store(true, Ordering::SeqCst);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_free_cancel_handle(cancel_handle: *const AtomicBool) {
+    let _ = Arc::from_raw(cancel_handle);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_find_after(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    result: &'static mut RawObjectSet,
+    last_id: i64,
+    limit: u32,
+) -> i64 {
+    isar_try_txn!(txn, move |txn| {
+        let objects = query
+            .find_after(txn, last_id, limit as usize)?
+            .into_iter()
+            .map(|(id, object)| {
+                let mut raw_obj = RawObject::new();
+                raw_obj.set_id(id);
+                raw_obj.set_object(Some(object));
+                raw_obj
+            })
+            .collect();
+        result.fill_from_vec(objects);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_find_first(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    object: &'static mut RawObject,
+) -> i64 {
+    isar_try_txn!(txn, move |txn| {
+        if let Some((id, result)) = query.find_first(txn)? {
+            object.set_id(id);
+            object.set_object(Some(result));
+        } else {
+            object.set_object(None);
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_any(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    any: &'static mut bool,
+) -> i64 {
+    let any = BoolSend(any);
+    isar_try_txn!(txn, move |txn| {
+        *any.0 = query.any(txn)?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_find_all_property(
+    collection: &'static IsarCollection,
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    property_index: u32,
+    result: &'static mut RawObjectSet,
+    limit: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try_txn!(txn, move |txn| {
+        let property = if let Some((_, property)) = property {
+            *property
+        } else {
+            return illegal_arg("Property does not exist.");
+        };
+        if limit == 0 {
+            result.fill_from_vec(vec![]);
+            return Ok(());
+        }
+
+        // all values are copied into one buffer which is only leaked once the query succeeded
+        let mut bytes = vec![];
+        let mut entries = vec![];
+        query.find_all_property(txn, property, |id, object| {
+            let start = bytes.len();
+            bytes.extend_from_slice(object.as_bytes());
+            entries.push((id, start, bytes.len()));
+            entries.len() < limit as usize
+        })?;
+
+        let bytes = result.fill_buffer(bytes);
+        let objects = entries
+            .into_iter()
+            .map(|(id, start, end)| {
+                let mut raw_obj = RawObject::new();
+                raw_obj.set_id(id);
+                raw_obj.set_object(Some(IsarObject::from_bytes(&bytes[start..end])));
+                raw_obj
+            })
+            .collect();
+        result.fill_from_vec(objects);
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_q_delete(
     query: &'static Query,
@@ -193,10 +433,24 @@ pub unsafe extern "C" fn isar_q_delete(
     })
 }
 
-struct JsonBytes(*mut *mut u8);
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_delete_all(
+    query: &'static Query,
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    count: &'static mut i64,
+) -> i64 {
+    let count = LongSend(count);
+    isar_try_txn!(txn, move |txn| {
+        *count.0 = query.delete_all(txn, collection)? as i64;
+        Ok(())
+    })
+}
+
+pub(crate) struct JsonBytes(pub *mut *mut u8);
 unsafe impl Send for JsonBytes {}
 
-struct JsonLen(*mut u32);
+pub(crate) struct JsonLen(pub *mut u32);
 unsafe impl Send for JsonLen {}
 
 #[no_mangle]
@@ -224,6 +478,19 @@ pub unsafe extern "C" fn isar_q_export_json(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_explain(
+    query: &Query,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) {
+    let bytes = query.explain().to_json().into_bytes();
+    let mut bytes = bytes.into_boxed_slice();
+    json_length.write(bytes.len() as u32);
+    json_bytes.write(bytes.as_mut_ptr());
+    std::mem::forget(bytes);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_free_json(json_bytes: *mut u8, json_length: u32) {
     Vec::from_raw_parts(json_bytes, json_length as usize, json_length as usize);
@@ -36,6 +36,29 @@ impl AggregationOp {
             _ => unreachable!(),
         }
     }
+
+    fn supports(&self, data_type: DataType) -> bool {
+        match self {
+            AggregationOp::Min | AggregationOp::Max => matches!(
+                data_type,
+                DataType::Int
+                    | DataType::Long
+                    | DataType::DateTime
+                    | DataType::Decimal
+                    | DataType::Float
+                    | DataType::Double
+            ),
+            AggregationOp::Sum | AggregationOp::Average => matches!(
+                data_type,
+                DataType::Int
+                    | DataType::Long
+                    | DataType::Decimal
+                    | DataType::Float
+                    | DataType::Double
+            ),
+            AggregationOp::Count => true,
+        }
+    }
 }
 
 fn aggregate(
@@ -74,7 +97,7 @@ fn aggregate(
         count += 1;
         match op {
             AggregationOp::Min | AggregationOp::Max => match property.data_type {
-                DataType::Int | DataType::Long => {
+                DataType::Int | DataType::Long | DataType::DateTime | DataType::Decimal => {
                     let value = if property.data_type == DataType::Int {
                         obj.read_int(property) as i64
                     } else {
@@ -102,7 +125,9 @@ fn aggregate(
                 DataType::Int => {
                     long_value = long_value.saturating_add(obj.read_int(property) as i64)
                 }
-                DataType::Long => long_value = long_value.saturating_add(obj.read_long(property)),
+                DataType::Long | DataType::Decimal => {
+                    long_value = long_value.saturating_add(obj.read_long(property))
+                }
                 DataType::Float => double_value += obj.read_float(property) as f64,
                 DataType::Double => double_value += obj.read_double(property),
                 _ => unreachable!(),
@@ -123,16 +148,14 @@ fn aggregate(
 
     let result = match op {
         AggregationOp::Average => {
-            let result = match property.unwrap().data_type {
-                DataType::Int | DataType::Long => (long_value as f64) / (count as f64),
-                DataType::Float | DataType::Double => double_value / (count as f64),
-                _ => unreachable!(),
-            };
+            let result = average(property.unwrap(), long_value, double_value, count);
             AggregationResult::Double(result)
         }
         AggregationOp::Count => AggregationResult::Long(count as i64),
         _ => match property.unwrap().data_type {
-            DataType::Int | DataType::Long => AggregationResult::Long(long_value),
+            DataType::Int | DataType::Long | DataType::DateTime | DataType::Decimal => {
+                AggregationResult::Long(long_value)
+            }
             DataType::Float | DataType::Double => AggregationResult::Double(double_value),
             _ => unreachable!(),
         },
@@ -141,6 +164,16 @@ fn aggregate(
     Ok(result)
 }
 
+fn average(property: Property, long_sum: i64, double_sum: f64, count: usize) -> f64 {
+    match property.data_type {
+        DataType::Int | DataType::Long => (long_sum as f64) / (count as f64),
+        // decimals are summed as scaled integers
+        DataType::Decimal => (long_sum as f64) / 10f64.powi(property.scale as i32) / (count as f64),
+        DataType::Float | DataType::Double => double_sum / (count as f64),
+        _ => unreachable!(),
+    }
+}
+
 pub struct AggregationResultSend(*mut *const AggregationResult);
 
 unsafe impl Send for AggregationResultSend {}
@@ -165,6 +198,11 @@ pub unsafe extern "C" fn isar_q_aggregate(
         if op != AggregationOp::Count && property.is_none() {
             return illegal_arg("Property does not exist.");
         }
+        if let Some(property) = property {
+            if !op.supports(property.data_type) {
+                return illegal_arg("Aggregation is not supported for this property type.");
+            }
+        }
         let aggregate_result = aggregate(query, txn, op, property)?;
         result.0.write(Box::into_raw(Box::new(aggregate_result)));
         Ok(())
@@ -188,3 +226,22 @@ pub unsafe extern "C" fn isar_q_aggregate_double_result(result: &AggregationResu
         AggregationResult::Null => IsarObject::NULL_DOUBLE,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_decimal() {
+        let property = Property {
+            data_type: DataType::Decimal,
+            offset: 2,
+            scale: 2,
+        };
+        // 10.50 and 2.50
+        assert_eq!(average(property, 1050 + 250, 0.0, 2), 6.5);
+
+        let property = Property::new(DataType::Decimal, 2);
+        assert_eq!(average(property, 3, 0.0, 2), 1.5);
+    }
+}
@@ -33,6 +33,8 @@ impl RawObject {
         self.id = id;
     }
 
+    /// Stores a pointer to the object bytes without copying them. The buffer belongs to the
+    /// transaction and must not be accessed after the transaction has been committed or aborted.
     pub fn set_object(&mut self, object: Option<IsarObject>) {
         if let Some(object) = object {
             let bytes = object.as_bytes();
@@ -51,6 +53,8 @@ impl RawObject {
 pub struct RawObjectSet {
     objects: *mut RawObject,
     length: u32,
+    buffer: *mut u8,
+    buffer_length: u32,
 }
 
 unsafe impl Send for RawObjectSet {}
@@ -71,6 +75,28 @@ impl RawObjectSet {
     pub fn get_length(&self) -> usize {
         self.length as usize
     }
+
+    /// Leaks `buffer` and stores it in the set so the objects can point into it. The buffer is
+    /// freed by [isar_free_raw_property_list].
+    pub fn fill_buffer(&mut self, buffer: Vec<u8>) -> &'static [u8] {
+        let buffer = Box::leak(buffer.into_boxed_slice());
+        self.buffer = buffer.as_mut_ptr();
+        self.buffer_length = buffer.len() as u32;
+        buffer
+    }
+}
+
+/// Frees a list returned by [isar_q_find_all_property](crate::query::isar_q_find_all_property).
+/// The values of all objects are stored in the buffer of the set.
+#[no_mangle]
+pub unsafe extern "C" fn isar_free_raw_property_list(ros: &mut RawObjectSet) {
+    if !ros.buffer.is_null() {
+        let buffer = slice::from_raw_parts_mut(ros.buffer, ros.buffer_length as usize);
+        drop(Box::from_raw(buffer));
+        ros.buffer = ptr::null_mut();
+        ros.buffer_length = 0;
+    }
+    isar_free_raw_obj_list(ros);
 }
 
 #[no_mangle]
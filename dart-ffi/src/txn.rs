@@ -1,13 +1,17 @@
 use crate::error::DartErrCode;
+use crate::instance::durability_from_index;
 use isar_core::error::{IsarError, Result};
 use isar_core::instance::IsarInstance;
+use isar_core::snapshot::IsarSnapshot;
 use isar_core::txn::IsarTxn;
 use once_cell::sync::Lazy;
 use std::borrow::BorrowMut;
+use std::mem::ManuallyDrop;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 use threadpool::{Builder, ThreadPool};
 use crate::dart::{dart_post_int, DartPort};
 
@@ -30,9 +34,33 @@ pub unsafe extern "C" fn isar_txn_begin(
 ) -> i64 {
     isar_try! {
         let new_txn = if sync {
-            IsarDartTxn::begin_sync(isar, write, silent)?
+            IsarDartTxn::begin_sync(isar, write, silent, None)?
         } else {
-            IsarDartTxn::begin_async(isar, write, silent, port)
+            IsarDartTxn::begin_async(isar, write, silent, None, port)
+        };
+        let txn_ptr = Box::into_raw(Box::new(new_txn));
+        txn.write(txn_ptr);
+    }
+}
+
+/// Like `isar_txn_begin` but fails if the write lock cannot be acquired within `timeout_ms`
+/// milliseconds.
+#[no_mangle]
+pub unsafe extern "C" fn isar_begin_txn_timeout(
+    isar: &'static IsarInstance,
+    txn: *mut *const IsarDartTxn,
+    sync: bool,
+    write: bool,
+    silent: bool,
+    timeout_ms: u32,
+    port: DartPort,
+) -> i64 {
+    let timeout = Some(Duration::from_millis(timeout_ms as u64));
+    isar_try! {
+        let new_txn = if sync {
+            IsarDartTxn::begin_sync(isar, write, silent, timeout)?
+        } else {
+            IsarDartTxn::begin_async(isar, write, silent, timeout, port)
         };
         let txn_ptr = Box::into_raw(Box::new(new_txn));
         txn.write(txn_ptr);
@@ -47,6 +75,30 @@ pub unsafe extern "C" fn isar_txn_finish(txn: *mut IsarDartTxn, commit: bool) ->
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_txn_set_durability(txn: &mut IsarDartTxn, durability: u8) -> i64 {
+    let durability = durability_from_index(durability);
+    isar_try_txn!(txn, move |txn| txn.set_durability(durability))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_begin_snapshot(
+    isar: *const IsarInstance,
+    txn: *mut *const IsarDartTxn,
+) -> i64 {
+    let isar = ManuallyDrop::new(Arc::from_raw(isar));
+    isar_try! {
+        let snapshot = isar.begin_snapshot()?;
+        let txn_ptr = Box::into_raw(Box::new(IsarDartTxn::Snapshot { snapshot }));
+        txn.write(txn_ptr);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_close_snapshot(txn: *mut IsarDartTxn) -> i64 {
+    isar_txn_finish(txn, false)
+}
+
 pub struct IsarTxnSend(IsarTxn<'static>);
 
 unsafe impl Send for IsarTxnSend {}
@@ -60,12 +112,33 @@ pub enum IsarDartTxn {
         port: DartPort,
         txn: Arc<Mutex<Option<IsarTxnSend>>>,
     },
+    Snapshot {
+        snapshot: IsarSnapshot,
+    },
 }
 
 impl IsarDartTxn {
-    fn begin_sync(isar: &'static IsarInstance, write: bool, silent: bool) -> Result<IsarDartTxn> {
+    fn begin_txn(
+        isar: &'static IsarInstance,
+        write: bool,
+        silent: bool,
+        timeout: Option<Duration>,
+    ) -> Result<IsarTxn<'static>> {
+        if let Some(timeout) = timeout {
+            isar.begin_txn_timeout(write, silent, timeout)
+        } else {
+            isar.begin_txn(write, silent)
+        }
+    }
+
+    fn begin_sync(
+        isar: &'static IsarInstance,
+        write: bool,
+        silent: bool,
+        timeout: Option<Duration>,
+    ) -> Result<IsarDartTxn> {
         let sync_txn = IsarDartTxn::Sync {
-            txn: Some(isar.begin_txn(write, silent)?),
+            txn: Some(Self::begin_txn(isar, write, silent, timeout)?),
         };
         Ok(sync_txn)
     }
@@ -74,13 +147,14 @@ impl IsarDartTxn {
         isar: &'static IsarInstance,
         write: bool,
         silent: bool,
+        timeout: Option<Duration>,
         port: DartPort,
     ) -> IsarDartTxn {
         let (tx, rx): (Sender<AsyncJob>, Receiver<AsyncJob>) = mpsc::channel();
         let txn = Arc::new(Mutex::new(None));
         let txn_clone = txn.clone();
         run_async(move || {
-            let new_txn = isar.begin_txn(write, silent);
+            let new_txn = Self::begin_txn(isar, write, silent, timeout);
             match new_txn {
                 Ok(new_txn) => {
                     txn_clone.lock().unwrap().replace(IsarTxnSend(new_txn));
@@ -143,6 +217,7 @@ impl IsarDartTxn {
                 IsarDartTxn::exec_async_internal(job, *port, tx.clone(), false);
                 Ok(())
             }
+            IsarDartTxn::Snapshot { snapshot } => job(&mut **snapshot),
         }
     }
 
@@ -178,6 +253,10 @@ impl IsarDartTxn {
                 IsarDartTxn::exec_async_internal(job, port, tx.clone(), true);
                 Ok(())
             }
+            IsarDartTxn::Snapshot { snapshot } => {
+                snapshot.close();
+                Ok(())
+            }
         }
     }
 }
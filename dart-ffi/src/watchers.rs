@@ -29,8 +29,8 @@ pub unsafe extern "C" fn isar_watch_object(
     let handle = isar.watch_object(
         collection,
         id,
-        Box::new(move || {
-            dart_post_int(port, 1);
+        Box::new(move |deleted| {
+            dart_post_int(port, if deleted { 2 } else { 1 });
         }),
     );
     Box::into_raw(Box::new(handle))
@@ -1,7 +1,10 @@
 use crate::cursor::IsarCursors;
-use crate::error::{IsarError, Result};
+use crate::error::{illegal_arg, IsarError, Result};
 use crate::id_key::IdKey;
+use crate::index::checked_index_key::CheckedIndexKey;
 use crate::index::index_key::IndexKey;
+use crate::index::index_key_builder::IndexKeyBuilder;
+use crate::index::index_key_decoder::IndexKeyValue;
 use crate::index::IsarIndex;
 use crate::link::IsarLink;
 use crate::mdbx::db::Db;
@@ -10,11 +13,78 @@ use crate::object::isar_object::{IsarObject, Property};
 use crate::object::json_encode_decode::JsonEncodeDecode;
 use crate::object::object_builder::ObjectBuilder;
 use crate::query::query_builder::QueryBuilder;
+use crate::query::where_clause::WhereClause;
+use crate::schema::collection_schema::CollectionSchemaInfo;
 use crate::txn::IsarTxn;
 use crate::watch::change_set::ChangeSet;
+use itertools::Itertools;
+use serde::Serialize;
 use serde_json::Value;
 use std::cell::Cell;
 use std::collections::HashSet;
+use std::io::Read;
+
+pub use crate::mdbx::db::DbStats;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CollectionStats {
+    pub name: String,
+    pub data: DbStats,
+    pub indexes: Vec<(String, DbStats)>,
+    pub links: Vec<(String, DbStats)>,
+}
+
+/// An inconsistency between the objects of a collection and its index or link databases.
+#[derive(Serialize, Clone, Eq, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum IntegrityIssue {
+    /// An index entry points to an object that does not exist.
+    DanglingIndexEntry {
+        collection: String,
+        index: String,
+        id: i64,
+    },
+    /// An index entry has a key that the object it points to does not generate.
+    StaleIndexEntry {
+        collection: String,
+        index: String,
+        id: i64,
+    },
+    /// An object is not stored under one of the keys it generates for an index.
+    MissingIndexEntry {
+        collection: String,
+        index: String,
+        id: i64,
+    },
+    /// A link exists without the matching backlink.
+    #[serde(rename_all = "camelCase")]
+    MissingBacklink {
+        collection: String,
+        link: String,
+        source_id: i64,
+        target_id: i64,
+    },
+}
+
+/// Number of objects created and replaced by a JSON import.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct ImportStats {
+    /// Objects whose id did not exist before.
+    pub inserted: u64,
+    /// Objects that replaced an existing object with the same id.
+    pub replaced: u64,
+}
+
+/// Determines what happens when an object violates a unique index.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum OnConflict {
+    /// Delete the existing objects.
+    Replace,
+    /// Fail with [IsarError::UniqueViolation]. The transaction must not be used afterwards.
+    Abort,
+    /// Keep the existing object and skip the new one. The id of the existing object is returned.
+    Ignore,
+}
 
 pub struct IsarCollection {
     pub name: String,
@@ -22,6 +92,9 @@ pub struct IsarCollection {
     props: Vec<Property>,
 
     pub(crate) instance_id: u64,
+    schema_hash: u64,
+    previous_schema_hash: Option<u64>,
+    schema: CollectionSchemaInfo,
     pub(crate) db: Db,
     pub(crate) indexes: Vec<(String, IsarIndex)>,
     pub(crate) links: Vec<(String, IsarLink)>, // links from this collection
@@ -38,6 +111,9 @@ impl IsarCollection {
         db: Db,
         instance_id: u64,
         name: String,
+        schema_hash: u64,
+        previous_schema_hash: Option<u64>,
+        schema: CollectionSchemaInfo,
         properties: Vec<(String, Property)>,
         indexes: Vec<(String, IsarIndex)>,
         links: Vec<(String, IsarLink)>,
@@ -45,6 +121,9 @@ impl IsarCollection {
         let props = properties.iter().map(|(_, p)| *p).collect();
         IsarCollection {
             instance_id,
+            schema_hash,
+            previous_schema_hash,
+            schema,
             db,
             name,
             properties,
@@ -55,6 +134,31 @@ impl IsarCollection {
         }
     }
 
+    /// Hash of the properties, indexes and links this collection was opened with. It is stable
+    /// across versions and changes whenever the schema of the collection changes, including the
+    /// order of its properties.
+    pub fn schema_hash(&self) -> u64 {
+        self.schema_hash
+    }
+
+    /// Hash that was stored for this collection before it was opened or `None` if the collection
+    /// did not exist yet.
+    pub fn previous_schema_hash(&self) -> Option<u64> {
+        self.previous_schema_hash
+    }
+
+    /// Whether the schema of the collection changed since it was last opened. New collections
+    /// count as changed.
+    pub fn schema_changed(&self) -> bool {
+        self.previous_schema_hash != Some(self.schema_hash)
+    }
+
+    /// Returns the properties with their types and offsets, the indexes and the links this
+    /// collection was opened with.
+    pub fn get_schema(&self) -> &CollectionSchemaInfo {
+        &self.schema
+    }
+
     pub fn new_object_builder(&self, buffer: Option<Vec<u8>>) -> ObjectBuilder {
         ObjectBuilder::new(&self.props, buffer)
     }
@@ -72,12 +176,22 @@ impl IsarCollection {
         Ok(())
     }
 
+    pub(crate) fn get_auto_increment(&self) -> i64 {
+        self.auto_increment.get()
+    }
+
+    pub(crate) fn set_auto_increment(&self, id: i64) {
+        self.auto_increment.set(id);
+    }
+
     pub(crate) fn update_auto_increment(&self, id: i64) {
         if id > self.auto_increment.get() {
             self.auto_increment.set(id);
         }
     }
 
+    /// Returns the next sequential id. The counter starts after the largest id in the collection
+    /// when the instance is opened and skips ids that were supplied by the user.
     pub fn auto_increment(&self, _: &mut IsarTxn) -> Result<i64> {
         self.auto_increment_internal()
     }
@@ -107,13 +221,99 @@ impl IsarCollection {
         })
     }
 
+    /// Checks whether an object with the given id exists without deserializing it.
+    pub fn exists(&self, txn: &mut IsarTxn, id: i64) -> Result<bool> {
+        txn.read(self.instance_id, |cursors| {
+            let mut cursor = cursors.get_cursor(self.db)?;
+            let id_key = IdKey::new(id);
+            Ok(cursor.move_to(id_key.as_bytes())?.is_some())
+        })
+    }
+
+    /// Checks whether an object with the given key exists in the index.
+    pub fn exists_by_index(
+        &self,
+        txn: &mut IsarTxn,
+        index_index: usize,
+        key: &IndexKey,
+    ) -> Result<bool> {
+        let index = self.get_index_by_index(index_index)?;
+        txn.read(self.instance_id, |cursors| {
+            Ok(index.get_id(cursors, key)?.is_some())
+        })
+    }
+
+    /// Iterates the decoded keys of an index between `lower` and `upper` without reading the
+    /// objects. With `skip_duplicates`, every key is returned once which allows listing the
+    /// distinct values of the indexed properties cheaply.
+    pub fn iter_index_keys(
+        &self,
+        txn: &mut IsarTxn,
+        index_index: usize,
+        lower: &IndexKey,
+        upper: &IndexKey,
+        skip_duplicates: bool,
+        mut callback: impl FnMut(Vec<IndexKeyValue>) -> Result<bool>,
+    ) -> Result<()> {
+        let index = self.get_index_by_index(index_index)?;
+        let decoder = index.new_key_decoder();
+        txn.read(self.instance_id, |cursors| {
+            index.iter_keys(cursors, lower, upper, skip_duplicates, |key| {
+                callback(decoder.decode(key)?)
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Returns the raw bytes of an object without copying them. The slice points directly into the
+    /// memory map of the database and is only valid while the transaction is active which the
+    /// borrow of `txn` guarantees. The bytes must not be modified.
+    pub fn get_bytes<'txn>(&self, txn: &'txn mut IsarTxn, id: i64) -> Result<Option<&'txn [u8]>> {
+        let object = self.get(txn, id)?;
+        Ok(object.map(|o| o.as_bytes()))
+    }
+
+    /// Looks up multiple objects using a single cursor. The callback is called with the object or
+    /// `None` for every id in order until it returns `false`.
+    pub fn get_all<'txn, F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        ids: &[i64],
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(i64, Option<IsarObject<'txn>>) -> bool,
+    {
+        txn.read(self.instance_id, |cursors| {
+            let mut cursor = cursors.get_cursor(self.db)?;
+            for id in ids {
+                let id_key = IdKey::new(*id);
+                let object = cursor
+                    .move_to(id_key.as_bytes())?
+                    .map(|(_, v)| IsarObject::from_bytes(v));
+                if !callback(*id, object) {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+
     pub(crate) fn get_index_by_index(&self, index_index: usize) -> Result<&IsarIndex> {
         self.indexes
             .get(index_index)
             .map(|(_, i)| i)
+            .filter(|i| !i.is_removed())
             .ok_or(IsarError::UnknownIndex {})
     }
 
+    /// Creates an empty key for the given index that validates its components against the index
+    /// properties.
+    pub fn new_index_key(&self, index_index: usize) -> Result<CheckedIndexKey> {
+        let index = self.get_index_by_index(index_index)?;
+        Ok(CheckedIndexKey::new(index.properties.clone()))
+    }
+
     pub fn get_by_index<'txn>(
         &self,
         txn: &'txn mut IsarTxn,
@@ -124,17 +324,44 @@ impl IsarCollection {
         txn.read(self.instance_id, |cursors| {
             if let Some(id_key) = index.get_id(cursors, key)? {
                 let mut cursor = cursors.get_cursor(self.db)?;
+                let entry = cursor.move_to(id_key.as_bytes())?;
+                let (_, bytes) = entry.ok_or(IsarError::DbCorrupted {
+                    message: "Invalid index entry".to_string(),
+                })?;
+                let result = (id_key.get_id(), IsarObject::from_bytes(bytes));
+                Ok(Some(result))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    /// Calls `callback` with every object whose index entry matches `key` in ascending id order.
+    /// Unlike [get_by_index](Self::get_by_index) all matches of non-unique indexes are returned.
+    /// Iteration stops when `callback` returns `false`.
+    pub fn get_all_by_index_key<'txn, F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        index_index: usize,
+        key: &IndexKey,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(i64, IsarObject<'txn>) -> bool,
+    {
+        let index = self.get_index_by_index(index_index)?;
+        txn.read(self.instance_id, |cursors| {
+            let mut cursor = cursors.get_cursor(self.db)?;
+            index.iter_between(cursors, key, key, false, true, |id_key| {
                 let (_, bytes) =
                     cursor
                         .move_to(id_key.as_bytes())?
                         .ok_or(IsarError::DbCorrupted {
                             message: "Invalid index entry".to_string(),
                         })?;
-                let result = (id_key.get_id(), IsarObject::from_bytes(bytes));
-                Ok(Some(result))
-            } else {
-                Ok(None)
-            }
+                Ok(callback(id_key.get_id(), IsarObject::from_bytes(bytes)))
+            })?;
+            Ok(())
         })
     }
 
@@ -143,24 +370,258 @@ impl IsarCollection {
         txn: &mut IsarTxn,
         id: Option<i64>,
         object: IsarObject,
-        replace_on_conflict: bool,
+        on_conflict: OnConflict,
     ) -> Result<i64> {
+        self.verify_object(object.as_bytes())?;
         txn.write(self.instance_id, |cursors, change_set| {
-            self.put_internal(cursors, change_set, id, object, replace_on_conflict)
+            let key_builders = self.new_key_builders();
+            self.put_internal(cursors, &key_builders, change_set, id, object, on_conflict)
         })
     }
 
+    /// Puts the object only if its bytes differ from the stored object with the same id. An
+    /// identical object is neither written nor are its indexes updated or watchers notified.
+    /// Returns whether the object has been written.
+    pub fn put_if_changed(
+        &self,
+        txn: &mut IsarTxn,
+        id: i64,
+        object: IsarObject,
+        on_conflict: OnConflict,
+    ) -> Result<bool> {
+        self.verify_object(object.as_bytes())?;
+        txn.write(self.instance_id, |cursors, change_set| {
+            let id_key = IdKey::new(id);
+            {
+                let mut cursor = cursors.get_cursor(self.db)?;
+                if let Some((_, existing)) = cursor.move_to(id_key.as_bytes())? {
+                    if existing == object.as_bytes() {
+                        return Ok(false);
+                    }
+                }
+            }
+            let key_builders = self.new_key_builders();
+            let put_id = self.put_internal(
+                cursors,
+                &key_builders,
+                change_set,
+                Some(id),
+                object,
+                on_conflict,
+            )?;
+            // ignored conflicts return the id of the existing object without writing
+            Ok(put_id == id)
+        })
+    }
+
+    /// Puts the object using the id of the existing object with the same key in the given unique
+    /// index. If there is no such object, a new id is generated.
+    pub fn put_by_index(
+        &self,
+        txn: &mut IsarTxn,
+        index_index: usize,
+        object: IsarObject,
+        on_conflict: OnConflict,
+    ) -> Result<i64> {
+        let index = self.get_index_by_index(index_index)?;
+        if !index.unique {
+            return illegal_arg("Index is not unique.");
+        }
+        self.verify_object(object.as_bytes())?;
+        txn.write(self.instance_id, |cursors, change_set| {
+            let mut id = None;
+            index.new_key_builder().create_keys(object, |key| {
+                id = index.get_id(cursors, key)?.map(|id_key| id_key.get_id());
+                Ok(id.is_none())
+            })?;
+            let key_builders = self.new_key_builders();
+            self.put_internal(cursors, &key_builders, change_set, id, object, on_conflict)
+        })
+    }
+
+    /// Puts all objects in a single write transaction. The objects are validated before any of
+    /// them is written and invalid objects fail with [IsarError::IllegalArg]. Returns the ids of
+    /// the objects in the same order.
+    pub fn put_all(
+        &self,
+        txn: &mut IsarTxn,
+        objects: &[(Option<i64>, &[u8])],
+        on_conflict: OnConflict,
+    ) -> Result<Vec<i64>> {
+        let chunk_size = objects.len().max(1);
+        self.put_all_with_progress(txn, objects, on_conflict, chunk_size, |_| true)
+    }
+
+    /// Like [put_all](Self::put_all) but calls `progress` with the number of objects written so
+    /// far after every `chunk_size` objects and after the last object. If `progress` returns
+    /// `false`, the operation fails with [IsarError::Cancelled] and the transaction must not be
+    /// used afterwards.
+    pub fn put_all_with_progress<F>(
+        &self,
+        txn: &mut IsarTxn,
+        objects: &[(Option<i64>, &[u8])],
+        on_conflict: OnConflict,
+        chunk_size: usize,
+        mut progress: F,
+    ) -> Result<Vec<i64>>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        if chunk_size == 0 {
+            return illegal_arg("Chunk size must be greater than zero.");
+        }
+        for (_, bytes) in objects {
+            self.verify_object(bytes)?;
+        }
+
+        txn.write(self.instance_id, |cursors, mut change_set| {
+            let key_builders = self.new_key_builders();
+            let mut ids = Vec::with_capacity(objects.len());
+            for (id, bytes) in objects {
+                let id = self.put_internal(
+                    cursors,
+                    &key_builders,
+                    change_set.as_deref_mut(),
+                    *id,
+                    IsarObject::from_bytes(bytes),
+                    on_conflict,
+                )?;
+                ids.push(id);
+
+                let count = ids.len();
+                if (count % chunk_size == 0 || count == objects.len()) && !progress(count) {
+                    return Err(IsarError::Cancelled {});
+                }
+            }
+            Ok(ids)
+        })
+    }
+
+    /// Overwrites a single static property of an existing object with the little-endian `value`
+    /// without rebuilding the object. Only the indexes containing the property are updated.
+    /// Returns `false` if there is no object with the given id. Objects that were written before
+    /// the property was added to the schema have no slot for it and cannot be updated in place.
+    pub fn update_property(
+        &self,
+        txn: &mut IsarTxn,
+        id: i64,
+        property: Property,
+        value: &[u8],
+    ) -> Result<bool> {
+        if !self.props.contains(&property) {
+            return illegal_arg("Property does not belong to this collection.");
+        }
+        if property.data_type.is_dynamic() {
+            return illegal_arg("Only static properties can be updated in place.");
+        }
+        if value.len() != property.data_type.get_static_size() {
+            return illegal_arg("Value has the wrong size.");
+        }
+
+        txn.write(self.instance_id, |cursors, change_set| {
+            let id_key = IdKey::new(id);
+            let mut cursor = cursors.get_cursor(self.db)?;
+            let old_bytes = if let Some((_, bytes)) = cursor.move_to(id_key.as_bytes())? {
+                bytes
+            } else {
+                return Ok(false);
+            };
+            let old_object = IsarObject::from_bytes(old_bytes);
+            if !old_object.contains_property(property) {
+                return illegal_arg("The object was written before the property was added.");
+            }
+            let mut new_bytes = old_bytes.to_vec();
+            new_bytes[property.offset..property.offset + value.len()].copy_from_slice(value);
+            let new_object = IsarObject::from_bytes(&new_bytes);
+
+            let indexes = self
+                .indexes
+                .iter()
+                .filter(|(_, index)| index.properties.iter().any(|p| p.property == property))
+                .collect_vec();
+            let key_builders = indexes
+                .iter()
+                .map(|(_, index)| index.new_key_builder())
+                .collect_vec();
+
+            for ((index_name, index), key_builder) in indexes.iter().zip(&key_builders) {
+                if !index.unique {
+                    continue;
+                }
+                let mut conflict = None;
+                key_builder.create_keys(new_object, |key| {
+                    if let Some(existing_key) = index.get_id(cursors, key)? {
+                        if existing_key.get_id() != id {
+                            conflict = Some(existing_key.get_id());
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                })?;
+                if let Some(existing_id) = conflict {
+                    return Err(IsarError::UniqueViolation {
+                        index: index_name.clone(),
+                        existing_id,
+                    });
+                }
+            }
+
+            for ((_, index), key_builder) in indexes.iter().zip(&key_builders) {
+                index.delete_for_object(cursors, key_builder, &id_key, old_object)?;
+                index.create_for_object(cursors, key_builder, &id_key, new_object, |_| Ok(true))?;
+            }
+
+            cursor.put(id_key.as_bytes(), &new_bytes)?;
+            if let Some(change_set) = change_set {
+                change_set.register_change(self.get_runtime_id(), Some(id), Some(new_object));
+            }
+            Ok(true)
+        })
+    }
+
+    fn verify_object(&self, bytes: &[u8]) -> Result<()> {
+        IsarObject::verify(bytes, &self.props)
+    }
+
+    fn new_key_builders(&self) -> Vec<IndexKeyBuilder> {
+        self.indexes
+            .iter()
+            .map(|(_, index)| index.new_key_builder())
+            .collect()
+    }
+
     fn put_internal(
         &self,
         cursors: &IsarCursors,
+        key_builders: &[IndexKeyBuilder],
         mut change_set: Option<&mut ChangeSet>,
         id: Option<i64>,
         object: IsarObject,
-        replace_on_conflict: bool,
+        on_conflict: OnConflict,
     ) -> Result<i64> {
+        if on_conflict != OnConflict::Replace {
+            let conflict = self.find_unique_conflict(cursors, key_builders, id, object)?;
+            if let Some((index_name, existing_id)) = conflict {
+                return if on_conflict == OnConflict::Ignore {
+                    Ok(existing_id)
+                } else {
+                    Err(IsarError::UniqueViolation {
+                        index: index_name.to_string(),
+                        existing_id,
+                    })
+                };
+            }
+        }
+
         let (id, id_key) = if let Some(id) = id {
             let id_key = IdKey::new(id);
-            self.delete_internal(cursors, false, change_set.as_deref_mut(), &id_key)?;
+            self.delete_internal(
+                cursors,
+                key_builders,
+                false,
+                change_set.as_deref_mut(),
+                &id_key,
+            )?;
             self.update_auto_increment(id);
             (id, id_key)
         } else {
@@ -168,17 +629,22 @@ impl IsarCollection {
             (id, IdKey::new(id))
         };
 
-        /*if !self.object_info.verify_object(object) {
-            return Err(IsarError::InvalidObject {});
-        }*/
-
-        for (_, index) in &self.indexes {
-            index.create_for_object(cursors, &id_key, object, |id_key| {
-                if replace_on_conflict {
-                    self.delete_internal(cursors, true, change_set.as_deref_mut(), id_key)?;
+        for ((index_name, index), key_builder) in self.indexes.iter().zip(key_builders) {
+            index.create_for_object(cursors, key_builder, &id_key, object, |existing_key| {
+                if on_conflict == OnConflict::Replace {
+                    self.delete_internal(
+                        cursors,
+                        key_builders,
+                        true,
+                        change_set.as_deref_mut(),
+                        existing_key,
+                    )?;
                     Ok(true)
                 } else {
-                    Err(IsarError::UniqueViolated {})
+                    Err(IsarError::UniqueViolation {
+                        index: index_name.clone(),
+                        existing_id: existing_key.get_id(),
+                    })
                 }
             })?;
         }
@@ -191,10 +657,42 @@ impl IsarCollection {
         Ok(id)
     }
 
+    /// Returns the name of the first unique index and the id of the object that would conflict
+    /// with the given object.
+    fn find_unique_conflict(
+        &self,
+        cursors: &IsarCursors,
+        key_builders: &[IndexKeyBuilder],
+        id: Option<i64>,
+        object: IsarObject,
+    ) -> Result<Option<(&str, i64)>> {
+        for ((index_name, index), key_builder) in self.indexes.iter().zip(key_builders) {
+            if !index.unique || index.is_removed_in(cursors) {
+                continue;
+            }
+            let mut conflict = None;
+            key_builder.create_keys(object, |key| {
+                if let Some(existing_key) = index.get_id(cursors, key)? {
+                    let existing_id = existing_key.get_id();
+                    if Some(existing_id) != id {
+                        conflict = Some(existing_id);
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            })?;
+            if let Some(existing_id) = conflict {
+                return Ok(Some((index_name.as_str(), existing_id)));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn delete(&self, txn: &mut IsarTxn, id: i64) -> Result<bool> {
         txn.write(self.instance_id, |cursors, change_set| {
             let id_key = IdKey::new(id);
-            self.delete_internal(cursors, true, change_set, &id_key)
+            let key_builders = self.new_key_builders();
+            self.delete_internal(cursors, &key_builders, true, change_set, &id_key)
         })
     }
 
@@ -207,7 +705,8 @@ impl IsarCollection {
         let index = self.get_index_by_index(index_index)?;
         txn.write(self.instance_id, |cursors, change_set| {
             if let Some(id_key) = index.get_id(cursors, key)? {
-                self.delete_internal(cursors, true, change_set, &id_key)?;
+                let key_builders = self.new_key_builders();
+                self.delete_internal(cursors, &key_builders, true, change_set, &id_key)?;
                 Ok(true)
             } else {
                 Ok(false)
@@ -215,9 +714,75 @@ impl IsarCollection {
         })
     }
 
+    /// Deletes all objects with an id between `lower` and `upper` and returns the number of
+    /// deleted objects.
+    pub fn delete_all_id_range(&self, txn: &mut IsarTxn, lower: i64, upper: i64) -> Result<u64> {
+        let mut qb = self.new_query_builder();
+        qb.add_id_where_clause(lower, upper)?;
+        self.delete_all_where_clauses(txn, &qb.into_where_clauses())
+    }
+
+    /// Deletes all objects whose index key is between `lower` and `upper` and returns the number
+    /// of deleted objects. Unlike a query, no filter is evaluated and all objects are deleted in
+    /// a single write.
+    pub fn delete_all_index_range(
+        &self,
+        txn: &mut IsarTxn,
+        index_index: usize,
+        lower: IndexKey,
+        include_lower: bool,
+        upper: IndexKey,
+        include_upper: bool,
+    ) -> Result<u64> {
+        let mut qb = self.new_query_builder();
+        qb.add_index_where_clause(
+            index_index,
+            lower,
+            include_lower,
+            upper,
+            include_upper,
+            false,
+        )?;
+        self.delete_all_where_clauses(txn, &qb.into_where_clauses())
+    }
+
+    fn delete_all_where_clauses(
+        &self,
+        txn: &mut IsarTxn,
+        where_clauses: &[WhereClause],
+    ) -> Result<u64> {
+        txn.write(self.instance_id, |cursors, mut change_set| {
+            // the ids are collected first because deleting invalidates the where clause cursors
+            let mut ids = vec![];
+            for where_clause in where_clauses {
+                where_clause.iter(cursors, None, |id_key, _| {
+                    ids.push(id_key.get_id());
+                    Ok(true)
+                })?;
+            }
+
+            let key_builders = self.new_key_builders();
+            let mut count = 0;
+            for id in ids {
+                let id_key = IdKey::new(id);
+                if self.delete_internal(
+                    cursors,
+                    &key_builders,
+                    true,
+                    change_set.as_deref_mut(),
+                    &id_key,
+                )? {
+                    count += 1;
+                }
+            }
+            Ok(count)
+        })
+    }
+
     fn delete_internal(
         &self,
         cursors: &IsarCursors,
+        key_builders: &[IndexKeyBuilder],
         delete_links: bool,
         change_set: Option<&mut ChangeSet>,
         id_key: &IdKey,
@@ -225,17 +790,20 @@ impl IsarCollection {
         let mut cursor = cursors.get_cursor(self.db)?;
         if let Some((_, object)) = cursor.move_to(id_key.as_bytes())? {
             let object = IsarObject::from_bytes(object);
-            for (_, index) in &self.indexes {
-                index.delete_for_object(cursors, id_key, object)?;
+            for ((_, index), key_builder) in self.indexes.iter().zip(key_builders) {
+                index.delete_for_object(cursors, key_builder, id_key, object)?;
             }
             if delete_links {
                 for (_, link) in &self.links {
                     link.delete_all_for_object(cursors, id_key)?;
+                    if link.is_self_link() {
+                        link.to_backlink().delete_all_for_object(cursors, id_key)?;
+                    }
                 }
             }
             if let Some(change_set) = change_set {
                 let id = id_key.get_id();
-                change_set.register_change(self.get_runtime_id(), Some(id), Some(object));
+                change_set.register_delete(self.get_runtime_id(), id, object);
             }
             cursor.delete_current()?;
             Ok(true)
@@ -269,6 +837,52 @@ impl IsarCollection {
         })
     }
 
+    /// Links the object to all existing target objects and returns the number of new links.
+    pub fn link_all(
+        &self,
+        txn: &mut IsarTxn,
+        link_index: usize,
+        id: i64,
+        target_ids: &[i64],
+    ) -> Result<u32> {
+        let link = self.get_link(link_index)?;
+        txn.write(self.instance_id, |cursors, change_set| {
+            self.register_link_change(change_set, link);
+            link.create_all(cursors, &IdKey::new(id), target_ids)
+        })
+    }
+
+    /// Replaces all links of the object with links to the given targets. Returns the number of
+    /// added and removed links.
+    pub fn replace_links(
+        &self,
+        txn: &mut IsarTxn,
+        link_index: usize,
+        id: i64,
+        target_ids: &[i64],
+    ) -> Result<(u32, u32)> {
+        let link = self.get_link(link_index)?;
+        txn.write(self.instance_id, |cursors, change_set| {
+            self.register_link_change(change_set, link);
+            link.replace(cursors, &IdKey::new(id), target_ids)
+        })
+    }
+
+    /// Same as [IsarCollection::link] but requires the link to point to this collection.
+    pub fn link_self(
+        &self,
+        txn: &mut IsarTxn,
+        link_index: usize,
+        id: i64,
+        target_id: i64,
+    ) -> Result<bool> {
+        let link = self.get_link(link_index)?;
+        if !link.is_self_link() {
+            return illegal_arg("IsarLink does not point to the same collection.");
+        }
+        self.link(txn, link_index, id, target_id)
+    }
+
     pub fn unlink(
         &self,
         txn: &mut IsarTxn,
@@ -294,7 +908,200 @@ impl IsarCollection {
         })
     }
 
-    pub fn clear(&self, txn: &mut IsarTxn) -> Result<()> {
+    /// Returns the number of objects linked to the given object. If `backlink` is set, the number
+    /// of objects of this collection that link to the target object `id` is returned instead.
+    pub fn link_count(
+        &self,
+        txn: &mut IsarTxn,
+        link_index: usize,
+        backlink: bool,
+        id: i64,
+    ) -> Result<u64> {
+        let link = self.get_link(link_index)?;
+        let link = if backlink { link.to_backlink() } else { link };
+        txn.read(self.instance_id, |cursors| {
+            link.count(cursors, &IdKey::new(id))
+        })
+    }
+
+    /// Calls `callback` with every object of this collection that links to the object
+    /// `target_id` of the target collection of the link. Iteration stops when `callback` returns
+    /// `false`.
+    pub fn get_backlinks<'txn, F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        link_index: usize,
+        target_id: i64,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(i64, IsarObject<'txn>) -> bool,
+    {
+        let backlink = self.get_link(link_index)?.to_backlink();
+        txn.read(self.instance_id, |cursors| {
+            backlink.iter(cursors, &IdKey::new(target_id), |id_key, object| {
+                Ok(callback(id_key.get_id(), object))
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Iterates the ids of all objects in ascending order without reading the objects.
+    pub fn iter_ids<F>(&self, txn: &mut IsarTxn, mut callback: F) -> Result<()>
+    where
+        F: FnMut(i64) -> Result<bool>,
+    {
+        txn.read(self.instance_id, |cursors| {
+            let mut cursor = cursors.get_cursor(self.db)?;
+            cursor.iter_between(
+                &u64::MIN.to_le_bytes(),
+                &u64::MAX.to_le_bytes(),
+                false,
+                false,
+                true,
+                |_, key, _| callback(IdKey::from_bytes(key).get_id()),
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn count(&self, txn: &mut IsarTxn) -> Result<u64> {
+        txn.count_db(self.instance_id, self.db)
+    }
+
+    pub fn get_stats(&self, txn: &mut IsarTxn) -> Result<CollectionStats> {
+        txn.read(self.instance_id, |cursors| {
+            let indexes = self
+                .indexes
+                .iter()
+                .map(|(name, index)| Ok((name.clone(), index.get_stats(cursors)?)))
+                .collect::<Result<Vec<_>>>()?;
+            let links = self
+                .links
+                .iter()
+                .map(|(name, link)| Ok((name.clone(), link.get_stats(cursors)?)))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(CollectionStats {
+                name: self.name.clone(),
+                data: cursors.db_stats(self.db)?,
+                indexes,
+                links,
+            })
+        })
+    }
+
+    /// Estimates the size of the collection on disk in bytes. The estimate is based on the number
+    /// of pages the databases of the collection occupy. Pages are usually not completely filled,
+    /// so the estimate is larger than the sum of the stored keys and values.
+    pub fn get_size(
+        &self,
+        txn: &mut IsarTxn,
+        include_indexes: bool,
+        include_links: bool,
+    ) -> Result<u64> {
+        txn.read(self.instance_id, |cursors| {
+            let mut size = cursors.db_stats(self.db)?.size_in_bytes();
+            if include_indexes {
+                for (_, index) in &self.indexes {
+                    size += index.get_stats(cursors)?.size_in_bytes();
+                }
+            }
+            if include_links {
+                for (_, link) in &self.links {
+                    size += link.size_in_bytes(cursors)?;
+                }
+            }
+            Ok(size)
+        })
+    }
+
+    pub(crate) fn verify(&self, cursors: &IsarCursors) -> Result<Vec<IntegrityIssue>> {
+        let mut issues = vec![];
+        let mut data_cursor = cursors.get_cursor(self.db)?;
+        for (name, index) in &self.indexes {
+            if index.is_removed_in(cursors) {
+                continue;
+            }
+            let key_builder = index.new_key_builder();
+            index.iter_entries(cursors, |key, id_key| {
+                let entry = data_cursor.move_to(id_key.as_bytes())?;
+                if let Some((_, object)) = entry {
+                    let mut generated = false;
+                    key_builder.create_keys(IsarObject::from_bytes(object), |object_key| {
+                        generated = object_key.as_bytes() == key;
+                        Ok(!generated)
+                    })?;
+                    if !generated {
+                        issues.push(IntegrityIssue::StaleIndexEntry {
+                            collection: self.name.clone(),
+                            index: name.clone(),
+                            id: id_key.get_id(),
+                        });
+                    }
+                } else {
+                    issues.push(IntegrityIssue::DanglingIndexEntry {
+                        collection: self.name.clone(),
+                        index: name.clone(),
+                        id: id_key.get_id(),
+                    });
+                }
+                Ok(true)
+            })?;
+        }
+
+        data_cursor.iter_between(
+            &u64::MIN.to_le_bytes(),
+            &u64::MAX.to_le_bytes(),
+            false,
+            false,
+            true,
+            |_, id_key, object| {
+                let id_key = IdKey::from_bytes(id_key);
+                let object = IsarObject::from_bytes(object);
+                for (name, index) in &self.indexes {
+                    if index.is_removed_in(cursors) {
+                        continue;
+                    }
+                    let mut missing = false;
+                    index.new_key_builder().create_keys(object, |key| {
+                        missing = !index.contains_entry(cursors, key, &id_key)?;
+                        Ok(!missing)
+                    })?;
+                    if missing {
+                        issues.push(IntegrityIssue::MissingIndexEntry {
+                            collection: self.name.clone(),
+                            index: name.clone(),
+                            id: id_key.get_id(),
+                        });
+                    }
+                }
+                Ok(true)
+            },
+        )?;
+
+        for (name, link) in &self.links {
+            link.iter_all(cursors, |source_key, target_key| {
+                if !link.backlink_exists(cursors, &source_key, &target_key)? {
+                    issues.push(IntegrityIssue::MissingBacklink {
+                        collection: self.name.clone(),
+                        link: name.clone(),
+                        source_id: source_key.get_id(),
+                        target_id: target_key.get_id(),
+                    });
+                }
+                Ok(true)
+            })?;
+        }
+        Ok(issues)
+    }
+
+    /// Deletes all objects of the collection including their index entries and links. If
+    /// `reset_ids` is `true`, the auto increment starts again at `1` like for a new collection.
+    /// Otherwise new ids continue after the largest id that has been used.
+    ///
+    /// Every collection, index and link has its own database so they are emptied as a whole
+    /// without reading any objects or index entries.
+    pub fn clear(&self, txn: &mut IsarTxn, reset_ids: bool) -> Result<()> {
         for (_, index) in &self.indexes {
             index.clear(txn)?;
         }
@@ -303,41 +1110,357 @@ impl IsarCollection {
         }
         txn.clear_db(self.db)?;
         txn.register_all_changed(self.get_runtime_id())?;
-        self.auto_increment.set(i64::MIN);
+        if reset_ids {
+            self.auto_increment.set(0);
+        }
         Ok(())
     }
 
+    /// Clears all indexes and recreates their entries from the stored objects. This repairs
+    /// indexes that are out of sync with the objects. Fails with [IsarError::UniqueViolation] for
+    /// the first object that violates a unique index.
+    pub fn rebuild_indexes(&self, txn: &mut IsarTxn) -> Result<()> {
+        for (_, index) in &self.indexes {
+            index.clear(txn)?;
+        }
+        txn.write(self.instance_id, |cursors, _| {
+            let key_builders = self.new_key_builders();
+            let mut cursor = cursors.get_cursor(self.db)?;
+            cursor.iter_between(
+                &u64::MIN.to_le_bytes(),
+                &u64::MAX.to_le_bytes(),
+                false,
+                false,
+                true,
+                |_, key, object| {
+                    let id_key = IdKey::from_bytes(key);
+                    let object = IsarObject::from_bytes(object);
+                    for ((name, index), key_builder) in self.indexes.iter().zip(&key_builders) {
+                        index.create_for_object(
+                            cursors,
+                            key_builder,
+                            &id_key,
+                            object,
+                            |existing| {
+                                Err(IsarError::UniqueViolation {
+                                    index: name.clone(),
+                                    existing_id: existing.get_id(),
+                                })
+                            },
+                        )?;
+                    }
+                    Ok(true)
+                },
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Writes the value of `default` for the given property to all objects that were stored
+    /// before the property was added to the schema and updates the indexes of these objects.
+    /// Objects that already contain the property are not modified so the migration can safely be
+    /// run again. Returns the number of migrated objects.
+    pub fn migrate_add_property(
+        &self,
+        txn: &mut IsarTxn,
+        property_name: &str,
+        default: IsarObject,
+    ) -> Result<u64> {
+        let property = self
+            .properties
+            .iter()
+            .find(|(name, _)| name == property_name)
+            .map(|(_, p)| *p)
+            .ok_or(IsarError::IllegalArg {
+                message: "Property does not exist".to_string(),
+            })?;
+        if !default.contains_property(property) {
+            return illegal_arg("The default object does not contain the property.");
+        }
+
+        txn.write(self.instance_id, |cursors, mut change_set| {
+            let mut migrated = vec![];
+            let mut cursor = cursors.get_cursor(self.db)?;
+            cursor.iter_between(
+                &u64::MIN.to_le_bytes(),
+                &u64::MAX.to_le_bytes(),
+                false,
+                false,
+                true,
+                |_, key, object| {
+                    let object = IsarObject::from_bytes(object);
+                    if !object.contains_property(property) {
+                        let mut ob = self.new_object_builder(None);
+                        for p in &self.props {
+                            if *p == property {
+                                ob.write_from(default, *p);
+                            } else {
+                                ob.write_from(object, *p);
+                            }
+                        }
+                        let id = IdKey::from_bytes(key).get_id();
+                        migrated.push((id, ob.finish().as_bytes().to_vec()));
+                    }
+                    Ok(true)
+                },
+            )?;
+            drop(cursor);
+
+            let key_builders = self.new_key_builders();
+            for (id, bytes) in &migrated {
+                self.put_internal(
+                    cursors,
+                    &key_builders,
+                    change_set.as_deref_mut(),
+                    Some(*id),
+                    IsarObject::from_bytes(bytes),
+                    OnConflict::Abort,
+                )?;
+            }
+            Ok(migrated.len() as u64)
+        })
+    }
+
+    /// Removes the value of a property from all objects and removes the indexes that contain the
+    /// property. Dynamic data of the property is reclaimed, the static slot is kept until the
+    /// property is removed from the schema. Unique indexes are only removed if
+    /// `drop_unique_indexes` is set. Returns the number of rewritten objects.
+    ///
+    /// The removed indexes are deleted from the stored schema and can no longer be used by this
+    /// instance once the transaction is committed. Queries that use a removed index fail with
+    /// [IsarError::UnknownIndex]. Indexes that are still part of the schema the instance is opened
+    /// with next time are created again from the stored objects.
+    pub fn migrate_remove_property(
+        &self,
+        txn: &mut IsarTxn,
+        property_index: usize,
+        drop_unique_indexes: bool,
+    ) -> Result<u64> {
+        let (_, property) = self
+            .properties
+            .get(property_index)
+            .ok_or(IsarError::IllegalArg {
+                message: "Property does not exist".to_string(),
+            })?;
+        let property = *property;
+
+        let indexes = self
+            .indexes
+            .iter()
+            .filter(|(_, index)| index.properties.iter().any(|p| p.property == property))
+            .collect_vec();
+        if !drop_unique_indexes && indexes.iter().any(|(_, index)| index.unique) {
+            return illegal_arg("The property is used by a unique index.");
+        }
+        txn.update_schema(self.instance_id, |schema| {
+            let col = schema.collections.iter_mut().find(|c| c.name == self.name);
+            if let Some(col) = col {
+                col.indexes
+                    .retain(|index| !indexes.iter().any(|(name, _)| *name == index.name));
+                Ok(())
+            } else {
+                illegal_arg("Collection does not exist.")
+            }
+        })?;
+        for (_, index) in indexes {
+            index.remove(txn)?;
+        }
+
+        txn.write(self.instance_id, |cursors, change_set| {
+            let mut rewritten = vec![];
+            let mut cursor = cursors.get_cursor(self.db)?;
+            cursor.iter_between(
+                &u64::MIN.to_le_bytes(),
+                &u64::MAX.to_le_bytes(),
+                false,
+                false,
+                true,
+                |_, key, object| {
+                    let object = IsarObject::from_bytes(object);
+                    if !object.is_null(property) {
+                        let mut ob = self.new_object_builder(None);
+                        for p in &self.props {
+                            if *p == property {
+                                ob.write_null(p.data_type);
+                            } else {
+                                ob.write_from(object, *p);
+                            }
+                        }
+                        rewritten.push((key.to_vec(), ob.finish().as_bytes().to_vec()));
+                    }
+                    Ok(true)
+                },
+            )?;
+
+            for (key, bytes) in &rewritten {
+                cursor.put(key, bytes)?;
+            }
+            if let Some(change_set) = change_set {
+                change_set.register_all(self.get_runtime_id());
+            }
+            Ok(rewritten.len() as u64)
+        })
+    }
+
+    /// Renames a property in the schema stored in the database without modifying any objects.
+    /// Indexes that contain the property are updated as well. The new name is used once the
+    /// instance is opened again with a schema that contains the renamed property.
+    pub fn rename_property(&self, txn: &mut IsarTxn, old_name: &str, new_name: &str) -> Result<()> {
+        txn.update_schema(self.instance_id, |schema| {
+            let col = schema.collections.iter_mut().find(|c| c.name == self.name);
+            if let Some(col) = col {
+                col.rename_property(old_name, new_name)
+            } else {
+                illegal_arg("Collection does not exist.")
+            }
+        })
+    }
+
     pub fn import_json(
         &self,
         txn: &mut IsarTxn,
         id_name: Option<&str>,
         json: Value,
-        replace_on_conflict: bool,
-    ) -> Result<()> {
+        on_conflict: OnConflict,
+    ) -> Result<ImportStats> {
         txn.write(self.instance_id, |cursors, mut change_set| {
             let array = json.as_array().ok_or(IsarError::InvalidJson {})?;
+            let key_builders = self.new_key_builders();
             let mut ob_result_cache = None;
+            let mut stats = ImportStats::default();
             for value in array {
-                let id = if let Some(id_name) = id_name {
-                    if let Some(id) = value.get(id_name) {
-                        let id = id.as_i64().ok_or(IsarError::InvalidJson {})?;
-                        Some(id)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-                let ob = JsonEncodeDecode::decode(self, value, ob_result_cache)?;
-                let object = ob.finish();
-                self.put_internal(
+                let buffer = self.import_json_object(
                     cursors,
+                    &key_builders,
                     change_set.as_deref_mut(),
-                    id,
-                    object,
-                    replace_on_conflict,
+                    id_name,
+                    value,
+                    ob_result_cache,
+                    on_conflict,
+                    &mut stats,
                 )?;
-                ob_result_cache = Some(ob.recycle());
+                ob_result_cache = Some(buffer);
+            }
+            Ok(stats)
+        })
+    }
+
+    /// Imports a JSON array read from `reader`. The objects are parsed and put one at a time so
+    /// the array never has to be held in memory at once.
+    pub fn import_json_stream(
+        &self,
+        txn: &mut IsarTxn,
+        id_name: Option<&str>,
+        reader: impl Read,
+        on_conflict: OnConflict,
+    ) -> Result<ImportStats> {
+        txn.write(self.instance_id, |cursors, mut change_set| {
+            let key_builders = self.new_key_builders();
+            let mut ob_result_cache = None;
+            let mut stats = ImportStats::default();
+            JsonEncodeDecode::decode_array_stream(reader, |value| {
+                let buffer = self.import_json_object(
+                    cursors,
+                    &key_builders,
+                    change_set.as_deref_mut(),
+                    id_name,
+                    &value,
+                    ob_result_cache.take(),
+                    on_conflict,
+                    &mut stats,
+                )?;
+                ob_result_cache = Some(buffer);
+                Ok(())
+            })?;
+            Ok(stats)
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn import_json_object(
+        &self,
+        cursors: &IsarCursors,
+        key_builders: &[IndexKeyBuilder],
+        change_set: Option<&mut ChangeSet>,
+        id_name: Option<&str>,
+        value: &Value,
+        buffer: Option<Vec<u8>>,
+        on_conflict: OnConflict,
+        stats: &mut ImportStats,
+    ) -> Result<Vec<u8>> {
+        let id = if let Some(id_name) = id_name {
+            if let Some(id) = value.get(id_name) {
+                let id = id.as_i64().ok_or(IsarError::InvalidJson {})?;
+                Some(id)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        let exists = if let Some(id) = id {
+            let mut cursor = cursors.get_cursor(self.db)?;
+            cursor.move_to(IdKey::new(id).as_bytes())?.is_some()
+        } else {
+            false
+        };
+        let ob = JsonEncodeDecode::decode(self, value, buffer)?;
+        let object = ob.finish();
+        self.put_internal(cursors, key_builders, change_set, id, object, on_conflict)?;
+        if exists {
+            stats.replaced += 1;
+        } else {
+            stats.inserted += 1;
+        }
+        Ok(ob.recycle())
+    }
+
+    /// Serializes a single object in the same format as [IsarCollection::export_json].
+    pub fn object_to_json(&self, object: IsarObject, id_name: &str, id: i64) -> Value {
+        let mut json = JsonEncodeDecode::encode(self, object, true, false);
+        json.insert(id_name.to_string(), Value::from(id));
+        Value::Object(json)
+    }
+
+    /// Serializes all objects of this collection to a JSON array. The array is passed to the
+    /// callback in chunks so it never has to be held in memory at once. Returning `false` from
+    /// the callback stops the export.
+    pub fn export_json(
+        &self,
+        txn: &mut IsarTxn,
+        id_name: &str,
+        mut callback: impl FnMut(&[u8]) -> Result<bool>,
+    ) -> Result<()> {
+        txn.read(self.instance_id, |cursors| {
+            if !callback(b"[")? {
+                return Ok(());
+            }
+            let mut first = true;
+            let mut buffer = vec![];
+            let mut cursor = cursors.get_cursor(self.db)?;
+            let completed = cursor.iter_between(
+                &u64::MIN.to_le_bytes(),
+                &u64::MAX.to_le_bytes(),
+                false,
+                false,
+                true,
+                |_, key, object| {
+                    let id = IdKey::from_bytes(key).get_id();
+                    let object = IsarObject::from_bytes(object);
+                    let json = self.object_to_json(object, id_name, id);
+
+                    buffer.clear();
+                    if !first {
+                        buffer.push(b',');
+                    }
+                    first = false;
+                    serde_json::to_writer(&mut buffer, &json)
+                        .map_err(|_| IsarError::InvalidJson {})?;
+                    callback(&buffer)
+                },
+            )?;
+            if completed {
+                callback(b"]")?;
             }
             Ok(())
         })
@@ -345,12 +1468,13 @@ impl IsarCollection {
 
     fn register_link_change(&self, change_set: Option<&mut ChangeSet>, link: IsarLink) {
         if let Some(change_set) = change_set {
-            change_set.register_change(self.get_runtime_id(), None, None);
-            change_set.register_change(link.get_target_col_runtime_id(), None, None);
+            change_set.register_link_change(self.get_runtime_id());
+            change_set.register_link_change(link.get_target_col_runtime_id());
         }
     }
 
     pub(crate) fn fill_indexes(&self, indexes: &[usize], cursors: &IsarCursors) -> Result<()> {
+        let key_builders = self.new_key_builders();
         let mut cursor = cursors.get_cursor(self.db)?;
         cursor.iter_between(
             &u64::MIN.to_le_bytes(),
@@ -363,8 +1487,10 @@ impl IsarCollection {
                 let object = IsarObject::from_bytes(object);
                 for index_index in indexes {
                     let (_, index) = self.indexes.get(*index_index).unwrap();
-                    index.create_for_object(cursors, &id_key, object, |id_key| {
-                        let deleted = self.delete_internal(cursors, true, None, id_key)?;
+                    let key_builder = &key_builders[*index_index];
+                    index.create_for_object(cursors, key_builder, &id_key, object, |id_key| {
+                        let deleted =
+                            self.delete_internal(cursors, &key_builders, true, None, id_key)?;
                         if deleted {
                             cursor.move_to_next()?; // todo find out why this is necessary
                         }
@@ -382,3 +1508,295 @@ impl IsarCollection {
         debug_dump_db(&mut cursor, true)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::{Durability, IsarInstance};
+    use crate::object::data_type::DataType;
+    use crate::schema::collection_schema::CollectionSchema;
+    use crate::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+    use crate::schema::link_schema::LinkSchema;
+    use crate::schema::property_schema::PropertySchema;
+    use crate::schema::Schema;
+    use std::sync::Arc;
+
+    fn open_isar(name: &str, unique: bool) -> Arc<IsarInstance> {
+        let mut dir = std::env::temp_dir();
+        dir.push(rand::random::<u64>().to_string());
+        let index = IndexSchema::new(
+            "int",
+            vec![IndexPropertySchema::new("int", IndexType::Value, false)],
+            unique,
+        );
+        let col_schema = CollectionSchema::new(
+            "col",
+            vec![PropertySchema::new("int", DataType::Int)],
+            vec![index],
+            vec![LinkSchema::new("link", "col")],
+        );
+        let schema = Schema::new(vec![col_schema]).unwrap();
+        IsarInstance::open(
+            name,
+            dir.to_str().unwrap(),
+            Durability::SafeSync,
+            None,
+            schema,
+        )
+        .unwrap()
+    }
+
+    fn put_int(txn: &mut IsarTxn, col: &IsarCollection, id: i64, value: i32) {
+        let mut builder = col.new_object_builder(None);
+        builder.write_int(value);
+        let object = builder.finish();
+        col.put(txn, Some(id), object, OnConflict::Abort).unwrap();
+    }
+
+    #[test]
+    fn test_verify() {
+        let isar = open_isar("verify", false);
+        let col = &isar.collections[0];
+
+        let mut txn = isar.begin_txn(true, false).unwrap();
+        for id in 1..=4 {
+            put_int(&mut txn, col, id, id as i32 * 10);
+        }
+        col.link(&mut txn, 0, 2, 3).unwrap();
+        assert!(isar.verify(&mut txn).unwrap().is_empty());
+
+        let (_, index) = &col.indexes[0];
+        let (_, link) = &col.links[0];
+        txn.write(col.instance_id, |cursors, _| {
+            let mut data_cursor = cursors.get_cursor(col.db)?;
+            data_cursor.move_to(IdKey::new(1).as_bytes())?;
+            data_cursor.delete_current()?;
+
+            let key_builder = index.new_key_builder();
+            let mut data_cursor = cursors.get_cursor(col.db)?;
+            let (_, object) = data_cursor.move_to(IdKey::new(2).as_bytes())?.unwrap();
+            let object = IsarObject::from_bytes(object);
+            index.delete_for_object(cursors, &key_builder, &IdKey::new(2), object)?;
+
+            let mut builder = col.new_object_builder(None);
+            builder.write_int(99);
+            let other = builder.finish();
+            index.create_for_object(cursors, &key_builder, &IdKey::new(4), other, |_| Ok(true))?;
+
+            link.to_backlink()
+                .iter_ids(cursors, &IdKey::new(3), |cursor, _| {
+                    cursor.delete_current()?;
+                    Ok(false)
+                })?;
+            Ok(())
+        })
+        .unwrap();
+
+        let issues = isar.verify(&mut txn).unwrap();
+        let col_name = "col".to_string();
+        let index_name = "int".to_string();
+        assert_eq!(
+            issues,
+            vec![
+                IntegrityIssue::DanglingIndexEntry {
+                    collection: col_name.clone(),
+                    index: index_name.clone(),
+                    id: 1,
+                },
+                IntegrityIssue::StaleIndexEntry {
+                    collection: col_name.clone(),
+                    index: index_name.clone(),
+                    id: 4,
+                },
+                IntegrityIssue::MissingIndexEntry {
+                    collection: col_name.clone(),
+                    index: index_name,
+                    id: 2,
+                },
+                IntegrityIssue::MissingBacklink {
+                    collection: col_name,
+                    link: "link".to_string(),
+                    source_id: 2,
+                    target_id: 3,
+                },
+            ]
+        );
+
+        txn.abort();
+        isar.close();
+    }
+
+    fn find_ids_by_int(
+        txn: &mut IsarTxn,
+        col: &IsarCollection,
+        lower: i32,
+        upper: i32,
+    ) -> Vec<i64> {
+        let mut lower_key = IndexKey::new();
+        lower_key.add_int(lower);
+        let mut upper_key = IndexKey::new();
+        upper_key.add_int(upper);
+        let mut qb = col.new_query_builder();
+        qb.add_index_where_clause(0, lower_key, true, upper_key, true, false)
+            .unwrap();
+        let results = qb.build().find_all_vec(txn).unwrap();
+        results.into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_rebuild_indexes() {
+        let isar = open_isar("rebuild_indexes", false);
+        let col = &isar.collections[0];
+
+        let mut txn = isar.begin_txn(true, false).unwrap();
+        for id in 1..=4 {
+            put_int(&mut txn, col, id, id as i32 * 10);
+        }
+
+        let (_, index) = &col.indexes[0];
+        txn.write(col.instance_id, |cursors, _| {
+            let key_builder = index.new_key_builder();
+            let mut data_cursor = cursors.get_cursor(col.db)?;
+            let (_, object) = data_cursor.move_to(IdKey::new(2).as_bytes())?.unwrap();
+            let object = IsarObject::from_bytes(object);
+            index.delete_for_object(cursors, &key_builder, &IdKey::new(2), object)?;
+
+            let mut builder = col.new_object_builder(None);
+            builder.write_int(25);
+            let other = builder.finish();
+            index.create_for_object(cursors, &key_builder, &IdKey::new(4), other, |_| Ok(true))?;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(find_ids_by_int(&mut txn, col, 10, 30), vec![1, 4, 3]);
+
+        col.rebuild_indexes(&mut txn).unwrap();
+        assert!(isar.verify(&mut txn).unwrap().is_empty());
+        assert_eq!(find_ids_by_int(&mut txn, col, 10, 30), vec![1, 2, 3]);
+        assert_eq!(find_ids_by_int(&mut txn, col, 40, 40), vec![4]);
+
+        txn.abort();
+        isar.close();
+    }
+
+    #[test]
+    fn test_rebuild_indexes_unique_violation() {
+        let isar = open_isar("rebuild_indexes_unique", true);
+        let col = &isar.collections[0];
+
+        let mut txn = isar.begin_txn(true, false).unwrap();
+        put_int(&mut txn, col, 1, 10);
+        txn.write(col.instance_id, |cursors, _| {
+            let mut builder = col.new_object_builder(None);
+            builder.write_int(10);
+            let object = builder.finish();
+            let mut data_cursor = cursors.get_cursor(col.db)?;
+            data_cursor.put(IdKey::new(2).as_bytes(), object.as_bytes())
+        })
+        .unwrap();
+
+        let result = col.rebuild_indexes(&mut txn);
+        assert!(matches!(
+            result,
+            Err(IsarError::UniqueViolation { existing_id: 1, .. })
+        ));
+
+        txn.abort();
+        isar.close();
+    }
+
+    #[test]
+    fn test_update_property() {
+        let mut dir = std::env::temp_dir();
+        dir.push(rand::random::<u64>().to_string());
+        let index_a = IndexSchema::new(
+            "a",
+            vec![IndexPropertySchema::new("a", IndexType::Value, false)],
+            true,
+        );
+        let index_b = IndexSchema::new(
+            "b",
+            vec![IndexPropertySchema::new("b", IndexType::Value, false)],
+            false,
+        );
+        let col_schema = CollectionSchema::new(
+            "col",
+            vec![
+                PropertySchema::new("a", DataType::Int),
+                PropertySchema::new("b", DataType::Long),
+                PropertySchema::new("c", DataType::String),
+            ],
+            vec![index_a, index_b],
+            vec![],
+        );
+        let schema = Schema::new(vec![col_schema]).unwrap();
+        let isar = IsarInstance::open(
+            "update_property",
+            dir.to_str().unwrap(),
+            Durability::SafeSync,
+            None,
+            schema,
+        )
+        .unwrap();
+        let col = &isar.collections[0];
+        let (_, a) = col.properties[0];
+        let (_, b) = col.properties[1];
+        let (_, c) = col.properties[2];
+
+        let mut txn = isar.begin_txn(true, false).unwrap();
+        for id in 1..=2 {
+            let mut builder = col.new_object_builder(None);
+            builder.write_int(id as i32);
+            builder.write_long(id * 100);
+            builder.write_string(Some("hello"));
+            let object = builder.finish();
+            col.put(&mut txn, Some(id), object, OnConflict::Abort)
+                .unwrap();
+        }
+        let index_b_before = txn
+            .read(col.instance_id, |cursors| {
+                Ok(col.indexes[1].1.debug_dump(cursors))
+            })
+            .unwrap();
+
+        assert!(col
+            .update_property(&mut txn, 1, a, &5i32.to_le_bytes())
+            .unwrap());
+        assert!(!col
+            .update_property(&mut txn, 3, a, &5i32.to_le_bytes())
+            .unwrap());
+
+        let object = col.get(&mut txn, 1).unwrap().unwrap();
+        assert_eq!(object.read_int(a), 5);
+        assert_eq!(object.read_long(b), 100);
+        assert_eq!(object.read_string(c), Some("hello"));
+        assert_eq!(find_ids_by_int(&mut txn, col, 5, 5), vec![1]);
+        assert!(find_ids_by_int(&mut txn, col, 1, 1).is_empty());
+        let index_b_after = txn
+            .read(col.instance_id, |cursors| {
+                Ok(col.indexes[1].1.debug_dump(cursors))
+            })
+            .unwrap();
+        assert_eq!(index_b_before, index_b_after);
+        assert!(isar.verify(&mut txn).unwrap().is_empty());
+
+        let result = col.update_property(&mut txn, 1, a, &2i32.to_le_bytes());
+        assert!(matches!(
+            result,
+            Err(IsarError::UniqueViolation { existing_id: 2, .. })
+        ));
+        assert!(col.update_property(&mut txn, 1, a, &[1]).is_err());
+        assert!(col.update_property(&mut txn, 1, c, &[0; 8]).is_err());
+
+        // object written before b and c were added
+        let mut builder = ObjectBuilder::new(&[a], None);
+        builder.write_int(3);
+        col.put(&mut txn, Some(3), builder.finish(), OnConflict::Abort)
+            .unwrap();
+        let result = col.update_property(&mut txn, 3, b, &7i64.to_le_bytes());
+        assert!(matches!(result, Err(IsarError::IllegalArg { .. })));
+
+        txn.abort();
+        isar.close();
+    }
+}
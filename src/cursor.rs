@@ -1,29 +1,40 @@
 use crate::error::Result;
 use crate::mdbx::cursor::{Cursor, UnboundCursor};
-use crate::mdbx::db::Db;
+use crate::mdbx::db::{Db, DbStats};
 use crate::mdbx::txn::Txn;
 use intmap::IntMap;
 use std::cell::RefCell;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
 pub(crate) struct IsarCursors<'txn, 'env> {
     txn: &'txn Txn<'env>,
     unbound_cursors: RefCell<Vec<UnboundCursor>>,
     cursors: RefCell<IntMap<Cursor<'txn>>>,
+    removed_indexes: &'txn [Arc<AtomicBool>],
 }
 
 impl<'txn, 'env> IsarCursors<'txn, 'env> {
     pub fn new(
         txn: &'txn Txn<'env>,
         unbound_cursors: Vec<UnboundCursor>,
+        removed_indexes: &'txn [Arc<AtomicBool>],
     ) -> IsarCursors<'txn, 'env> {
         IsarCursors {
             txn,
             unbound_cursors: RefCell::new(unbound_cursors),
             cursors: RefCell::new(IntMap::new()),
+            removed_indexes,
         }
     }
 
+    /// Whether the index has been removed by the transaction but the removal has not been
+    /// committed yet.
+    pub fn is_index_removed(&self, removed: &Arc<AtomicBool>) -> bool {
+        self.removed_indexes.iter().any(|r| Arc::ptr_eq(r, removed))
+    }
+
     pub fn get_cursor<'a>(&'a self, db: Db) -> Result<IsarCursor<'a, 'txn, 'env>> {
         let cursor = if let Some(cursor) = self.cursors.borrow_mut().remove(db.runtime_id()) {
             cursor
@@ -43,6 +54,10 @@ impl<'txn, 'env> IsarCursors<'txn, 'env> {
         })
     }
 
+    pub fn db_stats(&self, db: Db) -> Result<DbStats> {
+        db.stats(self.txn)
+    }
+
     pub fn close(self) -> Vec<UnboundCursor> {
         let mut unbound_cursors = self.unbound_cursors.take();
         for (_, cursor) in self.cursors.borrow_mut().drain() {
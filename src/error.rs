@@ -18,24 +18,35 @@ pub enum IsarError {
     #[snafu(display("The database is full."))]
     DbFull {},
 
-    #[snafu(display("Unique index violated."))]
-    UniqueViolated {},
+    #[snafu(display("The database is full and the maximum size limit is reached."))]
+    MapSizeLimitReached {},
+
+    #[snafu(display("Unique index {} violated by existing object {}.", index, existing_id))]
+    UniqueViolation { index: String, existing_id: i64 },
 
     #[snafu(display("Write transaction required."))]
     WriteTxnRequired {},
 
+    #[snafu(display("Operation not possible while a write transaction is active."))]
+    WriteTxnActive {},
+
     #[snafu(display("Auto increment id cannot be generated because the limit is reached."))]
     AutoIncrementOverflow {},
 
     #[snafu(display("The provided ObjectId does not match the collection."))]
     InvalidObjectId {},
 
-    #[snafu(display("The provided object is invalid."))]
-    InvalidObject {},
-
     #[snafu(display("Transaction closed."))]
     TransactionClosed {},
 
+    #[snafu(display(
+        "All reader slots are in use. Close unused transactions or increase max_readers."
+    ))]
+    ReadersFull {},
+
+    #[snafu(display("Too many snapshots are open. Snapshots have to be closed after use."))]
+    TooManySnapshots {},
+
     #[snafu(display("IllegalArg: {}.", message))]
     IllegalArg { message: String },
 
@@ -57,6 +68,18 @@ pub enum IsarError {
     #[snafu(display("InstanceMismatch: The transaction is from a different instance."))]
     InstanceMismatch {},
 
+    #[snafu(display("The operation was cancelled."))]
+    Cancelled {},
+
+    #[snafu(display("Timed out waiting for the write lock."))]
+    Timeout {},
+
+    #[snafu(display("The instance was opened read-only."))]
+    ReadOnly {},
+
+    #[snafu(display("The query matches more than {} objects.", max_results))]
+    ResultTooLarge { max_results: usize },
+
     #[snafu(display("MdbxError ({}): {}", code, message))]
     MdbxError { code: i32, message: String },
 }
@@ -6,6 +6,8 @@ pub struct IdKey<'a> {
 }
 
 impl<'a> IdKey<'a> {
+    /// Ids are stored in an `MDBX_INTEGERKEY` database which compares keys as native u64. The sign
+    /// bit is flipped so negative ids sort before positive ones.
     pub fn new(id: i64) -> Self {
         let unsigned: u64 = unsafe { std::mem::transmute(id) };
         let bytes = (unsigned ^ 1 << 63).to_le_bytes().to_vec();
@@ -56,6 +58,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_order() {
+        let ids = [i64::MIN, -100, -1, 0, 1, 100, i64::MAX];
+        for pair in ids.windows(2) {
+            assert!(IdKey::new(pair[0]).get_unsigned_id() < IdKey::new(pair[1]).get_unsigned_id());
+        }
+    }
+
     #[test]
     fn test_from_bytes() {
         assert_eq!(
@@ -0,0 +1,219 @@
+use crate::error::{illegal_arg, Result};
+use crate::index::index_key::IndexKey;
+use crate::index::IndexProperty;
+use crate::object::data_type::DataType;
+use crate::object::isar_object::IsarObject;
+use crate::schema::index_schema::IndexType;
+
+/// An [IndexKey] for a specific index. Components have to be added in the order of the index
+/// properties and are validated against their types. Strings are hashed automatically if the
+/// index requires it.
+pub struct CheckedIndexKey {
+    properties: Vec<IndexProperty>,
+    key: IndexKey,
+    added: usize,
+    // start and end of truncated strings in the key and their untruncated encoding
+    truncated: Vec<(usize, usize, IndexKey)>,
+}
+
+impl CheckedIndexKey {
+    pub(crate) fn new(properties: Vec<IndexProperty>) -> Self {
+        CheckedIndexKey {
+            properties,
+            key: IndexKey::new(),
+            added: 0,
+            truncated: vec![],
+        }
+    }
+
+    fn next_property(&mut self) -> Result<IndexProperty> {
+        if let Some(property) = self.properties.get(self.added) {
+            self.added += 1;
+            Ok(*property)
+        } else {
+            illegal_arg("All properties of the index have already been added.")
+        }
+    }
+
+    fn next_value_property(&mut self, data_types: &[DataType]) -> Result<IndexProperty> {
+        let property = self.next_property()?;
+        let data_type = property.property.data_type;
+        let value_type = data_type.get_element_type().unwrap_or(data_type);
+        if property.index_type == IndexType::Value && data_types.contains(&value_type) {
+            Ok(property)
+        } else {
+            illegal_arg("Value does not match the type of the index property.")
+        }
+    }
+
+    fn invert_if_descending(&mut self, property: IndexProperty, start: usize) {
+        if property.descending {
+            self.key.invert(start);
+        }
+    }
+
+    pub fn add_byte(&mut self, value: u8) -> Result<()> {
+        let property = self.next_value_property(&[DataType::Bool, DataType::Byte])?;
+        let start = self.key.len();
+        self.key.add_byte(value);
+        self.invert_if_descending(property, start);
+        Ok(())
+    }
+
+    pub fn add_int(&mut self, value: i32) -> Result<()> {
+        let property = self.next_value_property(&[DataType::Int])?;
+        let start = self.key.len();
+        self.key.add_int(value);
+        self.invert_if_descending(property, start);
+        Ok(())
+    }
+
+    pub fn add_long(&mut self, value: i64) -> Result<()> {
+        let property =
+            self.next_value_property(&[DataType::Long, DataType::DateTime, DataType::Decimal])?;
+        let start = self.key.len();
+        self.key.add_long(value);
+        self.invert_if_descending(property, start);
+        Ok(())
+    }
+
+    pub fn add_float(&mut self, value: f32) -> Result<()> {
+        let property = self.next_value_property(&[DataType::Float])?;
+        let start = self.key.len();
+        self.key.add_float(value);
+        self.invert_if_descending(property, start);
+        Ok(())
+    }
+
+    pub fn add_double(&mut self, value: f64) -> Result<()> {
+        let property = self.next_value_property(&[DataType::Double])?;
+        let start = self.key.len();
+        self.key.add_double(value);
+        self.invert_if_descending(property, start);
+        Ok(())
+    }
+
+    pub fn add_string(&mut self, value: Option<&str>) -> Result<()> {
+        let property = self.next_property()?;
+        let start = self.key.len();
+        match (property.property.data_type, property.index_type) {
+            (DataType::String, IndexType::Value) | (DataType::StringList, IndexType::Value) => {
+                let value = value.map(|value| property.collation.apply(value));
+                let truncated = self.key.add_truncated_string(
+                    value.as_deref(),
+                    property.case_sensitive,
+                    property.prefix_len,
+                );
+                self.invert_if_descending(property, start);
+                if truncated {
+                    let mut full = IndexKey::new();
+                    full.add_string(value.as_deref(), property.case_sensitive);
+                    if property.descending {
+                        full.invert(0);
+                    }
+                    self.truncated.push((start, self.key.len(), full));
+                }
+            }
+            (DataType::String, IndexType::Hash)
+            | (DataType::StringList, IndexType::HashElements) => {
+                let hash = IsarObject::hash_string(value, property.case_sensitive, 0);
+                self.key.add_hash(hash)
+            }
+            _ => return illegal_arg("Value does not match the type of the index property."),
+        }
+        Ok(())
+    }
+
+    /// Adds a precomputed hash for an index property that is hashed.
+    pub fn add_hash(&mut self, hash: u64) -> Result<()> {
+        let property = self.next_property()?;
+        if property.index_type == IndexType::Value {
+            return illegal_arg("Index property is not hashed.");
+        }
+        self.key.add_hash(hash);
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> IndexKey {
+        if !self.truncated.is_empty() {
+            let bytes = self.key.as_bytes();
+            let mut hash_offsets = vec![];
+            let mut full_bytes = vec![];
+            let mut position = 0;
+            for (start, end, full) in &self.truncated {
+                full_bytes.extend_from_slice(&bytes[position..*start]);
+                full_bytes.extend_from_slice(full.as_bytes());
+                hash_offsets.push(end - 8);
+                position = *end;
+            }
+            full_bytes.extend_from_slice(&bytes[position..]);
+            self.key.set_truncated(hash_offsets, full_bytes);
+        }
+        self.key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::isar_object::Property;
+
+    fn key(properties: &[(DataType, IndexType, bool)]) -> CheckedIndexKey {
+        let properties = properties
+            .iter()
+            .enumerate()
+            .map(|(i, (data_type, index_type, case_sensitive))| {
+                let property = Property::new(*data_type, 2 + i * 8);
+                IndexProperty::new(property, *index_type, *case_sensitive)
+            })
+            .collect();
+        CheckedIndexKey::new(properties)
+    }
+
+    #[test]
+    fn test_add_in_order() {
+        let mut checked = key(&[
+            (DataType::Long, IndexType::Value, false),
+            (DataType::String, IndexType::Value, false),
+        ]);
+        checked.add_long(5).unwrap();
+        checked.add_string(Some("Hello")).unwrap();
+        assert!(checked.add_byte(1).is_err());
+
+        let mut expected = IndexKey::new();
+        expected.add_long(5);
+        expected.add_string(Some("hello"), false);
+        assert_eq!(checked.finish().as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_add_wrong_type() {
+        let mut checked = key(&[(DataType::Int, IndexType::Value, false)]);
+        assert!(checked.add_long(5).is_err());
+
+        let mut checked = key(&[(DataType::String, IndexType::Hash, true)]);
+        assert!(checked.add_hash(5).is_ok());
+        let mut checked = key(&[(DataType::Int, IndexType::Value, false)]);
+        assert!(checked.add_hash(5).is_err());
+    }
+
+    #[test]
+    fn test_add_string_hash() {
+        let mut checked = key(&[(DataType::StringList, IndexType::HashElements, false)]);
+        checked.add_string(Some("Hello")).unwrap();
+
+        let mut expected = IndexKey::new();
+        expected.add_hash(IsarObject::hash_string(Some("Hello"), false, 0));
+        assert_eq!(checked.finish().as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_add_list_element() {
+        let mut checked = key(&[(DataType::IntList, IndexType::Value, false)]);
+        checked.add_int(3).unwrap();
+
+        let mut expected = IndexKey::new();
+        expected.add_int(3);
+        assert_eq!(checked.finish().as_bytes(), expected.as_bytes());
+    }
+}
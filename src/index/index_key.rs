@@ -4,14 +4,26 @@ use std::borrow::Borrow;
 use std::cmp::Ordering;
 use xxhash_rust::xxh3::xxh3_64;
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone)]
 pub struct IndexKey {
     bytes: Vec<u8>,
+    truncated: Option<TruncatedStrings>,
+}
+
+/// Strings of a key that have been truncated to the prefix length of their index. Truncated
+/// strings are ordered by their hash so range bounds need the full strings.
+#[derive(Clone)]
+struct TruncatedStrings {
+    hash_offsets: Vec<usize>,
+    full_bytes: Vec<u8>,
 }
 
 impl IndexKey {
     pub fn new() -> Self {
-        IndexKey { bytes: vec![] }
+        IndexKey {
+            bytes: vec![],
+            truncated: None,
+        }
     }
 
     pub fn add_byte(&mut self, value: u8) {
@@ -59,6 +71,18 @@ impl IndexKey {
     }
 
     pub fn add_string(&mut self, value: Option<&str>, case_sensitive: bool) {
+        self.add_truncated_string(value, case_sensitive, IsarIndex::MAX_STRING_INDEX_SIZE);
+    }
+
+    /// Like [IndexKey::add_string] but strings of at least `prefix_len` bytes are truncated to
+    /// `prefix_len` bytes and followed by the hash of the full string. Returns whether the string
+    /// has been truncated.
+    pub fn add_truncated_string(
+        &mut self,
+        value: Option<&str>,
+        case_sensitive: bool,
+        prefix_len: usize,
+    ) -> bool {
         if let Some(value) = value {
             let value = if case_sensitive {
                 value.to_string()
@@ -67,9 +91,9 @@ impl IndexKey {
             };
             let bytes = value.as_bytes();
             self.bytes.push(1);
-            if bytes.len() >= IsarIndex::MAX_STRING_INDEX_SIZE {
-                self.bytes
-                    .extend_from_slice(&bytes[0..IsarIndex::MAX_STRING_INDEX_SIZE]);
+            let truncated = bytes.len() >= prefix_len;
+            if truncated {
+                self.bytes.extend_from_slice(&bytes[0..prefix_len]);
                 self.bytes.push(0);
                 let hash = xxh3_64(bytes);
                 self.bytes.extend_from_slice(&u64::to_le_bytes(hash));
@@ -77,16 +101,99 @@ impl IndexKey {
                 self.bytes.extend_from_slice(bytes);
                 self.bytes.push(0);
             }
+            truncated
         } else {
             self.bytes.push(0);
+            false
         }
     }
 
+    /// Adds the bytes of a string without the terminator so the key sorts before all strings
+    /// starting with `value`.
+    pub fn add_string_prefix(&mut self, value: &str, case_sensitive: bool) {
+        let value = if case_sensitive {
+            value.to_string()
+        } else {
+            value.to_lowercase()
+        };
+        let bytes = value.as_bytes();
+        let len = bytes.len().min(IsarIndex::MAX_STRING_INDEX_SIZE);
+        self.bytes.push(1);
+        self.bytes.extend_from_slice(&bytes[0..len]);
+    }
+
     pub fn add_hash(&mut self, value: u64) {
         let bytes: [u8; 8] = value.to_be_bytes();
         self.bytes.extend_from_slice(&bytes);
     }
 
+    /// Appends the components of `key` after the components of this key.
+    pub fn append(&mut self, key: &IndexKey) {
+        if self.truncated.is_some() || key.truncated.is_some() {
+            let mut hash_offsets = self.hash_offsets().to_vec();
+            hash_offsets.extend(key.hash_offsets().iter().map(|o| o + self.bytes.len()));
+            let mut full_bytes = self.full_bytes().to_vec();
+            full_bytes.extend_from_slice(key.full_bytes());
+            self.set_truncated(hash_offsets, full_bytes);
+        }
+        self.bytes.extend_from_slice(key.as_bytes());
+    }
+
+    /// Records the offsets of the hashes of truncated strings and the bytes of the key with the
+    /// full strings.
+    pub(crate) fn set_truncated(&mut self, hash_offsets: Vec<usize>, full_bytes: Vec<u8>) {
+        self.truncated = Some(TruncatedStrings {
+            hash_offsets,
+            full_bytes,
+        });
+    }
+
+    pub(crate) fn is_truncated(&self) -> bool {
+        self.truncated.is_some()
+    }
+
+    fn hash_offsets(&self) -> &[usize] {
+        match &self.truncated {
+            Some(truncated) => &truncated.hash_offsets,
+            None => &[],
+        }
+    }
+
+    fn full_bytes(&self) -> &[u8] {
+        match &self.truncated {
+            Some(truncated) => &truncated.full_bytes,
+            None => &self.bytes,
+        }
+    }
+
+    /// Returns the key with the full strings instead of the truncated ones.
+    pub(crate) fn to_untruncated(&self) -> IndexKey {
+        IndexKey {
+            bytes: self.full_bytes().to_vec(),
+            truncated: None,
+        }
+    }
+
+    /// Replaces the hashes of truncated strings with the smallest or largest hash so the key
+    /// includes or excludes all strings sharing the prefix.
+    pub(crate) fn clamp_truncated(&mut self, upper: bool) {
+        let fill = if upper { 0xFF } else { 0x00 };
+        if let Some(truncated) = &self.truncated {
+            for offset in &truncated.hash_offsets {
+                for byte in &mut self.bytes[*offset..*offset + 8] {
+                    *byte = fill;
+                }
+            }
+        }
+    }
+
+    /// Inverts all bytes starting at `from` so the components after it sort in reverse order.
+    pub fn invert(&mut self, from: usize) {
+        for byte in &mut self.bytes[from..] {
+            *byte = !*byte;
+        }
+    }
+
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.bytes.len()
@@ -125,6 +232,14 @@ impl IndexKey {
     }
 }
 
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for IndexKey {}
+
 impl PartialOrd<Self> for IndexKey {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -36,23 +36,34 @@ impl<'a> IndexKeyBuilder<'a> {
         let mut key = IndexKey::new();
         for index_property in self.properties {
             let property = index_property.property;
+            let start = key.len();
 
             if index_property.index_type == IndexType::Hash {
                 let hash = object.hash_property(property, index_property.case_sensitive, 0);
                 key.add_hash(hash);
             } else {
                 match property.data_type {
-                    DataType::Byte => key.add_byte(object.read_byte(property)),
+                    DataType::Bool | DataType::Byte => key.add_byte(object.read_byte(property)),
                     DataType::Int => key.add_int(object.read_int(property)),
                     DataType::Float => key.add_float(object.read_float(property)),
-                    DataType::Long => key.add_long(object.read_long(property)),
-                    DataType::Double => key.add_double(object.read_double(property)),
-                    DataType::String => {
-                        key.add_string(object.read_string(property), index_property.case_sensitive)
+                    DataType::Long | DataType::DateTime | DataType::Decimal => {
+                        key.add_long(object.read_long(property))
                     }
+                    DataType::Double => key.add_double(object.read_double(property)),
+                    DataType::String => key.add_truncated_string(
+                        object
+                            .read_string(property)
+                            .map(|value| index_property.collation.apply(value))
+                            .as_deref(),
+                        index_property.case_sensitive,
+                        index_property.prefix_len,
+                    ),
                     _ => unreachable!(),
                 }
             }
+            if index_property.descending {
+                key.invert(start);
+            }
         }
         key
     }
@@ -67,12 +78,18 @@ impl<'a> IndexKeyBuilder<'a> {
         if object.is_null(property) {
             return Ok(true);
         }
+        let mut callback = |key: &mut IndexKey| {
+            if index_property.descending {
+                key.invert(0);
+            }
+            callback(key)
+        };
         match property.data_type {
             DataType::ByteList => {
                 for value in object.read_byte_list(property).unwrap() {
                     key.truncate(0);
                     key.add_byte(*value);
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
@@ -81,7 +98,7 @@ impl<'a> IndexKeyBuilder<'a> {
                 for value in object.read_int_list(property).unwrap() {
                     key.truncate(0);
                     key.add_int(value);
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
@@ -90,7 +107,7 @@ impl<'a> IndexKeyBuilder<'a> {
                 for value in object.read_long_list(property).unwrap() {
                     key.truncate(0);
                     key.add_long(value);
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
@@ -99,7 +116,7 @@ impl<'a> IndexKeyBuilder<'a> {
                 for value in object.read_float_list(property).unwrap() {
                     key.truncate(0);
                     key.add_float(value);
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
@@ -108,7 +125,7 @@ impl<'a> IndexKeyBuilder<'a> {
                 for value in object.read_double_list(property).unwrap() {
                     key.truncate(0);
                     key.add_double(value);
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
@@ -120,9 +137,15 @@ impl<'a> IndexKeyBuilder<'a> {
                         let hash = IsarObject::hash_string(value, index_property.case_sensitive, 0);
                         key.add_hash(hash);
                     } else {
-                        key.add_string(value, index_property.case_sensitive);
+                        key.add_truncated_string(
+                            value
+                                .map(|value| index_property.collation.apply(value))
+                                .as_deref(),
+                            index_property.case_sensitive,
+                            index_property.prefix_len,
+                        );
                     }
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
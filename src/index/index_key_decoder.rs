@@ -0,0 +1,220 @@
+use crate::error::{IsarError, Result};
+use crate::index::IndexProperty;
+use crate::object::data_type::DataType;
+use crate::schema::index_schema::IndexType;
+use serde::Serialize;
+
+/// A component of an index key. Strings of case insensitive indexes are lowercase, strings of
+/// folded indexes lack combining marks and long strings are truncated to the prefix length of the
+/// index property.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+#[serde(untagged)]
+pub enum IndexKeyValue {
+    Byte(u8),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(Option<String>),
+    Hash(u64),
+}
+
+pub(crate) struct IndexKeyDecoder<'a> {
+    properties: &'a [IndexProperty],
+}
+
+impl<'a> IndexKeyDecoder<'a> {
+    pub fn new(properties: &'a [IndexProperty]) -> Self {
+        Self { properties }
+    }
+
+    /// Decodes the raw bytes of an index key into one value per index property.
+    pub fn decode(&self, key: &[u8]) -> Result<Vec<IndexKeyValue>> {
+        let mut reader = KeyReader {
+            key,
+            position: 0,
+            inverted: false,
+        };
+        let mut values = vec![];
+        for index_property in self.properties {
+            reader.inverted = index_property.descending;
+            let data_type = index_property.property.data_type;
+            let value = if index_property.index_type != IndexType::Value {
+                IndexKeyValue::Hash(u64::from_be_bytes(reader.read()?))
+            } else {
+                match data_type.get_element_type().unwrap_or(data_type) {
+                    DataType::Bool | DataType::Byte => IndexKeyValue::Byte(reader.read::<1>()?[0]),
+                    DataType::Int => {
+                        let unsigned = u32::from_be_bytes(reader.read()?);
+                        IndexKeyValue::Int((unsigned ^ 1 << 31) as i32)
+                    }
+                    DataType::Long | DataType::DateTime | DataType::Decimal => {
+                        let unsigned = u64::from_be_bytes(reader.read()?);
+                        IndexKeyValue::Long((unsigned ^ 1 << 63) as i64)
+                    }
+                    DataType::Float => {
+                        let bits = u32::from_be_bytes(reader.read()?);
+                        let value = if bits == 0 {
+                            f32::NAN
+                        } else if bits >= 1 << 31 {
+                            f32::from_bits(bits - (1 << 31))
+                        } else {
+                            -f32::from_bits(!(bits + (1 << 31)))
+                        };
+                        IndexKeyValue::Float(value)
+                    }
+                    DataType::Double => {
+                        let bits = u64::from_be_bytes(reader.read()?);
+                        let value = if bits == 0 {
+                            f64::NAN
+                        } else if bits >= 1 << 63 {
+                            f64::from_bits(bits - (1 << 63))
+                        } else {
+                            -f64::from_bits(!(bits + (1 << 63)))
+                        };
+                        IndexKeyValue::Double(value)
+                    }
+                    DataType::String => {
+                        IndexKeyValue::String(reader.read_string(index_property.prefix_len)?)
+                    }
+                    _ => unreachable!(),
+                }
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+struct KeyReader<'a> {
+    key: &'a [u8],
+    position: usize,
+    inverted: bool,
+}
+
+impl<'a> KeyReader<'a> {
+    fn read_byte(&mut self) -> Result<u8> {
+        let byte = *self.key.get(self.position).ok_or(IsarError::DbCorrupted {
+            message: "Index key is too short.".to_string(),
+        })?;
+        self.position += 1;
+        Ok(if self.inverted { !byte } else { byte })
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut bytes = [0; N];
+        for byte in &mut bytes {
+            *byte = self.read_byte()?;
+        }
+        Ok(bytes)
+    }
+
+    fn read_string(&mut self, prefix_len: usize) -> Result<Option<String>> {
+        if self.read_byte()? == 0 {
+            return Ok(None);
+        }
+        let mut bytes = vec![];
+        loop {
+            let byte = self.read_byte()?;
+            if byte == 0 {
+                break;
+            }
+            bytes.push(byte);
+        }
+        if bytes.len() == prefix_len {
+            // truncated strings are followed by the hash of the full string
+            self.read::<8>()?;
+        }
+        Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::index_key::IndexKey;
+    use crate::object::isar_object::Property;
+
+    fn properties(properties: &[(DataType, IndexType, bool)]) -> Vec<IndexProperty> {
+        properties
+            .iter()
+            .enumerate()
+            .map(|(i, (data_type, index_type, descending))| {
+                let property = Property::new(*data_type, 2 + i * 8);
+                let mut index_property = IndexProperty::new(property, *index_type, false);
+                index_property.descending = *descending;
+                index_property
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decode() {
+        let properties = properties(&[
+            (DataType::Int, IndexType::Value, false),
+            (DataType::Double, IndexType::Value, true),
+            (DataType::String, IndexType::Hash, false),
+            (DataType::String, IndexType::Value, true),
+        ]);
+        let mut key = IndexKey::new();
+        key.add_int(-5);
+        let start = key.len();
+        key.add_double(-1.5);
+        key.invert(start);
+        key.add_hash(12345);
+        let start = key.len();
+        key.add_string(Some("hello"), true);
+        key.invert(start);
+
+        let values = IndexKeyDecoder::new(&properties)
+            .decode(key.as_bytes())
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                IndexKeyValue::Int(-5),
+                IndexKeyValue::Double(-1.5),
+                IndexKeyValue::Hash(12345),
+                IndexKeyValue::String(Some("hello".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_numbers() {
+        let properties = properties(&[
+            (DataType::Long, IndexType::Value, false),
+            (DataType::Float, IndexType::Value, false),
+            (DataType::Float, IndexType::Value, false),
+            (DataType::Byte, IndexType::Value, false),
+            (DataType::String, IndexType::Value, false),
+        ]);
+        let mut key = IndexKey::new();
+        key.add_long(i64::MIN);
+        key.add_float(2.25);
+        key.add_float(-0.5);
+        key.add_byte(7);
+        key.add_string(None, false);
+
+        let values = IndexKeyDecoder::new(&properties)
+            .decode(key.as_bytes())
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                IndexKeyValue::Long(i64::MIN),
+                IndexKeyValue::Float(2.25),
+                IndexKeyValue::Float(-0.5),
+                IndexKeyValue::Byte(7),
+                IndexKeyValue::String(None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_too_short() {
+        let properties = properties(&[(DataType::Long, IndexType::Value, false)]);
+        let decoder = IndexKeyDecoder::new(&properties);
+        assert!(decoder.decode(&[1, 2, 3]).is_err());
+    }
+}
@@ -1,23 +1,33 @@
-use crate::cursor::IsarCursors;
-use crate::error::Result;
+use crate::cursor::{IsarCursor, IsarCursors};
+use crate::error::{IsarError, Result};
 use crate::id_key::IdKey;
 use crate::index::index_key::IndexKey;
 use crate::index::index_key_builder::IndexKeyBuilder;
-use crate::mdbx::db::Db;
+use crate::index::index_key_decoder::IndexKeyDecoder;
+use crate::mdbx::db::{Db, DbStats};
 use crate::mdbx::debug_dump_db;
 use crate::object::isar_object::{IsarObject, Property};
-use crate::schema::index_schema::IndexType;
+use crate::schema::index_schema::{Collation, IndexType};
 use crate::txn::IsarTxn;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+pub mod checked_index_key;
 pub mod index_key;
 pub(crate) mod index_key_builder;
+pub mod index_key_decoder;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct IndexProperty {
     pub property: Property,
     pub index_type: IndexType,
     pub case_sensitive: bool,
+    pub descending: bool,
+    /// The number of bytes of strings that are stored in the index.
+    pub prefix_len: usize,
+    /// How strings are transformed before they are stored in the index.
+    pub collation: Collation,
 }
 
 impl IndexProperty {
@@ -26,6 +36,9 @@ impl IndexProperty {
             property,
             index_type,
             case_sensitive,
+            descending: false,
+            prefix_len: IsarIndex::MAX_STRING_INDEX_SIZE,
+            collation: Collation::Raw,
         }
     }
 
@@ -44,14 +57,24 @@ impl IndexProperty {
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone)]
 pub(crate) struct IsarIndex {
     pub properties: Vec<IndexProperty>,
     pub unique: bool,
     pub multi_entry: bool,
+    // shared with the clones of where clauses and only set once the removal is committed
+    removed: Arc<AtomicBool>,
     db: Db,
 }
 
+impl PartialEq for IsarIndex {
+    fn eq(&self, other: &Self) -> bool {
+        self.db == other.db
+    }
+}
+
+impl Eq for IsarIndex {}
+
 impl IsarIndex {
     pub const MAX_STRING_INDEX_SIZE: usize = 1024;
 
@@ -61,13 +84,51 @@ impl IsarIndex {
             properties,
             unique,
             multi_entry,
+            removed: Arc::new(AtomicBool::new(false)),
             db,
         }
     }
 
+    /// Removed indexes are skipped when objects are written and can no longer be used.
+    pub fn is_removed(&self) -> bool {
+        self.removed.load(Ordering::Acquire)
+    }
+
+    /// Like [is_removed](Self::is_removed) but also includes the uncommitted removals of the
+    /// transaction.
+    pub(crate) fn is_removed_in(&self, cursors: &IsarCursors) -> bool {
+        self.is_removed() || cursors.is_index_removed(&self.removed)
+    }
+
+    /// Clears the index and removes it once the transaction is committed.
+    pub(crate) fn remove(&self, txn: &mut IsarTxn) -> Result<()> {
+        self.clear(txn)?;
+        txn.remove_index(self.removed.clone())
+    }
+
+    fn get_cursor<'a, 'txn, 'env>(
+        &self,
+        cursors: &'a IsarCursors<'txn, 'env>,
+    ) -> Result<IsarCursor<'a, 'txn, 'env>> {
+        if self.is_removed_in(cursors) {
+            Err(IsarError::UnknownIndex {})
+        } else {
+            cursors.get_cursor(self.db)
+        }
+    }
+
+    pub fn new_key_builder(&self) -> IndexKeyBuilder {
+        IndexKeyBuilder::new(&self.properties)
+    }
+
+    pub fn new_key_decoder(&self) -> IndexKeyDecoder {
+        IndexKeyDecoder::new(&self.properties)
+    }
+
     pub fn create_for_object<F>(
         &self,
         cursors: &IsarCursors,
+        key_builder: &IndexKeyBuilder,
         id_key: &IdKey,
         object: IsarObject,
         mut on_conflict: F,
@@ -75,8 +136,10 @@ impl IsarIndex {
     where
         F: FnMut(&IdKey) -> Result<bool>,
     {
+        if self.is_removed_in(cursors) {
+            return Ok(());
+        }
         let mut cursor = cursors.get_cursor(self.db)?;
-        let key_builder = IndexKeyBuilder::new(&self.properties);
         key_builder.create_keys(object, |key| {
             if self.unique {
                 let existing = cursor.move_to(key.as_bytes())?;
@@ -93,11 +156,14 @@ impl IsarIndex {
     pub fn delete_for_object(
         &self,
         cursors: &IsarCursors,
+        key_builder: &IndexKeyBuilder,
         id_key: &IdKey,
         object: IsarObject,
     ) -> Result<()> {
+        if self.is_removed_in(cursors) {
+            return Ok(());
+        }
         let mut cursor = cursors.get_cursor(self.db)?;
-        let key_builder = IndexKeyBuilder::new(&self.properties);
         key_builder.create_keys(object, |key| {
             let entry = if self.unique {
                 cursor.move_to(key.as_bytes())?
@@ -121,7 +187,7 @@ impl IsarIndex {
         ascending: bool,
         mut callback: impl FnMut(IdKey<'txn>) -> Result<bool>,
     ) -> Result<bool> {
-        let mut cursor = cursors.get_cursor(self.db)?;
+        let mut cursor = self.get_cursor(cursors)?;
         cursor.iter_between(
             lower_key.as_bytes(),
             upper_key.as_bytes(),
@@ -132,6 +198,27 @@ impl IsarIndex {
         )
     }
 
+    /// Iterates the raw keys between `lower_key` and `upper_key` without reading the objects.
+    /// If `skip_duplicates` is set, each key is only returned once.
+    pub fn iter_keys<'txn, 'env>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        lower_key: &IndexKey,
+        upper_key: &IndexKey,
+        skip_duplicates: bool,
+        mut callback: impl FnMut(&'txn [u8]) -> Result<bool>,
+    ) -> Result<bool> {
+        let mut cursor = self.get_cursor(cursors)?;
+        cursor.iter_between(
+            lower_key.as_bytes(),
+            upper_key.as_bytes(),
+            !self.unique,
+            skip_duplicates,
+            true,
+            |_, key, _| callback(key),
+        )
+    }
+
     pub fn get_id<'txn, 'env>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
@@ -145,6 +232,41 @@ impl IsarIndex {
         Ok(result)
     }
 
+    /// Iterates all entries of the index in key order.
+    pub(crate) fn iter_entries<'txn, 'env>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        mut callback: impl FnMut(&'txn [u8], IdKey<'txn>) -> Result<bool>,
+    ) -> Result<bool> {
+        let mut cursor = self.get_cursor(cursors)?;
+        cursor.iter_between(&[], &u64::MAX.to_le_bytes(), false, false, true, |_, key, id| {
+            callback(key, IdKey::from_bytes(id))
+        })
+    }
+
+    pub(crate) fn contains_entry(
+        &self,
+        cursors: &IsarCursors,
+        key: &IndexKey,
+        id_key: &IdKey,
+    ) -> Result<bool> {
+        let mut cursor = self.get_cursor(cursors)?;
+        let exists = if self.unique {
+            cursor
+                .move_to(key.as_bytes())?
+                .map_or(false, |(_, id)| id == id_key.as_bytes())
+        } else {
+            cursor
+                .move_to_key_val(key.as_bytes(), id_key.as_bytes())?
+                .is_some()
+        };
+        Ok(exists)
+    }
+
+    pub fn get_stats(&self, cursors: &IsarCursors) -> Result<DbStats> {
+        cursors.db_stats(self.db)
+    }
+
     pub fn clear(&self, txn: &mut IsarTxn) -> Result<()> {
         txn.clear_db(self.db)
     }
@@ -1,24 +1,32 @@
-use crate::collection::IsarCollection;
+use crate::collection::{CollectionStats, IntegrityIssue, IsarCollection};
 use crate::error::*;
 use crate::mdbx::env::Env;
+use crate::metrics::{InstanceMetrics, MetricsCounters};
 use crate::query::Query;
 use crate::schema::schema_manager::SchemaManger;
 use crate::schema::Schema;
+use crate::snapshot::IsarSnapshot;
 use crate::txn::IsarTxn;
 use crate::watch::change_set::ChangeSet;
 use crate::watch::isar_watchers::{IsarWatchers, WatcherModifier};
-use crate::watch::watcher::WatcherCallback;
+use crate::watch::watcher::{ObjectWatcherCallback, WatcherCallback};
 use crate::watch::WatchHandle;
 use crossbeam_channel::{unbounded, Sender};
 use intmap::IntMap;
+use itertools::Itertools;
 use once_cell::sync::Lazy;
 use rand::random;
-use std::fs::{create_dir_all, remove_dir_all};
+use std::fs::{create_dir_all, metadata, remove_dir_all};
 use std::mem;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
 use xxhash_rust::xxh3::xxh3_64;
 
+pub use crate::mdbx::env::Durability;
+
 static INSTANCES: Lazy<RwLock<IntMap<Arc<IsarInstance>>>> =
     Lazy::new(|| RwLock::new(IntMap::new()));
 
@@ -28,41 +36,111 @@ pub struct IsarInstance {
     pub collections: Vec<IsarCollection>,
     pub(crate) instance_id: u64,
     pub(crate) schema_hash: u64,
+    read_only: bool,
 
     env: Env,
+    durability: Durability,
+    metrics: MetricsCounters,
+    max_size_limit: AtomicU64,
+    open_snapshots: AtomicU64,
     watchers: Mutex<IsarWatchers>,
     watcher_modifier_sender: Sender<WatcherModifier>,
 }
 
 impl IsarInstance {
+    pub const DEFAULT_MAX_SIZE_LIMIT: u64 = 4 << 30;
+    pub const MAX_SNAPSHOTS: u64 = 32;
+    const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(1);
+    const READ_ONLY_MAX_DBS: u64 = 1024;
+
     pub fn open(
         name: &str,
         dir: &str,
-        relaxed_durability: bool,
+        durability: Durability,
+        max_readers: Option<u32>,
         schema: Schema,
     ) -> Result<Arc<Self>> {
         let mut lock = INSTANCES.write().unwrap();
         let instance_id = xxh3_64(name.as_bytes());
         if let Some(instance) = lock.get(instance_id) {
-            if instance.schema_hash == schema.get_hash() {
+            if instance.read_only {
+                Err(IsarError::ReadOnly {})
+            } else if instance.schema_hash == schema.get_hash() {
                 Ok(instance.clone())
             } else {
                 Err(IsarError::SchemaMismatch {})
             }
         } else {
             let new_instance =
-                Self::open_internal(name, dir, instance_id, relaxed_durability, schema)?;
+                Self::open_internal(name, dir, instance_id, durability, max_readers, schema)?;
             let new_instance = Arc::new(new_instance);
             lock.insert(instance_id, new_instance.clone());
             Ok(new_instance)
         }
     }
 
+    /// Opens an existing instance using the schema stored in the database. The instance never
+    /// takes the write lock and every attempt to begin a write transaction fails with
+    /// [IsarError::ReadOnly]. Instances opened for writing lock the database exclusively so the
+    /// writer has to be closed, also in other processes, before the database can be opened
+    /// read-only.
+    pub fn open_read_only(name: &str, dir: &str, max_readers: Option<u32>) -> Result<Arc<Self>> {
+        let mut lock = INSTANCES.write().unwrap();
+        let instance_id = xxh3_64(name.as_bytes());
+        if let Some(instance) = lock.get(instance_id) {
+            if instance.read_only {
+                Ok(instance.clone())
+            } else {
+                illegal_arg("The instance is already open for writing.")
+            }
+        } else {
+            let new_instance = Self::open_read_only_internal(name, dir, instance_id, max_readers)?;
+            let new_instance = Arc::new(new_instance);
+            lock.insert(instance_id, new_instance.clone());
+            Ok(new_instance)
+        }
+    }
+
+    fn open_read_only_internal(
+        name: &str,
+        dir: &str,
+        instance_id: u64,
+        max_readers: Option<u32>,
+    ) -> Result<Self> {
+        let mut path_buf = PathBuf::from(dir);
+        path_buf.push(name);
+        let path = path_buf.as_path().to_str().unwrap();
+
+        let env = Env::open_read_only(path, Self::READ_ONLY_MAX_DBS, max_readers)
+            .map_err(|e| IsarError::EnvError { error: Box::new(e) })?;
+
+        let txn = env.txn(false)?;
+        let (schema, collections) = {
+            let mut manager = SchemaManger::create(instance_id, &txn)?;
+            let schema = manager.get_existing_schema()?;
+            let collections = manager.open_collections(&schema)?;
+            (schema, collections)
+        };
+        txn.commit()?;
+
+        Ok(Self::new(
+            env,
+            name,
+            dir,
+            instance_id,
+            schema.get_hash(),
+            true,
+            Durability::SafeSync,
+            collections,
+        ))
+    }
+
     fn open_internal(
         name: &str,
         dir: &str,
         instance_id: u64,
-        relaxed_durability: bool,
+        durability: Durability,
+        max_readers: Option<u32>,
         mut schema: Schema,
     ) -> Result<Self> {
         let schema_hash = schema.get_hash();
@@ -75,7 +153,7 @@ impl IsarInstance {
         }
 
         let db_count = schema.count_dbs() as u64 + 3;
-        let env = Env::create(path, db_count, relaxed_durability)
+        let env = Env::create(path, db_count, durability, max_readers)
             .map_err(|e| IsarError::EnvError { error: Box::new(e) })?;
 
         let txn = env.txn(true)?;
@@ -86,18 +164,45 @@ impl IsarInstance {
         };
         txn.commit()?;
 
-        let (tx, rx) = unbounded();
+        Ok(Self::new(
+            env,
+            name,
+            dir,
+            instance_id,
+            schema_hash,
+            false,
+            durability,
+            collections,
+        ))
+    }
 
-        Ok(IsarInstance {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        env: Env,
+        name: &str,
+        dir: &str,
+        instance_id: u64,
+        schema_hash: u64,
+        read_only: bool,
+        durability: Durability,
+        collections: Vec<IsarCollection>,
+    ) -> Self {
+        let (tx, rx) = unbounded();
+        IsarInstance {
             env,
+            durability,
+            metrics: MetricsCounters::default(),
+            max_size_limit: AtomicU64::new(Self::DEFAULT_MAX_SIZE_LIMIT),
+            open_snapshots: AtomicU64::new(0),
             name: name.to_string(),
             dir: dir.to_string(),
             collections,
             instance_id,
             schema_hash,
+            read_only,
             watchers: Mutex::new(IsarWatchers::new(rx)),
             watcher_modifier_sender: tx,
-        })
+        }
     }
 
     pub fn get_instance(name: &str) -> Option<Arc<Self>> {
@@ -105,7 +210,15 @@ impl IsarInstance {
         INSTANCES.read().unwrap().get(instance_id).cloned()
     }
 
+    /// Whether the instance was opened using [IsarInstance::open_read_only].
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     pub fn begin_txn(&self, write: bool, silent: bool) -> Result<IsarTxn> {
+        if write && self.read_only {
+            return Err(IsarError::ReadOnly {});
+        }
         let change_set = if write && !silent {
             let mut watchers_lock = self.watchers.lock().unwrap();
             watchers_lock.sync();
@@ -116,7 +229,197 @@ impl IsarInstance {
         };
 
         let txn = self.env.txn(write)?;
-        IsarTxn::new(self.instance_id, txn, write, change_set)
+        let tracker = self.metrics.track_txn(write);
+        IsarTxn::new(
+            self.instance_id,
+            txn,
+            write,
+            self.durability,
+            change_set,
+            tracker,
+        )
+    }
+
+    /// Like [IsarInstance::begin_txn] but fails with [IsarError::Timeout] if the write lock
+    /// cannot be acquired within `timeout`. Read transactions never wait for the lock.
+    pub fn begin_txn_timeout(
+        &self,
+        write: bool,
+        silent: bool,
+        timeout: Duration,
+    ) -> Result<IsarTxn> {
+        if !write || self.read_only {
+            return self.begin_txn(write, silent);
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(txn) = self.try_begin_write_txn(silent)? {
+                return Ok(txn);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(IsarError::Timeout {});
+            }
+            thread::sleep((deadline - now).min(Self::LOCK_RETRY_INTERVAL));
+        }
+    }
+
+    fn try_begin_write_txn(&self, silent: bool) -> Result<Option<IsarTxn>> {
+        let change_set = if !silent {
+            let mut watchers_lock = match self.watchers.try_lock() {
+                Err(TryLockError::WouldBlock) => return Ok(None),
+                lock => lock.unwrap(),
+            };
+            watchers_lock.sync();
+            Some(ChangeSet::new(watchers_lock))
+        } else {
+            None
+        };
+
+        if let Some(txn) = self.env.try_write_txn()? {
+            let tracker = self.metrics.track_txn(true);
+            let txn = IsarTxn::new(
+                self.instance_id,
+                txn,
+                true,
+                self.durability,
+                change_set,
+                tracker,
+            )?;
+            Ok(Some(txn))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Begins a read transaction that is not bound to a borrow of the instance. A snapshot sees
+    /// the database as it was when the snapshot was started and does not block writers but it
+    /// occupies a reader slot until it is closed. The instance cannot be closed while snapshots
+    /// are open.
+    pub fn begin_snapshot(self: &Arc<Self>) -> Result<IsarSnapshot> {
+        let txn = self.begin_txn(false, false)?;
+        if self.open_snapshots.fetch_add(1, Ordering::SeqCst) >= Self::MAX_SNAPSHOTS {
+            self.release_snapshot();
+            return Err(IsarError::TooManySnapshots {});
+        }
+        // the snapshot keeps the instance and thereby the environment alive
+        let txn = unsafe { mem::transmute::<IsarTxn, IsarTxn<'static>>(txn) };
+        Ok(IsarSnapshot::new(txn, self.clone()))
+    }
+
+    pub(crate) fn release_snapshot(&self) {
+        self.open_snapshots.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Returns counters about the transactions of this instance which can help to diagnose
+    /// contention between readers and writers.
+    pub fn metrics(&self) -> InstanceMetrics {
+        self.metrics.get()
+    }
+
+    /// Releases reader slots of transactions that have not been closed properly, for example
+    /// because the process crashed. Returns the number of released slots.
+    pub fn reader_check(&self) -> Result<u32> {
+        self.env.reader_check()
+    }
+
+    /// Flushes all committed transactions to disk. This is only necessary if the instance or
+    /// some of its transactions use a durability weaker than [Durability::SafeSync]. It does not
+    /// require a transaction and may be called while others are open.
+    pub fn sync(&self) -> Result<()> {
+        if self.durability == Durability::SafeSync {
+            Ok(())
+        } else {
+            self.env.sync()
+        }
+    }
+
+    /// Writes a compacted copy of the database to `target_path` and returns the size of the copy
+    /// in bytes. Fails if a write transaction is active. The write lock is held while the copy
+    /// is written so no write transaction can start in the meantime.
+    pub fn compact(&self, target_path: &str) -> Result<u64> {
+        let _lock = if self.read_only {
+            None
+        } else {
+            let lock = self.env.try_lock_write()?;
+            Some(lock.ok_or(IsarError::WriteTxnActive {})?)
+        };
+        self.env.copy_compact(target_path)?;
+        let metadata = metadata(target_path).map_err(|_| IsarError::PathError {})?;
+        Ok(metadata.len())
+    }
+
+    /// Returns the size statistics of the data, index and link databases of every collection.
+    pub fn get_db_stats(&self, txn: &mut IsarTxn) -> Result<Vec<CollectionStats>> {
+        self.collections
+            .iter()
+            .map(|col| col.get_stats(txn))
+            .collect()
+    }
+
+    /// Checks the index and link databases of every collection for entries that are inconsistent
+    /// with the stored objects. Corruption is reported as issues instead of errors.
+    pub fn verify(&self, txn: &mut IsarTxn) -> Result<Vec<IntegrityIssue>> {
+        let mut issues = vec![];
+        for col in &self.collections {
+            let col_issues = txn.read(self.instance_id, |cursors| col.verify(cursors))?;
+            issues.extend(col_issues);
+        }
+        Ok(issues)
+    }
+
+    /// Runs `job` in a new write transaction and commits it. If the database is full, the
+    /// transaction is aborted, the maximum database size is doubled and `job` runs once more.
+    /// Generated ids are reset before the retry so `job` produces the same ids again.
+    pub fn write_txn<T, F>(&self, silent: bool, mut job: F) -> Result<T>
+    where
+        F: FnMut(&mut IsarTxn) -> Result<T>,
+    {
+        let auto_increments = self
+            .collections
+            .iter()
+            .map(|col| col.get_auto_increment())
+            .collect_vec();
+        match self.write_txn_internal(silent, &mut job) {
+            Err(IsarError::DbFull {}) => {
+                for (col, auto_increment) in self.collections.iter().zip(auto_increments) {
+                    col.set_auto_increment(auto_increment);
+                }
+                self.grow_max_size()?;
+                self.write_txn_internal(silent, &mut job)
+            }
+            result => result,
+        }
+    }
+
+    fn write_txn_internal<T, F>(&self, silent: bool, job: &mut F) -> Result<T>
+    where
+        F: FnMut(&mut IsarTxn) -> Result<T>,
+    {
+        let mut txn = self.begin_txn(true, silent)?;
+        let result = job(&mut txn)?;
+        txn.commit()?;
+        Ok(result)
+    }
+
+    /// Sets the size in bytes up to which the database may grow automatically.
+    pub fn set_max_size_limit(&self, limit: u64) {
+        self.max_size_limit.store(limit, Ordering::SeqCst);
+    }
+
+    /// Sets the current maximum size of the database in bytes. [IsarInstance::write_txn] grows
+    /// it further up to the max size limit when it is reached.
+    pub fn set_max_size(&self, size: u64) -> Result<()> {
+        self.env.set_max_size(size)
+    }
+
+    fn grow_max_size(&self) -> Result<()> {
+        let max_size = self.env.get_max_size()?;
+        let limit = self.max_size_limit.load(Ordering::SeqCst);
+        if max_size >= limit {
+            return Err(IsarError::MapSizeLimitReached {});
+        }
+        self.env.set_max_size(max_size.saturating_mul(2).min(limit))
     }
 
     fn new_watcher(&self, start: WatcherModifier, stop: WatcherModifier) -> WatchHandle {
@@ -150,7 +453,7 @@ impl IsarInstance {
         &self,
         collection: &IsarCollection,
         oid: i64,
-        callback: WatcherCallback,
+        callback: ObjectWatcherCallback,
     ) -> WatchHandle {
         let watcher_id = random();
         let col_id = collection.get_runtime_id();
@@ -166,6 +469,9 @@ impl IsarInstance {
         )
     }
 
+    /// Notifies the callback when the results of the query may have changed. The query's
+    /// collection is watched using the query, all collections reachable via link filters are
+    /// watched as a whole.
     pub fn watch_query(
         &self,
         collection: &IsarCollection,
@@ -174,12 +480,28 @@ impl IsarInstance {
     ) -> WatchHandle {
         let watcher_id = random();
         let col_id = collection.get_runtime_id();
+        let linked_col_ids = query.get_linked_collections();
+        let stop_linked_col_ids = linked_col_ids.clone();
+        let callback = Arc::new(callback);
         self.new_watcher(
             Box::new(move |iw| {
-                iw.get_col_watchers(col_id)
-                    .add_query_watcher(watcher_id, query, callback);
+                // linked collections are watched coarse-grained
+                for linked_col_id in linked_col_ids {
+                    let callback = callback.clone();
+                    iw.get_col_watchers(linked_col_id)
+                        .add_watcher(watcher_id, Box::new(move || (*callback)()));
+                }
+                iw.get_col_watchers(col_id).add_query_watcher(
+                    watcher_id,
+                    query,
+                    Box::new(move || (*callback)()),
+                );
             }),
             Box::new(move |iw| {
+                for linked_col_id in stop_linked_col_ids {
+                    iw.get_col_watchers(linked_col_id)
+                        .remove_watcher(watcher_id);
+                }
                 iw.get_col_watchers(col_id).remove_query_watcher(watcher_id);
             }),
         )
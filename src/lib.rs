@@ -11,9 +11,11 @@ pub mod index;
 pub mod instance;
 mod link;
 mod mdbx;
+pub mod metrics;
 pub mod object;
 pub mod query;
 pub mod schema;
+pub mod snapshot;
 pub mod txn;
 pub mod verify;
 pub mod watch;
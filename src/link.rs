@@ -3,7 +3,7 @@ use crate::error::IsarError::DbCorrupted;
 use crate::error::{IsarError, Result};
 use crate::id_key::IdKey;
 use crate::mdbx::cursor::Cursor;
-use crate::mdbx::db::Db;
+use crate::mdbx::db::{Db, DbStats};
 use crate::mdbx::debug_dump_db;
 use crate::object::isar_object::IsarObject;
 use crate::txn::IsarTxn;
@@ -31,6 +31,22 @@ impl IsarLink {
         self.target_db.runtime_id()
     }
 
+    pub fn is_self_link(&self) -> bool {
+        self.source_db == self.target_db
+    }
+
+    /// Returns the reverse link from the target collection to the source collection. The link
+    /// and backlink dbs swap roles so the entries of both directions stay separate even if the
+    /// link points to its own collection.
+    pub fn to_backlink(&self) -> IsarLink {
+        IsarLink {
+            db: self.bl_db,
+            bl_db: self.db,
+            source_db: self.target_db,
+            target_db: self.source_db,
+        }
+    }
+
     pub fn iter_ids<F>(
         &self,
         cursors: &IsarCursors,
@@ -46,6 +62,15 @@ impl IsarLink {
         })
     }
 
+    pub fn count(&self, cursors: &IsarCursors, id_key: &IdKey) -> Result<u64> {
+        let mut count = 0;
+        self.iter_ids(cursors, id_key, |_, _| {
+            count += 1;
+            Ok(true)
+        })?;
+        Ok(count)
+    }
+
     pub fn iter<'txn, 'env, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
@@ -90,6 +115,68 @@ impl IsarLink {
         Ok(true)
     }
 
+    /// Links the source object to all target objects in a single pass. Targets that do not exist
+    /// are skipped. Returns the number of newly created links.
+    pub fn create_all(
+        &self,
+        cursors: &IsarCursors,
+        source_key: &IdKey,
+        target_ids: &[i64],
+    ) -> Result<u32> {
+        let mut source_cursor = cursors.get_cursor(self.source_db)?;
+        if source_cursor.move_to(source_key.as_bytes())?.is_none() {
+            return Ok(0);
+        }
+
+        let mut target_cursor = cursors.get_cursor(self.target_db)?;
+        let mut link_cursor = cursors.get_cursor(self.db)?;
+        let mut backlink_cursor = cursors.get_cursor(self.bl_db)?;
+        let mut count = 0;
+        for target_id in target_ids {
+            let target_key = IdKey::new(*target_id);
+            if target_cursor.move_to(target_key.as_bytes())?.is_none() {
+                continue;
+            }
+            let exists = link_cursor
+                .move_to_key_val(source_key.as_bytes(), target_key.as_bytes())?
+                .is_some();
+            if !exists {
+                link_cursor.put(source_key.as_bytes(), target_key.as_bytes())?;
+                backlink_cursor.put(target_key.as_bytes(), source_key.as_bytes())?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Replaces the links of the source object with links to the given targets. Links that exist
+    /// in both sets are not modified. Returns the number of added and removed links.
+    pub fn replace(
+        &self,
+        cursors: &IsarCursors,
+        source_key: &IdKey,
+        target_ids: &[i64],
+    ) -> Result<(u32, u32)> {
+        let new_ids: HashSet<i64> = target_ids.iter().copied().collect();
+        let mut removed_ids = vec![];
+        self.iter_ids(cursors, source_key, |_, target_key| {
+            let target_id = target_key.get_id();
+            if !new_ids.contains(&target_id) {
+                removed_ids.push(target_id);
+            }
+            Ok(true)
+        })?;
+
+        let mut removed = 0;
+        for target_id in removed_ids {
+            if self.delete(cursors, source_key, &IdKey::new(target_id))? {
+                removed += 1;
+            }
+        }
+        let added = self.create_all(cursors, source_key, target_ids)?;
+        Ok((added, removed))
+    }
+
     pub fn delete(
         &self,
         cursors: &IsarCursors,
@@ -139,6 +226,47 @@ impl IsarLink {
         Ok(())
     }
 
+    /// Iterates all links of all source objects.
+    pub(crate) fn iter_all<F>(&self, cursors: &IsarCursors, mut callback: F) -> Result<bool>
+    where
+        F: FnMut(IdKey, IdKey) -> Result<bool>,
+    {
+        let mut cursor = cursors.get_cursor(self.db)?;
+        cursor.iter_between(
+            &u64::MIN.to_le_bytes(),
+            &u64::MAX.to_le_bytes(),
+            false,
+            false,
+            true,
+            |_, source_key, target_key| {
+                callback(IdKey::from_bytes(source_key), IdKey::from_bytes(target_key))
+            },
+        )
+    }
+
+    pub(crate) fn backlink_exists(
+        &self,
+        cursors: &IsarCursors,
+        source_key: &IdKey,
+        target_key: &IdKey,
+    ) -> Result<bool> {
+        let mut backlink_cursor = cursors.get_cursor(self.bl_db)?;
+        let exists = backlink_cursor
+            .move_to_key_val(target_key.as_bytes(), source_key.as_bytes())?
+            .is_some();
+        Ok(exists)
+    }
+
+    pub fn get_stats(&self, cursors: &IsarCursors) -> Result<DbStats> {
+        cursors.db_stats(self.db)
+    }
+
+    /// The number of bytes occupied by the link and its backlinks.
+    pub fn size_in_bytes(&self, cursors: &IsarCursors) -> Result<u64> {
+        let size = cursors.db_stats(self.db)?.size_in_bytes();
+        Ok(size + cursors.db_stats(self.bl_db)?.size_in_bytes())
+    }
+
     pub fn clear(&self, txn: &mut IsarTxn) -> Result<()> {
         txn.clear_db(self.db)?;
         txn.clear_db(self.bl_db)
@@ -128,12 +128,21 @@ impl<'txn> Cursor<'txn> {
         ascending: bool,
         duplicates: bool,
     ) -> Result<Option<KeyVal<'txn>>> {
-        if upper_key < lower_key {
+        if upper_key.cmp_prefix(lower_key.bytes) == Ordering::Less {
             return Ok(None);
         }
 
         let first_entry = if !ascending {
-            if let Some(first_entry) = self.move_to_gte(upper_key.bytes)? {
+            let mut entry = self.move_to_gte(upper_key.bytes)?;
+            while let Some((key, _)) = entry {
+                if upper_key.cmp_prefix(key) == Ordering::Equal {
+                    entry = self.op_get(ffi::MDBX_cursor_op::MDBX_NEXT_NODUP, None, None)?;
+                } else {
+                    break;
+                }
+            }
+
+            if let Some(first_entry) = entry {
                 if duplicates {
                     self.move_to_last_dup()?.or(Some(first_entry))
                 } else {
@@ -154,7 +163,7 @@ impl<'txn> Cursor<'txn> {
         };
 
         if let Some(first_entry) = first_entry {
-            if upper_key < ByteKey::new(first_entry.0) {
+            if upper_key.cmp_prefix(first_entry.0) == Ordering::Less {
                 if !ascending {
                     if let Some(prev) = self.move_to_prev_no_dup()? {
                         if lower_key <= ByteKey::new(prev.0) {
@@ -171,6 +180,8 @@ impl<'txn> Cursor<'txn> {
         }
     }
 
+    /// Iterates all entries with `lower_key <= key` whose first bytes are at most `upper_key`.
+    /// Keys starting with `upper_key` are included which allows binding a prefix of a composite key.
     pub fn iter_between(
         &mut self,
         lower_key: &[u8],
@@ -183,7 +194,7 @@ impl<'txn> Cursor<'txn> {
         let lower_key = ByteKey::new(lower_key);
         let upper_key = ByteKey::new(upper_key);
 
-        if upper_key < lower_key {
+        if upper_key.cmp_prefix(lower_key.bytes) == Ordering::Less {
             return Ok(true);
         }
 
@@ -205,7 +216,7 @@ impl<'txn> Cursor<'txn> {
         };
         loop {
             if let Some((key, val)) = self.op_get(next, None, None)? {
-                if (ascending && upper_key.cmp_bytes(key) == Ordering::Less)
+                if (ascending && upper_key.cmp_prefix(key) == Ordering::Less)
                     || (!ascending && lower_key.cmp_bytes(key) == Ordering::Greater)
                 {
                     return Ok(true);
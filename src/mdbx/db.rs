@@ -4,6 +4,21 @@ use crate::mdbx::txn::Txn;
 use std::ffi::CString;
 use std::ptr;
 
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct DbStats {
+    pub entries: u64,
+    pub pages: u64,
+    pub depth: u32,
+    pub page_size: u32,
+}
+
+impl DbStats {
+    /// The number of bytes occupied by the pages of the database.
+    pub fn size_in_bytes(&self) -> u64 {
+        self.pages * self.page_size as u64
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Db {
     pub(crate) dbi: ffi::MDBX_dbi,
@@ -48,6 +63,28 @@ impl Db {
         Ok(Self { dbi, dup })
     }
 
+    pub fn count(&self, txn: &Txn) -> Result<u64> {
+        Ok(self.stats(txn)?.entries)
+    }
+
+    pub fn stats(&self, txn: &Txn) -> Result<DbStats> {
+        let mut stat: ffi::MDBX_stat = unsafe { std::mem::zeroed() };
+        unsafe {
+            mdbx_result(ffi::mdbx_dbi_stat(
+                txn.txn,
+                self.dbi,
+                &mut stat,
+                std::mem::size_of::<ffi::MDBX_stat>() as ffi::size_t,
+            ))?;
+        }
+        Ok(DbStats {
+            entries: stat.ms_entries as u64,
+            pages: (stat.ms_branch_pages + stat.ms_leaf_pages + stat.ms_overflow_pages) as u64,
+            depth: stat.ms_depth as u32,
+            page_size: stat.ms_psize as u32,
+        })
+    }
+
     pub fn clear(&self, txn: &Txn) -> Result<()> {
         unsafe { mdbx_result(ffi::mdbx_drop(txn.txn, self.dbi, false)) }?;
         Ok(())
@@ -13,8 +13,66 @@ unsafe impl Send for Env {}
 
 const MB: isize = 1 << 20;
 
+/// Determines how commits of write transactions are flushed to disk. The database stays
+/// consistent after a crash in every mode but the weaker modes may lose the most recent
+/// transactions if the system crashes before the data has been flushed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Durability {
+    /// Every commit is flushed to disk before it returns.
+    SafeSync,
+    /// The data of a commit is flushed but the meta page is flushed lazily. A system crash may
+    /// roll back the last transactions.
+    NoMetaSync,
+    /// Commits are not flushed. A system crash may roll back all transactions since the last
+    /// flush. Application crashes do not lose data.
+    NoSync,
+}
+
+impl Durability {
+    fn level(self) -> u8 {
+        match self {
+            Durability::NoSync => 0,
+            Durability::NoMetaSync => 1,
+            Durability::SafeSync => 2,
+        }
+    }
+
+    pub(crate) fn is_weaker_than(self, other: Durability) -> bool {
+        self.level() < other.level()
+    }
+}
+
 impl Env {
-    pub fn create(path: &str, max_dbs: u64, relaxed_durability: bool) -> Result<Env> {
+    pub fn create(
+        path: &str,
+        max_dbs: u64,
+        durability: Durability,
+        max_readers: Option<u32>,
+    ) -> Result<Env> {
+        let mut flags =
+            ffi::MDBX_NOTLS | ffi::MDBX_EXCLUSIVE | ffi::MDBX_NOMEMINIT | ffi::MDBX_COALESCE;
+        flags |= match durability {
+            Durability::SafeSync => 0,
+            Durability::NoMetaSync => ffi::MDBX_NOMETASYNC,
+            Durability::NoSync => ffi::MDBX_SAFE_NOSYNC,
+        };
+        Self::open(path, max_dbs, max_readers, flags)
+    }
+
+    /// Opens an existing environment without taking the write lock. Write transactions cannot
+    /// be started. Fails while the environment is open for writing because writers open it
+    /// exclusively.
+    pub fn open_read_only(path: &str, max_dbs: u64, max_readers: Option<u32>) -> Result<Env> {
+        let flags = ffi::MDBX_NOTLS | ffi::MDBX_RDONLY;
+        Self::open(path, max_dbs, max_readers, flags)
+    }
+
+    fn open(
+        path: &str,
+        max_dbs: u64,
+        max_readers: Option<u32>,
+        flags: ffi::MDBX_env_flags_t,
+    ) -> Result<Env> {
         let path = CString::new(path.as_bytes()).unwrap();
         let mut env: *mut ffi::MDBX_env = ptr::null_mut();
         unsafe {
@@ -24,11 +82,12 @@ impl Env {
                 ffi::MDBX_option_t::MDBX_opt_max_db,
                 max_dbs,
             ))?;
-
-            let mut flags =
-                ffi::MDBX_NOTLS | ffi::MDBX_EXCLUSIVE | ffi::MDBX_NOMEMINIT | ffi::MDBX_COALESCE;
-            if relaxed_durability {
-                flags |= ffi::MDBX_NOMETASYNC;
+            if let Some(max_readers) = max_readers {
+                mdbx_result(ffi::mdbx_env_set_option(
+                    env,
+                    ffi::MDBX_option_t::MDBX_opt_max_readers,
+                    max_readers as u64,
+                ))?;
             }
 
             let mut err_code = 0;
@@ -74,6 +133,102 @@ impl Env {
         }
         Ok(Txn::new(txn))
     }
+
+    /// Begins a write transaction without waiting for the write lock. Returns `None` if another
+    /// write transaction is active.
+    pub fn try_write_txn(&self) -> Result<Option<Txn>> {
+        let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
+        let err_code = unsafe {
+            ffi::mdbx_txn_begin_ex(
+                self.env,
+                ptr::null_mut(),
+                ffi::MDBX_TXN_TRY,
+                &mut txn,
+                ptr::null_mut(),
+            )
+        };
+        if err_code == ffi::MDBX_BUSY {
+            return Ok(None);
+        }
+        mdbx_result(err_code)?;
+        Ok(Some(Txn::new(txn)))
+    }
+
+    pub fn get_max_size(&self) -> Result<u64> {
+        let mut info: ffi::MDBX_envinfo = unsafe { std::mem::zeroed() };
+        unsafe {
+            mdbx_result(ffi::mdbx_env_info_ex(
+                self.env,
+                ptr::null(),
+                &mut info,
+                std::mem::size_of::<ffi::MDBX_envinfo>() as ffi::size_t,
+            ))?;
+        }
+        Ok(info.mi_geo.upper as u64)
+    }
+
+    pub fn set_max_size(&self, size: u64) -> Result<()> {
+        unsafe {
+            mdbx_result(ffi::mdbx_env_set_geometry(
+                self.env,
+                -1,
+                -1,
+                size as isize,
+                -1,
+                -1,
+                -1,
+            ))
+        }
+    }
+
+    /// Acquires the write lock without starting a transaction. Returns `None` if a write
+    /// transaction is active. No write transaction can be started until the lock is dropped.
+    pub fn try_lock_write(&self) -> Result<Option<WriteLock>> {
+        let err_code = unsafe { ffi::mdbx_txn_lock(self.env, true) };
+        if err_code == ffi::MDBX_BUSY {
+            return Ok(None);
+        }
+        mdbx_result(err_code)?;
+        Ok(Some(WriteLock { env: self }))
+    }
+
+    /// Releases reader slots of transactions that were not closed properly and returns the
+    /// number of cleared slots.
+    pub fn reader_check(&self) -> Result<u32> {
+        let mut dead = 0;
+        unsafe {
+            mdbx_result(ffi::mdbx_reader_check(self.env, &mut dead))?;
+        }
+        Ok(dead as u32)
+    }
+
+    /// Flushes all committed transactions to disk.
+    pub fn sync(&self) -> Result<()> {
+        unsafe { mdbx_result(ffi::mdbx_env_sync_ex(self.env, true, false)) }
+    }
+
+    pub fn copy_compact(&self, path: &str) -> Result<()> {
+        let path = CString::new(path.as_bytes()).unwrap();
+        unsafe {
+            mdbx_result(ffi::mdbx_env_copy(
+                self.env,
+                path.as_ptr(),
+                ffi::MDBX_CP_COMPACT,
+            ))
+        }
+    }
+}
+
+pub struct WriteLock<'env> {
+    env: &'env Env,
+}
+
+impl<'env> Drop for WriteLock<'env> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::mdbx_txn_unlock(self.env.env);
+        }
+    }
 }
 
 impl Drop for Env {
@@ -101,6 +256,29 @@ pub mod tests {
         let mut dir = std::env::temp_dir();
         let r: u64 = rand::random();
         dir.push(&r.to_string());
-        Env::create(dir.to_str().unwrap(), 50, false).unwrap()
+        Env::create(dir.to_str().unwrap(), 50, Durability::SafeSync, None).unwrap()
+    }
+
+    #[test]
+    fn test_readers_full() {
+        let mut dir = std::env::temp_dir();
+        let r: u64 = rand::random();
+        dir.push(&r.to_string());
+        let env = Env::create(dir.to_str().unwrap(), 50, Durability::SafeSync, Some(8)).unwrap();
+
+        let mut txns = vec![];
+        let err = loop {
+            match env.txn(false) {
+                Ok(txn) => txns.push(txn),
+                Err(e) => break e,
+            }
+            assert!(txns.len() < 1000);
+        };
+        assert_eq!(err, IsarError::ReadersFull {});
+        assert!(txns.len() >= 8);
+
+        txns.clear();
+        assert_eq!(env.reader_check(), Ok(0));
+        assert!(env.txn(false).is_ok());
     }
 }
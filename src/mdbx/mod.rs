@@ -43,6 +43,7 @@ pub fn mdbx_result(err_code: c_int) -> Result<()> {
     match err_code {
         ffi::MDBX_SUCCESS | ffi::MDBX_RESULT_TRUE => Ok(()),
         ffi::MDBX_MAP_FULL => Err(IsarError::DbFull {}),
+        ffi::MDBX_READERS_FULL => Err(IsarError::ReadersFull {}),
         other => unsafe {
             let err_raw = ffi::mdbx_strerror(other);
             let err = CStr::from_ptr(err_raw);
@@ -74,6 +75,19 @@ impl<'a> ByteKey<'a> {
             cmp
         }
     }
+
+    /// Compares only the first `self.bytes.len()` bytes of `other` so keys starting with this key
+    /// are considered equal.
+    #[inline]
+    pub fn cmp_prefix(&self, other: &[u8]) -> Ordering {
+        let len = min(self.bytes.len(), other.len());
+        let cmp = (&self.bytes[0..len]).cmp(&other[0..len]);
+        if cmp == Ordering::Equal && other.len() < self.bytes.len() {
+            Ordering::Greater
+        } else {
+            cmp
+        }
+    }
 }
 
 impl<'a> Ord for ByteKey<'a> {
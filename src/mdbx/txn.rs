@@ -16,6 +16,23 @@ impl<'env> Txn<'env> {
         }
     }
 
+    /// Begins a nested write transaction. The parent may not be used until the nested
+    /// transaction is committed or aborted.
+    pub fn nested(&self) -> Result<Txn<'env>> {
+        let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
+        unsafe {
+            let env = ffi::mdbx_txn_env(self.txn);
+            mdbx_result(ffi::mdbx_txn_begin_ex(
+                env,
+                self.txn,
+                0,
+                &mut txn,
+                ptr::null_mut(),
+            ))?;
+        }
+        Ok(Txn::new(txn))
+    }
+
     pub fn commit(mut self) -> Result<()> {
         let result = unsafe { mdbx_result(ffi::mdbx_txn_commit_ex(self.txn, ptr::null_mut())) };
         self.txn = ptr::null_mut();
@@ -23,6 +40,14 @@ impl<'env> Txn<'env> {
         Ok(())
     }
 
+    /// Commits the transaction and flushes the environment to disk even if commits are not
+    /// flushed by default.
+    pub fn commit_and_sync(self) -> Result<()> {
+        let env = unsafe { ffi::mdbx_txn_env(self.txn) };
+        self.commit()?;
+        unsafe { mdbx_result(ffi::mdbx_env_sync_ex(env, true, false)) }
+    }
+
     pub fn abort(self) {}
 }
 
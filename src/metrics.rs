@@ -0,0 +1,84 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Transaction counters of an instance since it has been opened.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct InstanceMetrics {
+    pub write_txns_committed: u64,
+    /// Write transactions that have been aborted or failed to commit.
+    pub write_txns_aborted: u64,
+    pub read_txns_opened: u64,
+    /// Total time write transactions have held the write lock in microseconds.
+    pub write_lock_micros: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct MetricsCounters {
+    write_txns_committed: AtomicU64,
+    write_txns_aborted: AtomicU64,
+    read_txns_opened: AtomicU64,
+    write_lock_micros: AtomicU64,
+}
+
+impl MetricsCounters {
+    pub fn get(&self) -> InstanceMetrics {
+        InstanceMetrics {
+            write_txns_committed: self.write_txns_committed.load(Ordering::Relaxed),
+            write_txns_aborted: self.write_txns_aborted.load(Ordering::Relaxed),
+            read_txns_opened: self.read_txns_opened.load(Ordering::Relaxed),
+            write_lock_micros: self.write_lock_micros.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn track_txn(&self, write: bool) -> TxnTracker {
+        if !write {
+            self.read_txns_opened.fetch_add(1, Ordering::Relaxed);
+        }
+        TxnTracker {
+            counters: self,
+            write,
+            write_start: if write { Some(Instant::now()) } else { None },
+            committed: false,
+        }
+    }
+}
+
+/// Records the end of a transaction when it is dropped.
+pub(crate) struct TxnTracker<'a> {
+    counters: &'a MetricsCounters,
+    write: bool,
+    write_start: Option<Instant>,
+    committed: bool,
+}
+
+impl<'a> TxnTracker<'a> {
+    /// Has to be called right after the commit so the time spent notifying watchers is not
+    /// counted as holding the write lock.
+    pub fn set_committed(&mut self) {
+        self.committed = true;
+        self.record_write_lock();
+    }
+
+    fn record_write_lock(&mut self) {
+        if let Some(write_start) = self.write_start.take() {
+            let micros = write_start.elapsed().as_micros() as u64;
+            self.counters
+                .write_lock_micros
+                .fetch_add(micros, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<'a> Drop for TxnTracker<'a> {
+    fn drop(&mut self) {
+        if self.write {
+            self.record_write_lock();
+            let counter = if self.committed {
+                &self.counters.write_txns_committed
+            } else {
+                &self.counters.write_txns_aborted
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
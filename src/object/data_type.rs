@@ -2,14 +2,15 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Hash)]
 pub enum DataType {
-    #[serde(alias = "Bool")]
+    Bool,
     Byte,
     Int,
     Float,
     Long,
     Double,
     String,
-    #[serde(alias = "BoolList")]
+    DateTime,
+    Decimal,
     ByteList,
     IntList,
     FloatList,
@@ -22,7 +23,14 @@ impl DataType {
     pub fn is_static(&self) -> bool {
         matches!(
             &self,
-            DataType::Int | DataType::Long | DataType::Float | DataType::Double | DataType::Byte
+            DataType::Int
+                | DataType::Long
+                | DataType::Float
+                | DataType::Double
+                | DataType::Bool
+                | DataType::Byte
+                | DataType::DateTime
+                | DataType::Decimal
         )
     }
 
@@ -32,7 +40,7 @@ impl DataType {
 
     pub fn get_static_size(&self) -> usize {
         match *self {
-            DataType::Byte => 1,
+            DataType::Bool | DataType::Byte => 1,
             DataType::Int | DataType::Float => 4,
             _ => 8,
         }
@@ -1,19 +1,28 @@
+use crate::error::{illegal_arg, Result};
 use crate::object::data_type::DataType;
 use crate::object::object_builder::ObjectBuilder;
 use byteorder::{ByteOrder, LittleEndian};
+use itertools::Itertools;
 use num_traits::Float;
 use std::cmp::Ordering;
+use std::ops::Range;
 use xxhash_rust::xxh3::xxh3_64_with_seed;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Property {
     pub data_type: DataType,
     pub offset: usize,
+    /// Number of decimal places of a [DataType::Decimal] property.
+    pub scale: u8,
 }
 
 impl Property {
     pub const fn new(data_type: DataType, offset: usize) -> Self {
-        Property { data_type, offset }
+        Property {
+            data_type,
+            offset,
+            scale: 0,
+        }
     }
 }
 
@@ -37,10 +46,105 @@ impl<'a> IsarObject<'a> {
         IsarObject { bytes, static_size }
     }
 
-    pub fn as_bytes(&self) -> &[u8] {
+    pub fn as_bytes(&self) -> &'a [u8] {
         self.bytes
     }
 
+    /// Checks that all data referenced by `properties` lies within `bytes`, that dynamic data
+    /// does not overlap and that strings are valid UTF-8. Size mismatches are reported with the
+    /// expected and actual number of bytes.
+    pub fn verify(bytes: &[u8], properties: &[Property]) -> Result<()> {
+        if bytes.len() < 2 {
+            return illegal_arg(&format!(
+                "Object is too small: expected at least 2 bytes but got {}",
+                bytes.len()
+            ));
+        }
+        let object = IsarObject::from_bytes(bytes);
+        if object.static_size < 2 {
+            return illegal_arg(&format!(
+                "Static size of the object is out of bounds: expected at least 2 bytes but got {}",
+                object.static_size
+            ));
+        } else if object.static_size > bytes.len() {
+            return illegal_arg(&format!(
+                "Object is too small: expected a static size of {} bytes but got {} bytes",
+                object.static_size,
+                bytes.len()
+            ));
+        }
+
+        let mut ranges = vec![];
+        for property in properties {
+            if !object.contains_property(*property) {
+                continue;
+            }
+            let property_end = property.offset + property.data_type.get_static_size();
+            if property_end > object.static_size {
+                return illegal_arg(&format!(
+                    "Property exceeds the static section of the object: expected a static size \
+                    of at least {} bytes but got {} bytes",
+                    property_end, object.static_size
+                ));
+            }
+            if property.data_type.is_static() {
+                continue;
+            }
+
+            if let Some((offset, length)) = object.get_offset_length(property.offset, false) {
+                let element_size = match property.data_type {
+                    DataType::String | DataType::ByteList => 1,
+                    DataType::IntList | DataType::FloatList => 4,
+                    _ => 8,
+                };
+                let range = object.verify_range(offset, length, element_size)?;
+                if property.data_type == DataType::String {
+                    Self::verify_str(&bytes[range.clone()])?;
+                } else if property.data_type == DataType::StringList {
+                    for entry in range.clone().step_by(8) {
+                        if let Some((offset, length)) = object.get_offset_length(entry, true) {
+                            let str_range = object.verify_range(offset, length, 1)?;
+                            Self::verify_str(&bytes[str_range.clone()])?;
+                            ranges.push(str_range);
+                        }
+                    }
+                }
+                ranges.push(range);
+            }
+        }
+
+        ranges.sort_unstable_by_key(|r| (r.start, r.end));
+        for (r1, r2) in ranges.iter().tuple_windows() {
+            if r2.start < r1.end {
+                return illegal_arg("Dynamic data of the object overlaps");
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_range(
+        &self,
+        offset: usize,
+        length: usize,
+        element_size: usize,
+    ) -> Result<Range<usize>> {
+        let end = length
+            .checked_mul(element_size)
+            .and_then(|len| offset.checked_add(len));
+        match end {
+            Some(end) if offset >= self.static_size && end <= self.bytes.len() => Ok(offset..end),
+            _ => illegal_arg("Dynamic data of the object is out of bounds"),
+        }
+    }
+
+    fn verify_str(bytes: &[u8]) -> Result<()> {
+        if std::str::from_utf8(bytes).is_err() {
+            illegal_arg("String is not valid UTF-8")
+        } else {
+            Ok(())
+        }
+    }
+
     #[inline]
     pub(crate) fn contains_offset(&self, offset: usize) -> bool {
         self.static_size > offset
@@ -51,11 +155,15 @@ impl<'a> IsarObject<'a> {
         self.contains_offset(property.offset)
     }
 
+    /// Floats and doubles have no dedicated null value: every NaN (including
+    /// [NULL_FLOAT](Self::NULL_FLOAT) and [NULL_DOUBLE](Self::NULL_DOUBLE)) counts as null.
     pub fn is_null(&self, property: Property) -> bool {
         match property.data_type {
-            DataType::Byte => self.read_byte(property) == Self::NULL_BYTE,
+            DataType::Bool | DataType::Byte => self.read_byte(property) == Self::NULL_BYTE,
             DataType::Int => self.read_int(property) == Self::NULL_INT,
-            DataType::Long => self.read_long(property) == Self::NULL_LONG,
+            DataType::Long | DataType::DateTime | DataType::Decimal => {
+                self.read_long(property) == Self::NULL_LONG
+            }
             DataType::Float => self.read_float(property).is_nan(),
             DataType::Double => self.read_double(property).is_nan(),
             _ => self.get_offset_length(property.offset, false).is_none(),
@@ -63,7 +171,10 @@ impl<'a> IsarObject<'a> {
     }
 
     pub fn read_byte(&self, property: Property) -> u8 {
-        assert_eq!(property.data_type, DataType::Byte);
+        assert!(matches!(
+            property.data_type,
+            DataType::Bool | DataType::Byte
+        ));
         if self.contains_property(property) {
             self.bytes[property.offset]
         } else {
@@ -94,7 +205,10 @@ impl<'a> IsarObject<'a> {
     }
 
     pub fn read_long(&self, property: Property) -> i64 {
-        assert_eq!(property.data_type, DataType::Long);
+        assert!(matches!(
+            property.data_type,
+            DataType::Long | DataType::DateTime | DataType::Decimal
+        ));
         if self.contains_property(property) {
             LittleEndian::read_i64(&self.bytes[property.offset..])
         } else {
@@ -194,12 +308,64 @@ impl<'a> IsarObject<'a> {
         Some(list)
     }
 
+    fn get_element_offset(&self, property: Property, index: usize, size: usize) -> Option<usize> {
+        let (offset, length) = self.get_offset_length(property.offset, false)?;
+        if index < length {
+            Some(offset + index * size)
+        } else {
+            None
+        }
+    }
+
+    pub fn read_byte_list_element(&self, property: Property, index: usize) -> Option<u8> {
+        assert_eq!(property.data_type, DataType::ByteList);
+        let offset = self.get_element_offset(property, index, 1)?;
+        Some(self.bytes[offset])
+    }
+
+    pub fn read_int_list_element(&self, property: Property, index: usize) -> Option<i32> {
+        assert_eq!(property.data_type, DataType::IntList);
+        let offset = self.get_element_offset(property, index, 4)?;
+        Some(LittleEndian::read_i32(&self.bytes[offset..]))
+    }
+
+    pub fn read_float_list_element(&self, property: Property, index: usize) -> Option<f32> {
+        assert_eq!(property.data_type, DataType::FloatList);
+        let offset = self.get_element_offset(property, index, 4)?;
+        Some(LittleEndian::read_f32(&self.bytes[offset..]))
+    }
+
+    pub fn read_long_list_element(&self, property: Property, index: usize) -> Option<i64> {
+        assert_eq!(property.data_type, DataType::LongList);
+        let offset = self.get_element_offset(property, index, 8)?;
+        Some(LittleEndian::read_i64(&self.bytes[offset..]))
+    }
+
+    pub fn read_double_list_element(&self, property: Property, index: usize) -> Option<f64> {
+        assert_eq!(property.data_type, DataType::DoubleList);
+        let offset = self.get_element_offset(property, index, 8)?;
+        Some(LittleEndian::read_f64(&self.bytes[offset..]))
+    }
+
+    /// Returns `None` if the list is null or too short and `Some(None)` if the element is null.
+    pub fn read_string_list_element(
+        &self,
+        property: Property,
+        index: usize,
+    ) -> Option<Option<&'a str>> {
+        assert_eq!(property.data_type, DataType::StringList);
+        let offset = self.get_element_offset(property, index, 8)?;
+        Some(self.read_string_at(offset, true))
+    }
+
     pub fn hash_property(&self, property: Property, case_sensitive: bool, seed: u64) -> u64 {
         match property.data_type {
-            DataType::Byte => xxh3_64_with_seed(&[self.read_byte(property)], seed),
+            DataType::Bool | DataType::Byte => xxh3_64_with_seed(&[self.read_byte(property)], seed),
             DataType::Int => xxh3_64_with_seed(&self.read_int(property).to_le_bytes(), seed),
             DataType::Float => xxh3_64_with_seed(&self.read_float(property).to_le_bytes(), seed),
-            DataType::Long => xxh3_64_with_seed(&self.read_long(property).to_le_bytes(), seed),
+            DataType::Long | DataType::DateTime | DataType::Decimal => {
+                xxh3_64_with_seed(&self.read_long(property).to_le_bytes(), seed)
+            }
             DataType::Double => xxh3_64_with_seed(&self.read_double(property).to_le_bytes(), seed),
             DataType::String => Self::hash_string(self.read_string(property), case_sensitive, seed),
             _ => {
@@ -269,11 +435,7 @@ impl<'a> IsarObject<'a> {
         fn compare_float<T: Float>(f1: T, f2: T) -> Ordering {
             if !f1.is_nan() {
                 if !f2.is_nan() {
-                    if f1 > f2 {
-                        Ordering::Greater
-                    } else {
-                        Ordering::Less
-                    }
+                    f1.partial_cmp(&f2).unwrap()
                 } else {
                     Ordering::Greater
                 }
@@ -283,15 +445,33 @@ impl<'a> IsarObject<'a> {
                 Ordering::Equal
             }
         }
+        fn compare_float_list<T: Float>(l1: Option<Vec<T>>, l2: Option<Vec<T>>) -> Ordering {
+            match (l1, l2) {
+                (Some(l1), Some(l2)) => {
+                    for (f1, f2) in l1.iter().zip(l2.iter()) {
+                        let ordering = compare_float(*f1, *f2);
+                        if ordering != Ordering::Equal {
+                            return ordering;
+                        }
+                    }
+                    l1.len().cmp(&l2.len())
+                }
+                (l1, l2) => l1.is_some().cmp(&l2.is_some()),
+            }
+        }
         match property.data_type {
-            DataType::Byte => self.read_byte(property).cmp(&other.read_byte(property)),
+            DataType::Bool | DataType::Byte => {
+                self.read_byte(property).cmp(&other.read_byte(property))
+            }
             DataType::Int => self.read_int(property).cmp(&other.read_int(property)),
             DataType::Float => {
                 let f1 = self.read_float(property);
                 let f2 = other.read_float(property);
                 compare_float(f1, f2)
             }
-            DataType::Long => self.read_long(property).cmp(&other.read_long(property)),
+            DataType::Long | DataType::DateTime | DataType::Decimal => {
+                self.read_long(property).cmp(&other.read_long(property))
+            }
             DataType::Double => {
                 let f1 = self.read_double(property);
                 let f2 = other.read_double(property);
@@ -312,7 +492,26 @@ impl<'a> IsarObject<'a> {
                     Ordering::Equal
                 }
             }
-            _ => Ordering::Equal,
+            DataType::ByteList => self
+                .read_byte_list(property)
+                .cmp(&other.read_byte_list(property)),
+            DataType::IntList => self
+                .read_int_list(property)
+                .cmp(&other.read_int_list(property)),
+            DataType::LongList => self
+                .read_long_list(property)
+                .cmp(&other.read_long_list(property)),
+            DataType::FloatList => compare_float_list(
+                self.read_float_list(property),
+                other.read_float_list(property),
+            ),
+            DataType::DoubleList => compare_float_list(
+                self.read_double_list(property),
+                other.read_double_list(property),
+            ),
+            DataType::StringList => self
+                .read_string_list(property)
+                .cmp(&other.read_string_list(property)),
         }
     }
 }
@@ -320,9 +519,10 @@ impl<'a> IsarObject<'a> {
 #[cfg(test)]
 mod tests {
     use super::Property;
-    use crate::object::data_type::DataType::*;
+    use crate::object::data_type::DataType::{self, *};
     use crate::object::isar_object::IsarObject;
     use crate::object::object_builder::ObjectBuilder;
+    use std::cmp::Ordering;
 
     macro_rules! builder {
         ($builder:ident, $prop:ident, $type:ident) => {
@@ -332,6 +532,125 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_compare_float_and_double() {
+        fn compare(data_type: DataType, v1: f64, v2: f64) -> Ordering {
+            let p = Property::new(data_type, 2);
+            let props = vec![p];
+            let mut b1 = ObjectBuilder::new(&props, None);
+            let mut b2 = ObjectBuilder::new(&props, None);
+            if data_type == Float {
+                b1.write_float(v1 as f32);
+                b2.write_float(v2 as f32);
+            } else {
+                b1.write_double(v1);
+                b2.write_double(v2);
+            }
+            b1.finish().compare_property(&b2.finish(), p)
+        }
+
+        for data_type in [Float, Double] {
+            assert_eq!(compare(data_type, 1.5, 2.5), Ordering::Less);
+            assert_eq!(compare(data_type, 2.5, 1.5), Ordering::Greater);
+            assert_eq!(compare(data_type, -1.0, 1.0), Ordering::Less);
+            assert_eq!(compare(data_type, 1.5, 1.5), Ordering::Equal);
+            assert_eq!(compare(data_type, f64::NAN, 1.5), Ordering::Less);
+            assert_eq!(compare(data_type, 1.5, f64::NAN), Ordering::Greater);
+            assert_eq!(compare(data_type, f64::NAN, f64::NAN), Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn test_compare_lists() {
+        let p = Property::new(IntList, 2);
+        let props = vec![p];
+        let lists: Vec<Option<&[i32]>> = vec![
+            None,
+            Some(&[][..]),
+            Some(&[1][..]),
+            Some(&[1, 2][..]),
+            Some(&[2][..]),
+        ];
+        for (i1, l1) in lists.iter().enumerate() {
+            for (i2, l2) in lists.iter().enumerate() {
+                let mut b1 = ObjectBuilder::new(&props, None);
+                b1.write_int_list(*l1);
+                let mut b2 = ObjectBuilder::new(&props, None);
+                b2.write_int_list(*l2);
+                assert_eq!(b1.finish().compare_property(&b2.finish(), p), i1.cmp(&i2));
+            }
+        }
+
+        let p = Property::new(DoubleList, 2);
+        let props = vec![p];
+        let lists: Vec<Option<&[f64]>> = vec![
+            None,
+            Some(&[][..]),
+            Some(&[f64::NAN][..]),
+            Some(&[-1.0, 5.0][..]),
+            Some(&[1.0][..]),
+            Some(&[1.0, f64::NAN][..]),
+            Some(&[1.0, 2.0][..]),
+        ];
+        for (i1, l1) in lists.iter().enumerate() {
+            for (i2, l2) in lists.iter().enumerate() {
+                let mut b1 = ObjectBuilder::new(&props, None);
+                b1.write_double_list(*l1);
+                let mut b2 = ObjectBuilder::new(&props, None);
+                b2.write_double_list(*l2);
+                assert_eq!(b1.finish().compare_property(&b2.finish(), p), i1.cmp(&i2));
+            }
+        }
+
+        let p = Property::new(StringList, 2);
+        let props = vec![p];
+        let lists: Vec<Option<&[Option<&str>]>> = vec![
+            None,
+            Some(&[][..]),
+            Some(&[None][..]),
+            Some(&[Some("a")][..]),
+            Some(&[Some("a"), None][..]),
+            Some(&[Some("b")][..]),
+        ];
+        for (i1, l1) in lists.iter().enumerate() {
+            for (i2, l2) in lists.iter().enumerate() {
+                let mut b1 = ObjectBuilder::new(&props, None);
+                b1.write_string_list(*l1);
+                let mut b2 = ObjectBuilder::new(&props, None);
+                b2.write_string_list(*l2);
+                assert_eq!(b1.finish().compare_property(&b2.finish(), p), i1.cmp(&i2));
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_list_element() {
+        builder!(b, p, IntList);
+        b.write_null(IntList);
+        assert_eq!(b.finish().read_int_list_element(p, 0), None);
+
+        builder!(b, p, IntList);
+        b.write_int_list(Some(&[1, 2, 3]));
+        assert_eq!(b.finish().read_int_list_element(p, 0), Some(1));
+        assert_eq!(b.finish().read_int_list_element(p, 2), Some(3));
+        assert_eq!(b.finish().read_int_list_element(p, 3), None);
+
+        builder!(b, p, DoubleList);
+        b.write_double_list(Some(&[1.5, -2.5]));
+        assert_eq!(b.finish().read_double_list_element(p, 1), Some(-2.5));
+        assert_eq!(b.finish().read_double_list_element(p, 2), None);
+
+        builder!(b, p, StringList);
+        b.write_string_list(Some(&[Some("a"), None]));
+        assert_eq!(b.finish().read_string_list_element(p, 0), Some(Some("a")));
+        assert_eq!(b.finish().read_string_list_element(p, 1), Some(None));
+        assert_eq!(b.finish().read_string_list_element(p, 2), None);
+
+        builder!(b, p, StringList);
+        b.write_string_list(Some(&[]));
+        assert_eq!(b.finish().read_string_list_element(p, 0), None);
+    }
+
     #[test]
     fn test_read_non_contained_property() {
         let data_types = vec![
@@ -349,7 +668,7 @@ mod tests {
     #[test]
     fn test_read_byte() {
         builder!(b, p, Byte);
-        b.write_null();
+        b.write_null(Byte);
         assert_eq!(b.finish().read_byte(p), IsarObject::NULL_BYTE);
         assert!(b.finish().is_null(p));
 
@@ -359,10 +678,28 @@ mod tests {
         assert!(!b.finish().is_null(p));
     }
 
+    #[test]
+    fn test_read_bool() {
+        builder!(b, p, Bool);
+        b.write_null(Bool);
+        assert!(!b.finish().read_bool(p));
+        assert!(b.finish().is_null(p));
+
+        builder!(b, p, Bool);
+        b.write_bool(true);
+        assert!(b.finish().read_bool(p));
+        assert!(!b.finish().is_null(p));
+
+        builder!(b, p, Bool);
+        b.write_bool(false);
+        assert!(!b.finish().read_bool(p));
+        assert!(!b.finish().is_null(p));
+    }
+
     #[test]
     fn test_read_int() {
         builder!(b, p, Int);
-        b.write_null();
+        b.write_null(Int);
         assert_eq!(b.finish().read_int(p), IsarObject::NULL_INT);
         assert!(b.finish().is_null(p));
 
@@ -375,7 +712,7 @@ mod tests {
     #[test]
     fn test_read_float() {
         builder!(b, p, Float);
-        b.write_null();
+        b.write_null(Float);
         assert!(b.finish().read_float(p).is_nan());
         assert!(b.finish().is_null(p));
 
@@ -388,7 +725,7 @@ mod tests {
     #[test]
     fn test_read_long() {
         builder!(b, p, Long);
-        b.write_null();
+        b.write_null(Long);
         assert_eq!(b.finish().read_long(p), IsarObject::NULL_LONG);
         assert!(b.finish().is_null(p));
 
@@ -401,7 +738,7 @@ mod tests {
     #[test]
     fn test_read_double() {
         builder!(b, p, Double);
-        b.write_null();
+        b.write_null(Double);
         assert!(b.finish().read_double(p).is_nan());
         assert!(b.finish().is_null(p));
 
@@ -414,7 +751,7 @@ mod tests {
     #[test]
     fn test_read_string() {
         builder!(b, p, String);
-        b.write_null();
+        b.write_null(String);
         assert_eq!(b.finish().read_string(p), None);
         assert!(b.finish().is_null(p));
 
@@ -432,7 +769,7 @@ mod tests {
     #[test]
     fn test_read_byte_list() {
         builder!(b, p, ByteList);
-        b.write_null();
+        b.write_null(ByteList);
         assert_eq!(b.finish().read_byte_list(p), None);
         assert!(b.finish().is_null(p));
 
@@ -450,7 +787,7 @@ mod tests {
     #[test]
     fn test_read_int_list() {
         builder!(b, p, IntList);
-        b.write_null();
+        b.write_null(IntList);
         assert_eq!(b.finish().read_int_list(p), None);
         assert!(b.finish().is_null(p));
 
@@ -468,7 +805,7 @@ mod tests {
     #[test]
     fn test_read_float_list() {
         builder!(b, p, FloatList);
-        b.write_null();
+        b.write_null(FloatList);
         assert_eq!(b.finish().read_float_list(p), None);
         assert!(b.finish().is_null(p));
 
@@ -486,7 +823,7 @@ mod tests {
     #[test]
     fn test_read_long_list() {
         builder!(b, p, LongList);
-        b.write_null();
+        b.write_null(LongList);
         assert_eq!(b.finish().read_long_list(p), None);
         assert!(b.finish().is_null(p));
 
@@ -504,7 +841,7 @@ mod tests {
     #[test]
     fn test_read_double_list() {
         builder!(b, p, DoubleList);
-        b.write_null();
+        b.write_null(DoubleList);
         assert_eq!(b.finish().read_double_list(p), None);
         assert!(b.finish().is_null(p));
 
@@ -522,7 +859,7 @@ mod tests {
     #[test]
     fn test_read_string_list() {
         builder!(b, p, StringList);
-        b.write_null();
+        b.write_null(StringList);
         assert_eq!(b.finish().read_string_list(p), None);
         assert!(b.finish().is_null(p));
 
@@ -3,7 +3,12 @@ use crate::error::{IsarError, Result};
 use crate::object::data_type::DataType;
 use crate::object::isar_object::IsarObject;
 use crate::object::object_builder::ObjectBuilder;
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::Deserializer;
 use serde_json::{json, Map, Value};
+use std::fmt;
+use std::io::{BufReader, Read};
 
 pub(crate) struct JsonEncodeDecode {}
 
@@ -23,6 +28,13 @@ impl<'a> JsonEncodeDecode {
                     Value::Null
                 } else {
                     match property.data_type {
+                        DataType::Bool => {
+                            if object.is_null(property) {
+                                Value::Null
+                            } else {
+                                json!(object.read_bool(property))
+                            }
+                        }
                         DataType::Byte => {
                             if byte_as_bool {
                                 json!(object.read_bool(property))
@@ -35,6 +47,10 @@ impl<'a> JsonEncodeDecode {
                         DataType::Long => json!(object.read_long(property)),
                         DataType::Double => json!(object.read_double(property)),
                         DataType::String => json!(object.read_string(property)),
+                        DataType::DateTime => Self::date_time_to_value(object.read_long(property)),
+                        DataType::Decimal => {
+                            Self::decimal_to_value(object.read_long(property), property.scale)
+                        }
                         DataType::ByteList => json!(object.read_byte_list(property)),
                         DataType::IntList => json!(object.read_int_list(property)),
                         DataType::FloatList => json!(object.read_float_list(property)),
@@ -60,12 +76,17 @@ impl<'a> JsonEncodeDecode {
         for (property_name, property) in &collection.properties {
             if let Some(value) = object.get(property_name) {
                 match property.data_type {
+                    DataType::Bool => ob.write_byte(Self::value_to_bool(value)?),
                     DataType::Byte => ob.write_byte(Self::value_to_byte(value)?),
                     DataType::Int => ob.write_int(Self::value_to_int(value)?),
                     DataType::Float => ob.write_float(Self::value_to_float(value)?),
                     DataType::Long => ob.write_long(Self::value_to_long(value)?),
                     DataType::Double => ob.write_double(Self::value_to_double(value)?),
                     DataType::String => ob.write_string(Self::value_to_string(value)?),
+                    DataType::DateTime => ob.write_long(Self::value_to_date_time(value)?),
+                    DataType::Decimal => {
+                        ob.write_long(Self::value_to_decimal(value, property.scale)?)
+                    }
                     DataType::ByteList => {
                         let list = Self::value_to_array(value, Self::value_to_byte)?;
                         ob.write_byte_list(list.as_deref());
@@ -99,13 +120,32 @@ impl<'a> JsonEncodeDecode {
                     }
                 }
             } else {
-                ob.write_null();
+                ob.write_null(property.data_type);
             }
         }
 
         Ok(ob)
     }
 
+    /// Reads a JSON array from `reader` and passes its elements to `callback` one at a time
+    /// without materializing the whole array.
+    pub fn decode_array_stream(
+        reader: impl Read,
+        callback: impl FnMut(Value) -> Result<()>,
+    ) -> Result<()> {
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+        let mut visitor = ArrayVisitor {
+            callback,
+            error: None,
+        };
+        let result = deserializer.deserialize_seq(&mut visitor);
+        if let Some(err) = visitor.error {
+            return Err(err);
+        }
+        result.map_err(|_| IsarError::InvalidJson {})?;
+        deserializer.end().map_err(|_| IsarError::InvalidJson {})
+    }
+
     fn value_to_byte(value: &Value) -> Result<u8> {
         if value.is_null() {
             return Ok(IsarObject::NULL_BYTE);
@@ -124,6 +164,20 @@ impl<'a> JsonEncodeDecode {
         Err(IsarError::InvalidJson {})
     }
 
+    fn value_to_bool(value: &Value) -> Result<u8> {
+        if value.is_null() {
+            Ok(IsarObject::NULL_BYTE)
+        } else if let Some(value) = value.as_bool() {
+            if value {
+                Ok(IsarObject::TRUE_BYTE)
+            } else {
+                Ok(IsarObject::FALSE_BYTE)
+            }
+        } else {
+            Err(IsarError::InvalidJson {})
+        }
+    }
+
     fn value_to_int(value: &Value) -> Result<i32> {
         if value.is_null() {
             return Ok(IsarObject::NULL_INT);
@@ -166,6 +220,86 @@ impl<'a> JsonEncodeDecode {
         }
     }
 
+    fn date_time_to_value(millis: i64) -> Value {
+        if millis == IsarObject::NULL_LONG {
+            return Value::Null;
+        }
+        match Utc.timestamp_millis_opt(millis).single() {
+            Some(date) => json!(date.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            None => json!(millis),
+        }
+    }
+
+    fn value_to_date_time(value: &Value) -> Result<i64> {
+        if value.is_null() {
+            Ok(IsarObject::NULL_LONG)
+        } else if let Some(value) = value.as_str() {
+            DateTime::parse_from_rfc3339(value)
+                .map(|date| date.timestamp_millis())
+                .map_err(|_| IsarError::InvalidJson {})
+        } else if let Some(value) = value.as_i64() {
+            Ok(value)
+        } else {
+            Err(IsarError::InvalidJson {})
+        }
+    }
+
+    fn decimal_to_value(value: i64, scale: u8) -> Value {
+        if value == IsarObject::NULL_LONG {
+            return Value::Null;
+        }
+        let scale = scale as usize;
+        let sign = if value < 0 { "-" } else { "" };
+        let digits = format!("{:0>width$}", value.unsigned_abs(), width = scale + 1);
+        if scale == 0 {
+            json!(format!("{}{}", sign, digits))
+        } else {
+            let (int, frac) = digits.split_at(digits.len() - scale);
+            json!(format!("{}{}.{}", sign, int, frac))
+        }
+    }
+
+    fn value_to_decimal(value: &Value, scale: u8) -> Result<i64> {
+        let factor = 10i64.pow(scale as u32);
+        if value.is_null() {
+            Ok(IsarObject::NULL_LONG)
+        } else if let Some(value) = value.as_str() {
+            let (negative, value) = if let Some(value) = value.strip_prefix('-') {
+                (true, value)
+            } else {
+                (false, value.strip_prefix('+').unwrap_or(value))
+            };
+            let (int, frac) = value.split_at(value.find('.').unwrap_or(value.len()));
+            let frac = frac.strip_prefix('.').unwrap_or(frac);
+            let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+            if (int.is_empty() && frac.is_empty())
+                || !is_digits(int)
+                || !is_digits(frac)
+                || frac.len() > scale as usize
+            {
+                return Err(IsarError::InvalidJson {});
+            }
+            let parse = |s: &str| -> Option<i64> {
+                if s.is_empty() {
+                    Some(0)
+                } else {
+                    s.parse().ok()
+                }
+            };
+            let frac_factor = 10i64.pow((scale as usize - frac.len()) as u32);
+            let scaled = parse(int)
+                .and_then(|int| int.checked_mul(factor))
+                .zip(parse(frac).map(|frac| frac * frac_factor))
+                .and_then(|(int, frac)| int.checked_add(frac))
+                .ok_or(IsarError::InvalidJson {})?;
+            Ok(if negative { -scaled } else { scaled })
+        } else if let Some(value) = value.as_i64() {
+            value.checked_mul(factor).ok_or(IsarError::InvalidJson {})
+        } else {
+            Err(IsarError::InvalidJson {})
+        }
+    }
+
     fn value_to_string(value: &Value) -> Result<Option<&str>> {
         if value.is_null() {
             Ok(None)
@@ -190,3 +324,141 @@ impl<'a> JsonEncodeDecode {
         }
     }
 }
+
+struct ArrayVisitor<F> {
+    callback: F,
+    error: Option<IsarError>,
+}
+
+impl<'de, F> Visitor<'de> for &mut ArrayVisitor<F>
+where
+    F: FnMut(Value) -> Result<()>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON array")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(value) = seq.next_element::<Value>()? {
+            if let Err(err) = (self.callback)(value) {
+                self.error = Some(err);
+                return Err(A::Error::custom("callback failed"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_time_json() {
+        let pairs = vec![
+            (IsarObject::NULL_LONG, Value::Null),
+            (0, json!("1970-01-01T00:00:00.000Z")),
+            (1614556800123, json!("2021-03-01T00:00:00.123Z")),
+            (-1000, json!("1969-12-31T23:59:59.000Z")),
+        ];
+        for (millis, value) in pairs {
+            assert_eq!(JsonEncodeDecode::date_time_to_value(millis), value);
+            assert_eq!(
+                JsonEncodeDecode::value_to_date_time(&value).unwrap(),
+                millis
+            );
+        }
+
+        let offset = json!("2021-03-01T02:00:00.123+02:00");
+        assert_eq!(
+            JsonEncodeDecode::value_to_date_time(&offset).unwrap(),
+            1614556800123
+        );
+        assert!(JsonEncodeDecode::value_to_date_time(&json!("2021-03-01")).is_err());
+    }
+
+    #[test]
+    fn test_decimal_json() {
+        let pairs = vec![
+            (IsarObject::NULL_LONG, 2, Value::Null),
+            (0, 2, json!("0.00")),
+            (12345, 2, json!("123.45")),
+            (-12345, 2, json!("-123.45")),
+            (-5, 2, json!("-0.05")),
+            (7, 0, json!("7")),
+            (-1, 18, json!("-0.000000000000000001")),
+            (i64::MAX, 4, json!("922337203685477.5807")),
+        ];
+        for (scaled, scale, value) in pairs {
+            assert_eq!(JsonEncodeDecode::decimal_to_value(scaled, scale), value);
+            assert_eq!(
+                JsonEncodeDecode::value_to_decimal(&value, scale).unwrap(),
+                scaled
+            );
+        }
+    }
+
+    #[test]
+    fn test_value_to_decimal() {
+        let valid = vec![
+            (json!("1.5"), 150),
+            (json!("+1"), 100),
+            (json!("-.25"), -25),
+            (json!("3."), 300),
+            (json!(42), 4200),
+            (json!(-42), -4200),
+        ];
+        for (value, scaled) in valid {
+            assert_eq!(
+                JsonEncodeDecode::value_to_decimal(&value, 2).unwrap(),
+                scaled
+            );
+        }
+
+        let invalid = vec![
+            json!("1.234"),
+            json!(""),
+            json!("."),
+            json!("-"),
+            json!("1,5"),
+            json!("1e3"),
+            json!("--1"),
+            json!(1.5),
+            json!("92233720368547758.08"),
+            json!(i64::MAX),
+        ];
+        for value in invalid {
+            assert!(JsonEncodeDecode::value_to_decimal(&value, 2).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_array_stream() {
+        let json = b" [1, {\"a\": [2]}, null] ";
+        let mut values = vec![];
+        JsonEncodeDecode::decode_array_stream(&json[..], |value| {
+            values.push(value);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(values, vec![json!(1), json!({"a": [2]}), Value::Null]);
+
+        for json in [&b"{}"[..], b"[1, 2", b"[1] 2", b"1"] {
+            let result = JsonEncodeDecode::decode_array_stream(json, |_| Ok(()));
+            assert!(result.is_err());
+        }
+
+        let mut count = 0;
+        let result = JsonEncodeDecode::decode_array_stream(&b"[1, 2, 3]"[..], |_| {
+            count += 1;
+            Err(IsarError::Cancelled {})
+        });
+        assert!(matches!(result, Err(IsarError::Cancelled {})));
+        assert_eq!(count, 1);
+    }
+}
@@ -44,13 +44,18 @@ impl<'a> ObjectBuilder<'a> {
         self.buffer[offset..(offset + bytes.len())].clone_from_slice(bytes);
     }
 
-    pub fn write_null(&mut self) {
+    /// Writes the null value of `data_type` for the next property. Dynamic properties are
+    /// written as a null position without touching the dynamic section.
+    pub fn write_null(&mut self, data_type: DataType) {
         let property = self.next_property(true);
-        match property.data_type {
-            DataType::Byte => self.write_byte(IsarObject::NULL_BYTE),
+        assert_eq!(property.data_type, data_type);
+        match data_type {
+            DataType::Bool | DataType::Byte => self.write_byte(IsarObject::NULL_BYTE),
             DataType::Int => self.write_int(IsarObject::NULL_INT),
             DataType::Float => self.write_float(IsarObject::NULL_FLOAT),
-            DataType::Long => self.write_long(IsarObject::NULL_LONG),
+            DataType::Long | DataType::DateTime | DataType::Decimal => {
+                self.write_long(IsarObject::NULL_LONG)
+            }
             DataType::Double => self.write_double(IsarObject::NULL_DOUBLE),
             DataType::String => self.write_string(None),
             DataType::ByteList => self.write_byte_list(None),
@@ -64,7 +69,10 @@ impl<'a> ObjectBuilder<'a> {
 
     pub fn write_byte(&mut self, value: u8) {
         let property = self.next_property(false);
-        assert_eq!(property.data_type, DataType::Byte);
+        assert!(matches!(
+            property.data_type,
+            DataType::Bool | DataType::Byte
+        ));
         self.write_at(property.offset, &[value]);
     }
 
@@ -91,7 +99,10 @@ impl<'a> ObjectBuilder<'a> {
 
     pub fn write_long(&mut self, value: i64) {
         let property = self.next_property(false);
-        assert_eq!(property.data_type, DataType::Long);
+        assert!(matches!(
+            property.data_type,
+            DataType::Long | DataType::DateTime | DataType::Decimal
+        ));
         self.write_at(property.offset, &value.to_le_bytes());
     }
 
@@ -155,6 +166,31 @@ impl<'a> ObjectBuilder<'a> {
         }
     }
 
+    pub fn write_from(&mut self, object: IsarObject, property: Property) {
+        match property.data_type {
+            DataType::Bool | DataType::Byte => self.write_byte(object.read_byte(property)),
+            DataType::Int => self.write_int(object.read_int(property)),
+            DataType::Float => self.write_float(object.read_float(property)),
+            DataType::Long | DataType::DateTime | DataType::Decimal => {
+                self.write_long(object.read_long(property))
+            }
+            DataType::Double => self.write_double(object.read_double(property)),
+            DataType::String => self.write_string(object.read_string(property)),
+            DataType::ByteList => self.write_byte_list(object.read_byte_list(property)),
+            DataType::IntList => self.write_int_list(object.read_int_list(property).as_deref()),
+            DataType::FloatList => {
+                self.write_float_list(object.read_float_list(property).as_deref())
+            }
+            DataType::LongList => self.write_long_list(object.read_long_list(property).as_deref()),
+            DataType::DoubleList => {
+                self.write_double_list(object.read_double_list(property).as_deref())
+            }
+            DataType::StringList => {
+                self.write_string_list(object.read_string_list(property).as_deref())
+            }
+        }
+    }
+
     fn write_list<T>(&mut self, offset: usize, list: Option<&[T]>) {
         if let Some(list) = list {
             self.write_at(offset, &(self.dynamic_offset as u32).to_le_bytes());
@@ -203,29 +239,29 @@ mod tests {
     #[test]
     pub fn test_write_null() {
         builder!(b, Byte);
-        b.write_null();
+        b.write_null(Byte);
         assert_eq!(b.finish().as_bytes(), &[11, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
 
         builder!(b, Int);
-        b.write_null();
+        b.write_null(Int);
         let mut bytes = vec![14, 0, 1, 0, 0, 0, 0, 0, 0, 0];
         bytes.extend_from_slice(&IsarObject::NULL_INT.to_le_bytes());
         assert_eq!(b.finish().as_bytes(), &bytes);
 
         builder!(b, Float);
-        b.write_null();
+        b.write_null(Float);
         let mut bytes = vec![14, 0, 1, 0, 0, 0, 0, 0, 0, 0];
         bytes.extend_from_slice(&IsarObject::NULL_FLOAT.to_le_bytes());
         assert_eq!(b.finish().as_bytes(), &bytes);
 
         builder!(b, Long);
-        b.write_null();
+        b.write_null(Long);
         let mut bytes = vec![18, 0, 1, 0, 0, 0, 0, 0, 0, 0];
         bytes.extend_from_slice(&IsarObject::NULL_LONG.to_le_bytes());
         assert_eq!(b.finish().as_bytes(), &bytes);
 
         builder!(b, Double);
-        b.write_null();
+        b.write_null(Double);
         let mut bytes = vec![18, 0, 1, 0, 0, 0, 0, 0, 0, 0];
         bytes.extend_from_slice(&IsarObject::NULL_DOUBLE.to_le_bytes());
         assert_eq!(b.finish().as_bytes(), &bytes);
@@ -236,13 +272,44 @@ mod tests {
 
         for list_type in list_types {
             builder!(b, list_type);
-            b.write_null();
+            b.write_null(list_type);
             let mut bytes = vec![18, 0, 1, 0, 0, 0, 0, 0, 0, 0];
             bytes.extend_from_slice(&0u64.to_le_bytes());
             assert_eq!(b.finish().as_bytes(), &bytes);
         }
     }
 
+    #[test]
+    pub fn test_write_null_is_null() {
+        let types = vec![
+            Bool, Byte, Int, Float, Long, Double, DateTime, Decimal, String, ByteList, IntList,
+            FloatList, LongList, DoubleList, StringList,
+        ];
+
+        for data_type in types {
+            let props = vec![
+                Property::new(Long, 2),
+                Property::new(data_type, 10),
+                Property::new(String, 10 + data_type.get_static_size()),
+            ];
+            let mut b = ObjectBuilder::new(&props, None);
+            b.write_long(1);
+            b.write_null(data_type);
+            b.write_string(Some("after"));
+            let object = b.finish();
+            assert!(object.is_null(props[1]));
+            assert_eq!(object.read_long(props[0]), 1);
+            assert_eq!(object.read_string(props[2]), Some("after"));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_write_null_wrong_type() {
+        builder!(b, Int);
+        b.write_null(Long);
+    }
+
     #[test]
     pub fn test_write_byte() {
         builder!(b, Byte);
@@ -377,6 +444,36 @@ mod tests {
         b.write_string(Some("hello"));
     }
 
+    #[test]
+    pub fn test_write_from() {
+        let props = vec![
+            Property::new(DataType::Long, 2),
+            Property::new(DataType::String, 10),
+            Property::new(DataType::StringList, 18),
+        ];
+        let mut b = ObjectBuilder::new(&props, None);
+        b.write_long(123);
+        b.write_string(Some("hello"));
+        b.write_string_list(Some(&[Some("a"), None, Some("bc")]));
+        let object = b.finish();
+
+        for (i, property) in props.iter().enumerate() {
+            let projected_props = vec![Property::new(property.data_type, 2)];
+            let mut projected = ObjectBuilder::new(&projected_props, None);
+            projected.write_from(object, *property);
+            let projected = projected.finish();
+            let projected_prop = projected_props[0];
+            match i {
+                0 => assert_eq!(projected.read_long(projected_prop), 123),
+                1 => assert_eq!(projected.read_string(projected_prop), Some("hello")),
+                _ => assert_eq!(
+                    projected.read_string_list(projected_prop),
+                    Some(vec![Some("a"), None, Some("bc")])
+                ),
+            }
+        }
+    }
+
     #[test]
     pub fn test_write_multiple_static_types() {
         let props = vec![
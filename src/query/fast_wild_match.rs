@@ -1,25 +1,61 @@
 const ASTERISK: u8 = 42;
 const QUESTION_MARK: u8 = 63;
+const BACKSLASH: u8 = 92;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum WildToken {
+    Literal(u8),
+    Any,
+    Star,
+}
+
+impl WildToken {
+    fn matches(self, byte: u8) -> bool {
+        match self {
+            WildToken::Literal(literal) => literal == byte,
+            WildToken::Any => true,
+            WildToken::Star => false,
+        }
+    }
+}
+
+/// Splits the pattern into tokens. A backslash escapes the following byte so `\*`, `\?` and
+/// `\\` match a literal asterisk, question mark and backslash. A trailing lone backslash
+/// matches a literal backslash.
+fn parse_wild(wild: &str) -> Vec<WildToken> {
+    let mut tokens = Vec::with_capacity(wild.len());
+    let mut bytes = wild.bytes();
+    while let Some(byte) = bytes.next() {
+        let token = match byte {
+            ASTERISK => WildToken::Star,
+            QUESTION_MARK => WildToken::Any,
+            BACKSLASH => WildToken::Literal(bytes.next().unwrap_or(BACKSLASH)),
+            _ => WildToken::Literal(byte),
+        };
+        tokens.push(token);
+    }
+    tokens
+}
 
 pub(crate) fn fast_wild_match(tame: &str, wild: &str) -> bool {
-    let wild = wild.as_bytes();
+    let wild = parse_wild(wild);
     let tame = tame.as_bytes();
     let mut i_wild = 0;
     let mut i_tame = 0;
     let mut i_last = 0;
     let mut i_star = 0;
 
-    while tame.get(i_tame).is_some() {
+    while let Some(&tame_byte) = tame.get(i_tame) {
         match wild.get(i_wild) {
-            Some(&QUESTION_MARK) => {
+            Some(WildToken::Any) => {
                 i_tame += 1;
                 i_wild += 1;
                 continue;
             }
-            Some(&ASTERISK) => {
+            Some(WildToken::Star) => {
                 loop {
                     i_wild += 1;
-                    if wild.get(i_wild) != Some(&ASTERISK) {
+                    if wild.get(i_wild) != Some(&WildToken::Star) {
                         break;
                     }
                 }
@@ -29,7 +65,7 @@ pub(crate) fn fast_wild_match(tame: &str, wild: &str) -> bool {
                 i_star = i_wild;
             }
             _ => {
-                if tame.get(i_tame) == wild.get(i_wild) {
+                if wild.get(i_wild) == Some(&WildToken::Literal(tame_byte)) {
                     i_tame += 1;
                     i_wild += 1;
                     continue;
@@ -42,17 +78,19 @@ pub(crate) fn fast_wild_match(tame: &str, wild: &str) -> bool {
             }
         }
 
-        while tame.get(i_tame) != wild.get(i_wild) && wild.get(i_wild) != Some(&QUESTION_MARK) {
-            i_tame += 1;
-            if tame.get(i_tame).is_none() {
-                return false;
+        let token = wild[i_wild];
+        loop {
+            match tame.get(i_tame) {
+                Some(&byte) if token.matches(byte) => break,
+                Some(_) => i_tame += 1,
+                None => return false,
             }
         }
         i_last = i_tame;
         i_tame += 1;
         i_wild += 1;
     }
-    while wild.get(i_wild) == Some(&ASTERISK) {
+    while wild.get(i_wild) == Some(&WildToken::Star) {
         i_wild += 1;
     }
     wild.get(i_wild).is_none()
@@ -149,6 +187,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_escaped() {
+        let escaped_cases = vec![
+            ("a*c", r"a\*c", true),
+            ("abc", r"a\*c", false),
+            ("a?c", r"a\?c", true),
+            ("abc", r"a\?c", false),
+            (r"a\c", r"a\\c", true),
+            (r"a\bc", r"a\\*", true),
+            ("abc", r"a\\*", false),
+            ("what?", r"*\?", true),
+            ("what", r"*\?", false),
+            ("x*y*z", r"x\**\*z", true),
+            ("x*yyz", r"x\**\*z", false),
+            ("a*b?c", r"?\*?\??", true),
+            ("ab", r"\a\b", true),
+            (r"ab\", r"ab\", true),
+            ("ab", r"ab\", false),
+            (r"ab\", r"*\", true),
+        ];
+
+        for (tame, wild, result) in escaped_cases {
+            assert_eq!(fast_wild_match(tame, wild), result);
+        }
+    }
+
     #[test]
     fn test_tame() {
         let tame_cases = vec![
@@ -9,6 +9,9 @@ use crate::query::fast_wild_match::fast_wild_match;
 use enum_dispatch::enum_dispatch;
 use itertools::Itertools;
 use paste::paste;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashSet;
 
 #[macro_export]
 macro_rules! primitive_create {
@@ -37,6 +40,37 @@ macro_rules! primitive_create {
     };
 }
 
+#[macro_export]
+macro_rules! float_create {
+    ($data_type:ident, $property:expr, $lower:expr, $include_lower:expr, $upper:expr, $include_upper:expr) => {
+        paste! {
+            if $property.data_type == DataType::$data_type {
+                Ok(Filter(
+                    FilterCond::[<$data_type Between>]([<$data_type BetweenCond>] {
+                        property: $property,
+                        lower: $lower,
+                        include_lower: $include_lower,
+                        upper: $upper,
+                        include_upper: $include_upper,
+                    })
+                ))
+            } else if $property.data_type == DataType::[<$data_type List>] {
+                Ok(Filter(
+                    FilterCond::[<Any $data_type Between>]([<Any $data_type BetweenCond>] {
+                        property: $property,
+                        lower: $lower,
+                        include_lower: $include_lower,
+                        upper: $upper,
+                        include_upper: $include_upper,
+                    })
+                ))
+            } else {
+                illegal_arg("Property does not support this filter.")
+            }
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! string_filter_create {
     ($name:ident, $property:expr, $value:expr, $case_sensitive:expr) => {
@@ -68,17 +102,102 @@ macro_rules! string_filter_create {
     };
 }
 
+#[macro_export]
+macro_rules! primitive_in_create {
+    ($data_type:ident, $property:expr, $values:expr) => {
+        paste! {
+            if $property.data_type == DataType::$data_type {
+                let mut values = $values;
+                values.sort_unstable();
+                values.dedup();
+                Ok(Filter(FilterCond::[<$data_type In>]([<$data_type InCond>] {
+                    property: $property,
+                    values,
+                })))
+            } else {
+                illegal_arg("Property does not support this filter.")
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! primitive_not_in_create {
+    ($data_type:ident, $property:expr, $values:expr, $include_null:expr) => {
+        paste! {{
+            let mut values = $values;
+            values.sort_unstable();
+            values.dedup();
+            Ok(Filter(FilterCond::[<$data_type NotIn>]([<$data_type NotInCond>] {
+                property: $property,
+                values,
+                include_null: $include_null,
+            })))
+        }}
+    };
+}
+
 #[derive(Clone)]
 pub struct Filter(FilterCond);
 
+#[macro_export]
+macro_rules! list_between_create {
+    ($cond:ident, $list_type:ident, $property:expr, $lower:expr, $upper:expr) => {
+        paste! {
+            if $property.data_type == DataType::$list_type {
+                Ok(Filter(FilterCond::[<$cond Between>]([<$cond BetweenCond>] {
+                    property: $property,
+                    lower: $lower,
+                    upper: $upper,
+                })))
+            } else {
+                illegal_arg("Property does not support this filter.")
+            }
+        }
+    };
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ListElementValue {
+    Byte(u8),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(Option<String>),
+}
+
 impl Filter {
     pub fn id(lower: i64, upper: i64) -> Result<Filter> {
         let filter_cond = FilterCond::IdBetween(IdBetweenCond { lower, upper });
         Ok(Filter(filter_cond))
     }
 
+    pub fn bool(property: Property, value: Option<bool>) -> Result<Filter> {
+        if property.data_type == DataType::Bool {
+            let value = match value {
+                Some(true) => IsarObject::TRUE_BYTE,
+                Some(false) => IsarObject::FALSE_BYTE,
+                None => IsarObject::NULL_BYTE,
+            };
+            let filter_cond = FilterCond::BoolEqual(BoolEqualCond { property, value });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
     pub fn byte(property: Property, lower: u8, upper: u8) -> Result<Filter> {
-        primitive_create!(Byte, property, lower, upper)
+        if property.data_type == DataType::Bool {
+            let filter_cond = FilterCond::ByteBetween(ByteBetweenCond {
+                property,
+                lower,
+                upper,
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            primitive_create!(Byte, property, lower, upper)
+        }
     }
 
     pub fn int(property: Property, lower: i32, upper: i32) -> Result<Filter> {
@@ -86,15 +205,225 @@ impl Filter {
     }
 
     pub fn long(property: Property, lower: i64, upper: i64) -> Result<Filter> {
-        primitive_create!(Long, property, lower, upper)
+        if property.data_type == DataType::DateTime {
+            let filter_cond = FilterCond::LongBetween(LongBetweenCond {
+                property,
+                lower,
+                upper,
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            primitive_create!(Long, property, lower, upper)
+        }
+    }
+
+    /// Matches decimals between the scaled integers `lower` and `upper`. The values are expected
+    /// to use the scale of the property.
+    pub fn decimal(property: Property, lower: i64, upper: i64) -> Result<Filter> {
+        if property.data_type == DataType::Decimal {
+            let filter_cond = FilterCond::DecimalBetween(DecimalBetweenCond {
+                property,
+                lower,
+                upper,
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    pub fn byte_gt(property: Property, value: u8) -> Result<Filter> {
+        if let Some(lower) = value.checked_add(1) {
+            Self::byte(property, lower, u8::MAX)
+        } else {
+            Self::byte(property, u8::MAX, u8::MIN)
+        }
+    }
+
+    /// Matches values less than `value` including null.
+    pub fn byte_lt(property: Property, value: u8) -> Result<Filter> {
+        if let Some(upper) = value.checked_sub(1) {
+            Self::byte(property, u8::MIN, upper)
+        } else {
+            Self::byte(property, u8::MAX, u8::MIN)
+        }
+    }
+
+    pub fn int_gt(property: Property, value: i32) -> Result<Filter> {
+        if let Some(lower) = value.checked_add(1) {
+            Self::int(property, lower, i32::MAX)
+        } else {
+            Self::int(property, i32::MAX, i32::MIN)
+        }
+    }
+
+    /// Matches values less than `value` including null.
+    pub fn int_lt(property: Property, value: i32) -> Result<Filter> {
+        if let Some(upper) = value.checked_sub(1) {
+            Self::int(property, i32::MIN, upper)
+        } else {
+            Self::int(property, i32::MAX, i32::MIN)
+        }
+    }
+
+    pub fn long_gt(property: Property, value: i64) -> Result<Filter> {
+        if let Some(lower) = value.checked_add(1) {
+            Self::long(property, lower, i64::MAX)
+        } else {
+            Self::long(property, i64::MAX, i64::MIN)
+        }
+    }
+
+    /// Matches values less than `value` including null.
+    pub fn long_lt(property: Property, value: i64) -> Result<Filter> {
+        if let Some(upper) = value.checked_sub(1) {
+            Self::long(property, i64::MIN, upper)
+        } else {
+            Self::long(property, i64::MAX, i64::MIN)
+        }
     }
 
     pub fn float(property: Property, lower: f32, upper: f32) -> Result<Filter> {
-        primitive_create!(Float, property, lower, upper)
+        Self::float_between(property, lower, false, upper, false)
+    }
+
+    /// Matches floats between `lower` and `upper`. A NaN `lower` bound matches all values
+    /// including NaN and a positive infinite `upper` bound matches all values.
+    pub fn float_between(
+        property: Property,
+        lower: f32,
+        include_lower: bool,
+        upper: f32,
+        include_upper: bool,
+    ) -> Result<Filter> {
+        float_create!(Float, property, lower, include_lower, upper, include_upper)
+    }
+
+    pub fn float_gt(property: Property, value: f32) -> Result<Filter> {
+        Self::float_between(property, value, false, f32::INFINITY, true)
+    }
+
+    /// Matches values less than `value` including null.
+    pub fn float_lt(property: Property, value: f32) -> Result<Filter> {
+        Self::float_between(property, f32::NAN, false, value, false)
     }
 
     pub fn double(property: Property, lower: f64, upper: f64) -> Result<Filter> {
-        primitive_create!(Double, property, lower, upper)
+        Self::double_between(property, lower, false, upper, false)
+    }
+
+    /// Matches doubles between `lower` and `upper`. A NaN `lower` bound matches all values
+    /// including NaN and a positive infinite `upper` bound matches all values.
+    pub fn double_between(
+        property: Property,
+        lower: f64,
+        include_lower: bool,
+        upper: f64,
+        include_upper: bool,
+    ) -> Result<Filter> {
+        float_create!(Double, property, lower, include_lower, upper, include_upper)
+    }
+
+    pub fn double_gt(property: Property, value: f64) -> Result<Filter> {
+        Self::double_between(property, value, false, f64::INFINITY, true)
+    }
+
+    /// Matches values less than `value` including null.
+    pub fn double_lt(property: Property, value: f64) -> Result<Filter> {
+        Self::double_between(property, f64::NAN, false, value, false)
+    }
+
+    /// Matches floats that have been written as NaN. Null floats are stored as a NaN too but are
+    /// not matched since their bit pattern is [IsarObject::NULL_FLOAT]. `f32::NAN` has the same
+    /// bits as null so only NaNs with a different bit pattern are matched.
+    pub fn float_is_nan(property: Property) -> Result<Filter> {
+        if property.data_type == DataType::Float {
+            let filter_cond = FilterCond::FloatIsNan(FloatIsNanCond { property });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    /// Matches doubles that have been written as NaN. Null doubles are stored as a NaN too but
+    /// are not matched since their bit pattern is [IsarObject::NULL_DOUBLE]. `f64::NAN` has the
+    /// same bits as null so only NaNs with a different bit pattern are matched.
+    pub fn double_is_nan(property: Property) -> Result<Filter> {
+        if property.data_type == DataType::Double {
+            let filter_cond = FilterCond::DoubleIsNan(DoubleIsNanCond { property });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    pub fn int_in(property: Property, values: Vec<i32>) -> Result<Filter> {
+        primitive_in_create!(Int, property, values)
+    }
+
+    pub fn long_in(property: Property, values: Vec<i64>) -> Result<Filter> {
+        if property.data_type == DataType::DateTime || property.data_type == DataType::Decimal {
+            let mut values = values;
+            values.sort_unstable();
+            values.dedup();
+            let filter_cond = FilterCond::LongIn(LongInCond { property, values });
+            Ok(Filter(filter_cond))
+        } else {
+            primitive_in_create!(Long, property, values)
+        }
+    }
+
+    /// Matches values that are not contained in `values`. Null values only match if
+    /// `include_null` is set.
+    pub fn int_not_in(property: Property, values: Vec<i32>, include_null: bool) -> Result<Filter> {
+        if property.data_type == DataType::Int {
+            primitive_not_in_create!(Int, property, values, include_null)
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    /// Matches values that are not contained in `values`. Null values only match if
+    /// `include_null` is set.
+    pub fn long_not_in(property: Property, values: Vec<i64>, include_null: bool) -> Result<Filter> {
+        if property.data_type == DataType::Long
+            || property.data_type == DataType::DateTime
+            || property.data_type == DataType::Decimal
+        {
+            primitive_not_in_create!(Long, property, values, include_null)
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    pub fn string_in(
+        property: Property,
+        values: Vec<Option<&str>>,
+        case_sensitive: bool,
+    ) -> Result<Filter> {
+        if property.data_type == DataType::String {
+            let include_null = values.contains(&None);
+            let values = values
+                .into_iter()
+                .flatten()
+                .map(|value| {
+                    if case_sensitive {
+                        value.to_string()
+                    } else {
+                        value.to_lowercase()
+                    }
+                })
+                .collect();
+            let filter_cond = FilterCond::StringIn(StringInCond {
+                property,
+                values,
+                case_sensitive,
+                include_null,
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
     }
 
     pub fn string_to_bytes(str: Option<&str>, case_sensitive: bool) -> Option<Vec<u8>> {
@@ -105,6 +434,37 @@ impl Filter {
         }
     }
 
+    /// Matches strings that are not contained in `values`. Null values only match if
+    /// `include_null` is set.
+    pub fn string_not_in(
+        property: Property,
+        values: Vec<&str>,
+        case_sensitive: bool,
+        include_null: bool,
+    ) -> Result<Filter> {
+        if property.data_type == DataType::String {
+            let values = values
+                .into_iter()
+                .map(|value| {
+                    if case_sensitive {
+                        value.to_string()
+                    } else {
+                        value.to_lowercase()
+                    }
+                })
+                .collect();
+            let filter_cond = FilterCond::StringNotIn(StringNotInCond {
+                property,
+                values,
+                case_sensitive,
+                include_null,
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
     pub fn string(
         property: Property,
         lower: Option<&str>,
@@ -169,12 +529,110 @@ impl Filter {
         string_filter_create!(Contains, property, value, case_sensitive)
     }
 
+    /// Matches strings against a wildcard pattern where `*` matches any sequence and `?` matches
+    /// a single byte. Wildcards can be escaped with a backslash.
     pub fn string_matches(property: Property, value: &str, case_sensitive: bool) -> Result<Filter> {
         string_filter_create!(Matches, property, value, case_sensitive)
     }
 
+    /// Matches strings against a regular expression. The pattern is compiled once and matches
+    /// anywhere in the string unless it is anchored.
+    pub fn string_matches_regex(
+        property: Property,
+        pattern: &str,
+        case_sensitive: bool,
+    ) -> Result<Filter> {
+        if property.data_type != DataType::String {
+            return illegal_arg("Property does not support this filter.");
+        }
+        let pattern = if case_sensitive {
+            pattern.to_string()
+        } else {
+            format!("(?i){}", pattern)
+        };
+        if let Ok(regex) = Regex::new(&pattern) {
+            let filter_cond =
+                FilterCond::StringMatchesRegex(StringMatchesRegexCond { property, regex });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Invalid regular expression.")
+        }
+    }
+
+    /// Matches objects whose list contains `value` at position `index`. Null lists and lists
+    /// that are too short never match.
+    pub fn list_element(
+        property: Property,
+        index: usize,
+        value: ListElementValue,
+        case_sensitive: bool,
+    ) -> Result<Filter> {
+        let matches_type = matches!(
+            (property.data_type, &value),
+            (DataType::ByteList, ListElementValue::Byte(_))
+                | (DataType::IntList, ListElementValue::Int(_))
+                | (DataType::LongList, ListElementValue::Long(_))
+                | (DataType::FloatList, ListElementValue::Float(_))
+                | (DataType::DoubleList, ListElementValue::Double(_))
+                | (DataType::StringList, ListElementValue::String(_))
+        );
+        if !matches_type {
+            return illegal_arg("Property does not support this filter.");
+        }
+        let value = match value {
+            ListElementValue::String(Some(str)) if !case_sensitive => {
+                ListElementValue::String(Some(str.to_lowercase()))
+            }
+            value => value,
+        };
+        let filter_cond = FilterCond::ListElementEquals(ListElementEqualsCond {
+            property,
+            index,
+            value,
+            case_sensitive,
+        });
+        Ok(Filter(filter_cond))
+    }
+
+    /// Matches int lists with at least one element between `lower` and `upper`. Null and empty
+    /// lists never match.
+    pub fn int_list_any(property: Property, lower: i32, upper: i32) -> Result<Filter> {
+        list_between_create!(AnyInt, IntList, property, lower, upper)
+    }
+
+    /// Matches int lists whose elements are all between `lower` and `upper`. Empty lists always
+    /// match, null lists never do.
+    pub fn int_list_all(property: Property, lower: i32, upper: i32) -> Result<Filter> {
+        list_between_create!(AllInt, IntList, property, lower, upper)
+    }
+
+    /// Matches long lists with at least one element between `lower` and `upper`. Null and empty
+    /// lists never match.
+    pub fn long_list_any(property: Property, lower: i64, upper: i64) -> Result<Filter> {
+        list_between_create!(AnyLong, LongList, property, lower, upper)
+    }
+
+    /// Matches long lists whose elements are all between `lower` and `upper`. Empty lists always
+    /// match, null lists never do.
+    pub fn long_list_all(property: Property, lower: i64, upper: i64) -> Result<Filter> {
+        list_between_create!(AllLong, LongList, property, lower, upper)
+    }
+
+    /// Matches null values. Floats and doubles are null if they are any NaN, see
+    /// [IsarObject::is_null].
     pub fn null(property: Property) -> Filter {
-        let filter_cond = FilterCond::Null(NullCond { property });
+        let filter_cond = FilterCond::IsNull(IsNullCond {
+            property,
+            is_null: true,
+        });
+        Filter(filter_cond)
+    }
+
+    pub fn not_null(property: Property) -> Filter {
+        let filter_cond = FilterCond::IsNull(IsNullCond {
+            property,
+            is_null: false,
+        });
         Filter(filter_cond)
     }
 
@@ -208,6 +666,18 @@ impl Filter {
         Ok(Filter(filter_cond))
     }
 
+    /// Matches objects whose number of linked objects is between `lower` and `upper`.
+    pub fn link_length(
+        collection: &IsarCollection,
+        link_index: usize,
+        lower: u32,
+        upper: u32,
+    ) -> Result<Filter> {
+        let link = collection.get_link(link_index)?;
+        let filter_cond = FilterCond::LinkLength(LinkLengthCond { link, lower, upper });
+        Ok(Filter(filter_cond))
+    }
+
     pub(crate) fn evaluate(
         &self,
         id: &IdKey,
@@ -216,23 +686,49 @@ impl Filter {
     ) -> Result<bool> {
         self.0.evaluate(id, object, cursors)
     }
+
+    pub(crate) fn collect_linked_collections(&self, col_ids: &mut Vec<u64>) {
+        self.0.collect_linked_collections(col_ids)
+    }
+
+    pub(crate) fn uses_links(&self) -> bool {
+        self.0.uses_links()
+    }
+
+    /// Returns the property, the value and the case sensitivity if the filter only matches string
+    /// lists containing a single value.
+    pub(crate) fn get_list_contains(&self) -> Option<(Property, Option<&[u8]>, bool)> {
+        self.0.get_list_contains()
+    }
 }
 
 #[enum_dispatch]
 #[derive(Clone)]
 enum FilterCond {
     IdBetween(IdBetweenCond),
+    BoolEqual(BoolEqualCond),
     ByteBetween(ByteBetweenCond),
     IntBetween(IntBetweenCond),
     LongBetween(LongBetweenCond),
+    DecimalBetween(DecimalBetweenCond),
     FloatBetween(FloatBetweenCond),
     DoubleBetween(DoubleBetweenCond),
+    FloatIsNan(FloatIsNanCond),
+    DoubleIsNan(DoubleIsNanCond),
+
+    IntIn(IntInCond),
+    LongIn(LongInCond),
+    IntNotIn(IntNotInCond),
+    LongNotIn(LongNotInCond),
 
     StringBetween(StringBetweenCond),
+    StringIn(StringInCond),
+    StringNotIn(StringNotInCond),
     StringStartsWith(StringStartsWithCond),
     StringEndsWith(StringEndsWithCond),
     StringContains(StringContainsCond),
     StringMatches(StringMatchesCond),
+    StringMatchesRegex(StringMatchesRegexCond),
 
     AnyByteBetween(AnyByteBetweenCond),
     AnyIntBetween(AnyIntBetweenCond),
@@ -240,18 +736,65 @@ enum FilterCond {
     AnyFloatBetween(AnyFloatBetweenCond),
     AnyDoubleBetween(AnyDoubleBetweenCond),
 
+    AllIntBetween(AllIntBetweenCond),
+    AllLongBetween(AllLongBetweenCond),
+
     AnyStringBetween(AnyStringBetweenCond),
     AnyStringStartsWith(AnyStringStartsWithCond),
     AnyStringEndsWith(AnyStringEndsWithCond),
     AnyStringContains(AnyStringContainsCond),
     AnyStringMatches(AnyStringMatchesCond),
 
-    Null(NullCond),
+    ListElementEquals(ListElementEqualsCond),
+
+    IsNull(IsNullCond),
     And(AndCond),
     Or(OrCond),
     Not(NotCond),
     Static(StaticCond),
     Link(LinkCond),
+    LinkLength(LinkLengthCond),
+}
+
+impl FilterCond {
+    fn collect_linked_collections(&self, col_ids: &mut Vec<u64>) {
+        match self {
+            FilterCond::And(AndCond { filters }) | FilterCond::Or(OrCond { filters }) => {
+                for filter in filters {
+                    filter.collect_linked_collections(col_ids);
+                }
+            }
+            FilterCond::Not(NotCond { filter }) => filter.collect_linked_collections(col_ids),
+            FilterCond::Link(LinkCond { link, filter }) => {
+                col_ids.push(link.get_target_col_runtime_id());
+                filter.collect_linked_collections(col_ids);
+            }
+            _ => {}
+        }
+    }
+
+    fn get_list_contains(&self) -> Option<(Property, Option<&[u8]>, bool)> {
+        match self {
+            FilterCond::AnyStringBetween(cond) if cond.lower == cond.upper => {
+                Some((cond.property, cond.lower.as_deref(), cond.case_sensitive))
+            }
+            FilterCond::And(AndCond { filters }) => {
+                filters.iter().find_map(|filter| filter.get_list_contains())
+            }
+            _ => None,
+        }
+    }
+
+    fn uses_links(&self) -> bool {
+        match self {
+            FilterCond::And(AndCond { filters }) | FilterCond::Or(OrCond { filters }) => {
+                filters.iter().any(|filter| filter.uses_links())
+            }
+            FilterCond::Not(NotCond { filter }) => filter.uses_links(),
+            FilterCond::Link(_) | FilterCond::LinkLength(_) => true,
+            _ => false,
+        }
+    }
 }
 
 #[enum_dispatch(FilterCond)]
@@ -277,6 +820,18 @@ impl Condition for IdBetweenCond {
     }
 }
 
+#[derive(Clone)]
+struct BoolEqualCond {
+    property: Property,
+    value: u8,
+}
+
+impl Condition for BoolEqualCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        Ok(object.read_byte(self.property) == self.value)
+    }
+}
+
 #[macro_export]
 macro_rules! filter_between_struct {
     ($name:ident, $data_type:ident, $type:ty) => {
@@ -312,6 +867,65 @@ filter_between_struct!(IntBetweenCond, Int, i32);
 primitive_filter_between!(IntBetweenCond, read_int);
 filter_between_struct!(LongBetweenCond, Long, i64);
 primitive_filter_between!(LongBetweenCond, read_long);
+filter_between_struct!(DecimalBetweenCond, Decimal, i64);
+primitive_filter_between!(DecimalBetweenCond, read_long);
+
+#[macro_export]
+macro_rules! primitive_filter_in {
+    ($name:ident, $type:ty, $prop_accessor:ident) => {
+        #[derive(Clone)]
+        struct $name {
+            property: Property,
+            values: Vec<$type>,
+        }
+
+        impl Condition for $name {
+            fn evaluate(
+                &self,
+                _id: &IdKey,
+                object: IsarObject,
+                _: Option<&IsarCursors>,
+            ) -> Result<bool> {
+                let val = object.$prop_accessor(self.property);
+                Ok(self.values.binary_search(&val).is_ok())
+            }
+        }
+    };
+}
+
+primitive_filter_in!(IntInCond, i32, read_int);
+primitive_filter_in!(LongInCond, i64, read_long);
+
+#[macro_export]
+macro_rules! primitive_filter_not_in {
+    ($name:ident, $type:ty, $prop_accessor:ident, $null_value:expr) => {
+        #[derive(Clone)]
+        struct $name {
+            property: Property,
+            values: Vec<$type>,
+            include_null: bool,
+        }
+
+        impl Condition for $name {
+            fn evaluate(
+                &self,
+                _id: &IdKey,
+                object: IsarObject,
+                _: Option<&IsarCursors>,
+            ) -> Result<bool> {
+                let val = object.$prop_accessor(self.property);
+                if val == $null_value {
+                    Ok(self.include_null)
+                } else {
+                    Ok(self.values.binary_search(&val).is_err())
+                }
+            }
+        }
+    };
+}
+
+primitive_filter_not_in!(IntNotInCond, i32, read_int, IsarObject::NULL_INT);
+primitive_filter_not_in!(LongNotInCond, i64, read_long, IsarObject::NULL_LONG);
 
 #[macro_export]
 macro_rules! primitive_filter_between_list {
@@ -358,28 +972,99 @@ primitive_filter_between_list!(AnyIntBetweenCond, read_int_list);
 filter_between_struct!(AnyLongBetweenCond, Long, i64);
 primitive_filter_between_list!(AnyLongBetweenCond, read_long_list);
 
+#[macro_export]
+macro_rules! primitive_filter_all_between_list {
+    ($name:ident, $prop_accessor:ident) => {
+        impl Condition for $name {
+            fn evaluate(
+                &self,
+                _id: &IdKey,
+                object: IsarObject,
+                _: Option<&IsarCursors>,
+            ) -> Result<bool> {
+                let vals = object.$prop_accessor(self.property);
+                if let Some(vals) = vals {
+                    for val in vals {
+                        if self.lower > val || self.upper < val {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+        }
+    };
+}
+
+filter_between_struct!(AllIntBetweenCond, Int, i32);
+primitive_filter_all_between_list!(AllIntBetweenCond, read_int_list);
+filter_between_struct!(AllLongBetweenCond, Long, i64);
+primitive_filter_all_between_list!(AllLongBetweenCond, read_long_list);
+
+#[macro_export]
+macro_rules! float_filter_between_struct {
+    ($name:ident, $type:ty) => {
+        #[derive(Clone)]
+        struct $name {
+            upper: $type,
+            include_upper: bool,
+            lower: $type,
+            include_lower: bool,
+            property: Property,
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! float_filter_between {
     ($name:ident, $prop_accessor:ident) => {
         impl Condition for $name {
             fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
                 let val = object.$prop_accessor(self.property);
-                Ok(float_filter_between!(eval val, self.lower, self.upper))
+                Ok(float_filter_between!(eval val, self))
             }
         }
     };
 
-    (eval $val:expr, $lower:expr, $upper:expr) => {{
-        ($lower < $val || $lower.is_nan()) &&
-        ($upper > $val || $val.is_nan() || ($upper.is_infinite() && $upper.is_sign_positive()))
+    (eval $val:expr, $filter:expr) => {{
+        ($filter.lower < $val || ($filter.include_lower && $filter.lower == $val) || $filter.lower.is_nan()) &&
+        ($filter.upper > $val || ($filter.include_upper && $filter.upper == $val) || $val.is_nan() ||
+            ($filter.upper.is_infinite() && $filter.upper.is_sign_positive()))
     }};
 }
 
-filter_between_struct!(FloatBetweenCond, Float, f32);
+float_filter_between_struct!(FloatBetweenCond, f32);
 float_filter_between!(FloatBetweenCond, read_float);
-filter_between_struct!(DoubleBetweenCond, Double, f64);
+float_filter_between_struct!(DoubleBetweenCond, f64);
 float_filter_between!(DoubleBetweenCond, read_double);
 
+#[macro_export]
+macro_rules! float_filter_is_nan {
+    ($name:ident, $prop_accessor:ident, $null:expr) => {
+        #[derive(Clone)]
+        struct $name {
+            property: Property,
+        }
+
+        impl Condition for $name {
+            fn evaluate(
+                &self,
+                _id: &IdKey,
+                object: IsarObject,
+                _: Option<&IsarCursors>,
+            ) -> Result<bool> {
+                let val = object.$prop_accessor(self.property);
+                Ok(val.is_nan() && val.to_bits() != $null.to_bits())
+            }
+        }
+    };
+}
+
+float_filter_is_nan!(FloatIsNanCond, read_float, IsarObject::NULL_FLOAT);
+float_filter_is_nan!(DoubleIsNanCond, read_double, IsarObject::NULL_DOUBLE);
+
 #[macro_export]
 macro_rules! float_filter_between_list {
     ($name:ident, $prop_accessor:ident) => {
@@ -388,7 +1073,7 @@ macro_rules! float_filter_between_list {
                 let vals = object.$prop_accessor(self.property);
                 if let Some(vals) = vals {
                     for val in vals {
-                        if float_filter_between!(eval val, self.lower, self.upper) {
+                        if float_filter_between!(eval val, self) {
                             return Ok(true);
                         }
                     }
@@ -399,9 +1084,9 @@ macro_rules! float_filter_between_list {
     };
 }
 
-filter_between_struct!(AnyFloatBetweenCond, Float, f32);
+float_filter_between_struct!(AnyFloatBetweenCond, f32);
 float_filter_between_list!(AnyFloatBetweenCond, read_float_list);
-filter_between_struct!(AnyDoubleBetweenCond, Double, f64);
+float_filter_between_struct!(AnyDoubleBetweenCond, f64);
 float_filter_between_list!(AnyDoubleBetweenCond, read_double_list);
 
 #[derive(Clone)]
@@ -487,6 +1172,100 @@ impl Condition for AnyStringBetweenCond {
     }
 }
 
+#[derive(Clone)]
+struct ListElementEqualsCond {
+    property: Property,
+    index: usize,
+    value: ListElementValue,
+    case_sensitive: bool,
+}
+
+impl Condition for ListElementEqualsCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let (property, index) = (self.property, self.index);
+        let result = match &self.value {
+            ListElementValue::Byte(value) => {
+                object.read_byte_list_element(property, index) == Some(*value)
+            }
+            ListElementValue::Int(value) => {
+                object.read_int_list_element(property, index) == Some(*value)
+            }
+            ListElementValue::Long(value) => {
+                object.read_long_list_element(property, index) == Some(*value)
+            }
+            ListElementValue::Float(value) => {
+                object.read_float_list_element(property, index) == Some(*value)
+            }
+            ListElementValue::Double(value) => {
+                object.read_double_list_element(property, index) == Some(*value)
+            }
+            ListElementValue::String(value) => {
+                if let Some(element) = object.read_string_list_element(property, index) {
+                    if self.case_sensitive {
+                        element == value.as_deref()
+                    } else {
+                        element.map(|e| e.to_lowercase()) == *value
+                    }
+                } else {
+                    false
+                }
+            }
+        };
+        Ok(result)
+    }
+}
+
+/// Lowercases `value` but only allocates if it contains characters that change.
+fn to_lowercase(value: &str) -> Cow<str> {
+    let is_lowercase = value.chars().all(|c| {
+        let mut lowercase = c.to_lowercase();
+        lowercase.next() == Some(c) && lowercase.next().is_none()
+    });
+    if is_lowercase {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(value.to_lowercase())
+    }
+}
+
+#[derive(Clone)]
+struct StringInCond {
+    property: Property,
+    values: HashSet<String>,
+    case_sensitive: bool,
+    include_null: bool,
+}
+
+impl Condition for StringInCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let result = match object.read_string(self.property) {
+            Some(value) if self.case_sensitive => self.values.contains(value),
+            Some(value) => self.values.contains(&*to_lowercase(value)),
+            None => self.include_null,
+        };
+        Ok(result)
+    }
+}
+
+#[derive(Clone)]
+struct StringNotInCond {
+    property: Property,
+    values: HashSet<String>,
+    case_sensitive: bool,
+    include_null: bool,
+}
+
+impl Condition for StringNotInCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let result = match object.read_string(self.property) {
+            Some(value) if self.case_sensitive => !self.values.contains(value),
+            Some(value) => !self.values.contains(&*to_lowercase(value)),
+            None => self.include_null,
+        };
+        Ok(result)
+    }
+}
+
 #[macro_export]
 macro_rules! string_filter_struct {
     ($name:ident) => {
@@ -568,18 +1347,34 @@ string_filter!(StringContains);
 string_filter!(StringMatches);
 
 #[derive(Clone)]
-struct NullCond {
+struct StringMatchesRegexCond {
     property: Property,
+    regex: Regex,
 }
 
-impl Condition for NullCond {
+impl Condition for StringMatchesRegexCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let result = object
+            .read_string(self.property)
+            .map_or(false, |value| self.regex.is_match(value));
+        Ok(result)
+    }
+}
+
+#[derive(Clone)]
+struct IsNullCond {
+    property: Property,
+    is_null: bool,
+}
+
+impl Condition for IsNullCond {
     fn evaluate(
         &self,
         _id: &IdKey,
         object: IsarObject,
         _cursors: Option<&IsarCursors>,
     ) -> Result<bool> {
-        Ok(object.is_null(self.property))
+        Ok(object.is_null(self.property) == self.is_null)
     }
 }
 
@@ -692,3 +1487,30 @@ impl LinkCond {
         }))
     }
 }
+
+#[derive(Clone)]
+struct LinkLengthCond {
+    link: IsarLink,
+    lower: u32,
+    upper: u32,
+}
+
+impl Condition for LinkLengthCond {
+    fn evaluate(
+        &self,
+        id: &IdKey,
+        _object: IsarObject,
+        cursors: Option<&IsarCursors>,
+    ) -> Result<bool> {
+        if let Some(cursors) = cursors {
+            let mut count = 0;
+            self.link.iter_ids(cursors, id, |_, _| {
+                count += 1;
+                Ok(count <= self.upper)
+            })?;
+            Ok(self.lower <= count && self.upper >= count)
+        } else {
+            Ok(true)
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::error::Result;
 use crate::id_key::IdKey;
 use crate::mdbx::db::Db;
 use crate::object::isar_object::IsarObject;
+use crate::query::query_plan::WhereClausePlan;
 use crate::query::Sort;
 use intmap::IntMap;
 
@@ -28,6 +29,21 @@ impl IdWhereClause {
         self.upper < self.lower
     }
 
+    /// Returns the part of this where clause that follows `id` in iteration order.
+    pub(crate) fn after(&self, id: i64) -> Option<Self> {
+        let (lower, upper) = if self.sort == Sort::Ascending {
+            (self.lower.max(id.checked_add(1)?), self.upper)
+        } else {
+            (self.lower, self.upper.min(id.checked_sub(1)?))
+        };
+        let wc = IdWhereClause::new(self.db, lower, upper, self.sort);
+        if wc.is_empty() {
+            None
+        } else {
+            Some(wc)
+        }
+    }
+
     pub(crate) fn id_matches(&self, oid: i64) -> bool {
         self.lower <= oid && self.upper >= oid
     }
@@ -63,8 +79,20 @@ impl IdWhereClause {
         )
     }
 
+    pub(crate) fn explain(&self) -> WhereClausePlan {
+        let ascending = self.sort == Sort::Ascending;
+        if self.lower == i64::MIN && self.upper == i64::MAX {
+            WhereClausePlan::PrimaryScan { ascending }
+        } else {
+            WhereClausePlan::Id {
+                lower: self.lower,
+                upper: self.upper,
+                ascending,
+            }
+        }
+    }
+
     pub(crate) fn is_overlapping(&self, other: &Self) -> bool {
-        (self.lower <= other.lower && self.upper >= other.upper)
-            || (other.lower <= self.lower && other.upper >= self.upper)
+        self.lower <= other.upper && other.lower <= self.upper
     }
 }
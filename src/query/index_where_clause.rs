@@ -3,11 +3,15 @@ use crate::error::{IsarError, Result};
 use crate::id_key::IdKey;
 use crate::index::index_key::IndexKey;
 use crate::index::index_key_builder::IndexKeyBuilder;
-use crate::index::IsarIndex;
+use crate::index::{IndexProperty, IsarIndex};
 use crate::mdbx::db::Db;
+use crate::mdbx::ByteKey;
 use crate::object::isar_object::IsarObject;
+use crate::query::query_plan::{IndexPropertyPlan, WhereClausePlan};
 use crate::query::Sort;
 use intmap::IntMap;
+use itertools::Itertools;
+use std::cmp::Ordering;
 
 #[derive(Clone)]
 pub(crate) struct IndexWhereClause {
@@ -15,6 +19,8 @@ pub(crate) struct IndexWhereClause {
     index: IsarIndex,
     lower_key: IndexKey,
     upper_key: IndexKey,
+    // bounds with the full strings if the keys contain truncated strings
+    full_keys: Option<(IndexKey, IndexKey)>,
     skip_duplicates: bool,
     sort: Sort,
 }
@@ -25,6 +31,7 @@ impl IndexWhereClause {
         index: IsarIndex,
         lower_key: IndexKey,
         upper_key: IndexKey,
+        full_keys: Option<(IndexKey, IndexKey)>,
         skip_duplicates: bool,
         sort: Sort,
     ) -> Result<Self> {
@@ -33,17 +40,43 @@ impl IndexWhereClause {
             index,
             lower_key,
             upper_key,
+            full_keys,
             skip_duplicates,
             sort,
         })
     }
 
     pub fn object_matches(&self, object: IsarObject) -> bool {
+        if let Some((lower_key, upper_key)) = &self.full_keys {
+            let properties = self
+                .index
+                .properties
+                .iter()
+                .map(|p| IndexProperty {
+                    prefix_len: IsarIndex::MAX_STRING_INDEX_SIZE,
+                    ..*p
+                })
+                .collect_vec();
+            Self::has_key_between(&properties, object, lower_key, upper_key)
+        } else {
+            let properties = &self.index.properties;
+            Self::has_key_between(properties, object, &self.lower_key, &self.upper_key)
+        }
+    }
+
+    fn has_key_between(
+        properties: &[IndexProperty],
+        object: IsarObject,
+        lower_key: &IndexKey,
+        upper_key: &IndexKey,
+    ) -> bool {
         let mut key_matches = false;
-        let key_builder = IndexKeyBuilder::new(&self.index.properties);
+        let key_builder = IndexKeyBuilder::new(properties);
         key_builder
             .create_keys(object, |key| {
-                key_matches = key >= &self.lower_key && key <= &self.upper_key;
+                key_matches = key >= lower_key
+                    && ByteKey::new(upper_key.as_bytes()).cmp_prefix(key.as_bytes())
+                        != Ordering::Less;
                 Ok(!key_matches)
             })
             .unwrap();
@@ -79,8 +112,8 @@ impl IndexWhereClause {
     {
         let mut data_cursor = cursors.get_cursor(self.db)?;
         self.iter_ids(cursors, |id_key| {
-            if let Some(result_ids) = result_ids.as_deref_mut() {
-                if !result_ids.insert(id_key.get_unsigned_id(), ()) {
+            if let Some(result_ids) = result_ids.as_deref() {
+                if result_ids.contains_key(id_key.get_unsigned_id()) {
                     return Ok(true);
                 }
             }
@@ -91,14 +124,46 @@ impl IndexWhereClause {
             })?;
             let object = IsarObject::from_bytes(object);
 
+            // truncated strings sharing the prefix of a bound are not ordered by the full string
+            if self.full_keys.is_some() && !self.object_matches(object) {
+                return Ok(true);
+            }
+            if let Some(result_ids) = result_ids.as_deref_mut() {
+                result_ids.insert(id_key.get_unsigned_id(), ());
+            }
+
             callback(id_key, object)
         })
     }
 
+    pub fn explain(&self) -> WhereClausePlan {
+        let properties = self
+            .index
+            .properties
+            .iter()
+            .map(|p| IndexPropertyPlan {
+                offset: p.property.offset,
+                index_type: p.index_type,
+                case_sensitive: p.case_sensitive,
+            })
+            .collect();
+        WhereClausePlan::Index {
+            properties,
+            unique: self.index.unique,
+            lower_key: self.lower_key.as_bytes().to_vec(),
+            upper_key: self.upper_key.as_bytes().to_vec(),
+            skip_duplicates: self.skip_duplicates,
+            ascending: self.sort == Sort::Ascending,
+        }
+    }
+
+    /// Clauses of different indexes may always return the same objects.
     pub fn is_overlapping(&self, other: &Self) -> bool {
-        self.index == other.index
-            && ((self.lower_key <= other.lower_key && self.upper_key >= other.upper_key)
-                || (other.lower_key <= self.lower_key && other.upper_key >= self.upper_key))
+        self.index != other.index
+            || (ByteKey::new(other.upper_key.as_bytes()).cmp_prefix(self.lower_key.as_bytes())
+                != Ordering::Less
+                && ByteKey::new(self.upper_key.as_bytes()).cmp_prefix(other.lower_key.as_bytes())
+                    != Ordering::Less)
     }
 
     pub fn has_duplicates(&self) -> bool {
@@ -3,6 +3,7 @@ use crate::error::Result;
 use crate::id_key::IdKey;
 use crate::link::IsarLink;
 use crate::object::isar_object::IsarObject;
+use crate::query::query_plan::WhereClausePlan;
 use intmap::IntMap;
 
 #[derive(Clone)]
@@ -16,6 +17,10 @@ impl LinkWhereClause {
         Ok(LinkWhereClause { link, id })
     }
 
+    pub(crate) fn explain(&self) -> WhereClausePlan {
+        WhereClausePlan::Link { id: self.id }
+    }
+
     pub fn iter<'txn, 'env, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
@@ -1,14 +1,17 @@
 use intmap::IntMap;
 use serde_json::{json, Value};
 use std::cmp::Ordering;
+use std::sync::atomic::{self, AtomicBool};
 
 use crate::collection::IsarCollection;
 use crate::cursor::IsarCursors;
-use crate::error::Result;
+use crate::error::{illegal_arg, IsarError, Result};
 use crate::id_key::IdKey;
 use crate::object::isar_object::{IsarObject, Property};
 use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::object::object_builder::ObjectBuilder;
 use crate::query::filter::Filter;
+use crate::query::query_plan::{DistinctPlan, QueryPlan, SortPlan};
 use crate::query::where_clause::WhereClause;
 use crate::txn::IsarTxn;
 
@@ -18,7 +21,8 @@ mod id_where_clause;
 mod index_where_clause;
 mod link_where_clause;
 pub mod query_builder;
-mod where_clause;
+pub mod query_plan;
+pub(crate) mod where_clause;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum Sort {
@@ -31,6 +35,9 @@ pub enum Case {
     Insensitive,
 }
 
+/// A query created by a [QueryBuilder](query_builder::QueryBuilder). It does not hold any
+/// transaction state, so a query can be built once and executed in any number of transactions
+/// of its instance. Queries are `Send` and `Sync` and may be used from multiple threads at once.
 #[derive(Clone)]
 pub struct Query {
     instance_id: u64,
@@ -41,6 +48,7 @@ pub struct Query {
     distinct: Vec<(Property, bool)>,
     offset: usize,
     limit: usize,
+    max_results: usize,
 }
 
 impl<'txn> Query {
@@ -53,6 +61,7 @@ impl<'txn> Query {
         distinct: Vec<(Property, bool)>,
         offset: usize,
         limit: usize,
+        max_results: usize,
     ) -> Self {
         let where_clauses_dup = Self::check_where_clauses_duplicates(&where_clauses);
         Query {
@@ -64,6 +73,7 @@ impl<'txn> Query {
             distinct,
             offset,
             limit,
+            max_results,
         }
     }
 
@@ -84,6 +94,7 @@ impl<'txn> Query {
     pub(crate) fn execute_raw<'env, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
+        cancel: Option<&AtomicBool>,
         mut callback: F,
     ) -> Result<()>
     where
@@ -100,6 +111,7 @@ impl<'txn> Query {
 
         for where_clause in &self.where_clauses {
             let result = where_clause.iter(cursors, result_ids.as_mut(), |id_key, object| {
+                Self::check_cancelled(cancel)?;
                 if filter.evaluate(&id_key, object, Some(cursors))? {
                     callback(id_key, object)
                 } else {
@@ -114,9 +126,18 @@ impl<'txn> Query {
         Ok(())
     }
 
+    fn check_cancelled(cancel: Option<&AtomicBool>) -> Result<()> {
+        if cancel.map_or(false, |cancel| cancel.load(atomic::Ordering::SeqCst)) {
+            Err(IsarError::Cancelled {})
+        } else {
+            Ok(())
+        }
+    }
+
     fn execute_unsorted<'env, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
+        cancel: Option<&AtomicBool>,
         callback: F,
     ) -> Result<()>
     where
@@ -125,10 +146,10 @@ impl<'txn> Query {
         if !self.distinct.is_empty() {
             let callback = self.add_distinct_unsorted(callback);
             let callback = self.add_offset_limit_unsorted(callback);
-            self.execute_raw(cursors, callback)
+            self.execute_raw(cursors, cancel, callback)
         } else {
             let callback = self.add_offset_limit_unsorted(callback);
-            self.execute_raw(cursors, callback)
+            self.execute_raw(cursors, cancel, callback)
         }
     }
 
@@ -182,14 +203,15 @@ impl<'txn> Query {
     fn execute_sorted<'env>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
+        cancel: Option<&AtomicBool>,
     ) -> Result<Vec<(IdKey<'txn>, IsarObject<'txn>)>> {
         let mut results = vec![];
-        self.execute_raw(cursors, |id_key, object| {
+        self.execute_raw(cursors, cancel, |id_key, object| {
             results.push((id_key, object));
             Ok(true)
         })?;
 
-        results.sort_unstable_by(|(_, o1), (_, o2)| {
+        results.sort_by(|(_, o1), (_, o2)| {
             for (p, sort) in &self.sort {
                 let ord = o1.compare_property(o2, *p);
                 if ord != Ordering::Equal {
@@ -232,6 +254,58 @@ impl<'txn> Query {
         results.into_iter().skip(self.offset).take(self.limit)
     }
 
+    /// Describes how the query will be executed without accessing the database.
+    pub fn explain(&self) -> QueryPlan {
+        QueryPlan {
+            where_clauses: self.where_clauses.iter().map(|wc| wc.explain()).collect(),
+            dedup_ids: self.where_clauses_dup,
+            post_filter: self.filter.is_some(),
+            sort_buffer: !self.sort.is_empty(),
+            sort: self
+                .sort
+                .iter()
+                .map(|(p, sort)| SortPlan {
+                    offset: p.offset,
+                    ascending: *sort == Sort::Ascending,
+                })
+                .collect(),
+            distinct: self
+                .distinct
+                .iter()
+                .map(|(p, case_sensitive)| DistinctPlan {
+                    offset: p.offset,
+                    case_sensitive: *case_sensitive,
+                })
+                .collect(),
+            offset: self.offset,
+            limit: if self.limit == usize::MAX {
+                None
+            } else {
+                Some(self.limit)
+            },
+        }
+    }
+
+    pub(crate) fn get_linked_collections(&self) -> Vec<u64> {
+        let mut col_ids = vec![];
+        if let Some(filter) = &self.filter {
+            filter.collect_linked_collections(&mut col_ids);
+        }
+        col_ids.sort_unstable();
+        col_ids.dedup();
+        col_ids
+    }
+
+    /// Whether the results of the query depend on links and may change when objects are linked
+    /// or unlinked.
+    pub(crate) fn uses_links(&self) -> bool {
+        let link_wc = self
+            .where_clauses
+            .iter()
+            .any(|wc| matches!(wc, WhereClause::Link(_)));
+        link_wc || self.filter.as_ref().map_or(false, |f| f.uses_links())
+    }
+
     pub(crate) fn maybe_matches_wc_filter(&self, id: i64, object: IsarObject) -> bool {
         let maybe_matches = self
             .where_clauses
@@ -253,17 +327,19 @@ impl<'txn> Query {
         &self,
         cursors: &IsarCursors<'txn, 'env>,
         skip_sorting: bool,
+        cancel: Option<&AtomicBool>,
         mut callback: F,
     ) -> Result<()>
     where
         F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
     {
         if self.sort.is_empty() || skip_sorting {
-            self.execute_unsorted(cursors, callback)?;
+            self.execute_unsorted(cursors, cancel, callback)?;
         } else {
-            let results = self.execute_sorted(cursors)?;
+            let results = self.execute_sorted(cursors, cancel)?;
             let results_iter = self.add_offset_limit_sorted(results);
             for (id, object) in results_iter {
+                Self::check_cancelled(cancel)?;
                 if !callback(id, object)? {
                     break;
                 }
@@ -272,35 +348,217 @@ impl<'txn> Query {
         Ok(())
     }
 
+    fn add_max_results<F>(
+        &self,
+        mut callback: F,
+    ) -> impl FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        let max_results = self.max_results;
+        let mut count = 0;
+        move |id_key, object| {
+            count += 1;
+            if count > max_results {
+                Err(IsarError::ResultTooLarge { max_results })
+            } else {
+                callback(id_key, object)
+            }
+        }
+    }
+
     pub fn find_while<F>(&self, txn: &'txn mut IsarTxn, mut callback: F) -> Result<()>
     where
         F: FnMut(i64, IsarObject<'txn>) -> bool,
     {
         txn.read(self.instance_id, |cursors| {
-            self.find_while_internal(cursors, false, |id_key, object| {
+            self.find_while_internal(cursors, false, None, |id_key, object| {
                 Ok(callback(id_key.get_id(), object))
             })
         })
     }
 
+    /// Like [find_while](Self::find_while) but fails with [IsarError::ResultTooLarge] if the
+    /// query returns more objects than its max results. Only the `find_all` methods check the max
+    /// results.
+    pub fn find_all<F>(&self, txn: &'txn mut IsarTxn, mut callback: F) -> Result<()>
+    where
+        F: FnMut(i64, IsarObject<'txn>) -> bool,
+    {
+        txn.read(self.instance_id, |cursors| {
+            let callback =
+                self.add_max_results(|id_key, object| Ok(callback(id_key.get_id(), object)));
+            self.find_while_internal(cursors, false, None, callback)
+        })
+    }
+
+    /// Like [find_all](Self::find_all) but fails with [IsarError::Cancelled] once `cancel` is
+    /// set. The flag is checked for every object the query visits. Objects passed to the callback
+    /// before the cancellation are a partial result.
+    pub fn find_all_cancellable<F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        cancel: &AtomicBool,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(i64, IsarObject<'txn>) -> bool,
+    {
+        txn.read(self.instance_id, |cursors| {
+            let callback =
+                self.add_max_results(|id_key, object| Ok(callback(id_key.get_id(), object)));
+            self.find_while_internal(cursors, false, Some(cancel), callback)
+        })
+    }
+
+    /// Like [find_all](Self::find_all) but passes an object containing only the value of
+    /// `property` to the callback. The value is copied into a buffer that is reused for every
+    /// object so the passed object is only valid during the callback.
+    pub fn find_all_property<F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        property: Property,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(i64, IsarObject) -> bool,
+    {
+        let projected_props = [Property::new(property.data_type, 2)];
+        let mut buffer = Some(vec![]);
+        self.find_all(txn, |id, object| {
+            let mut builder = ObjectBuilder::new(&projected_props, buffer.take());
+            builder.write_from(object, property);
+            let result = callback(id, builder.finish());
+            buffer = Some(builder.recycle());
+            result
+        })
+    }
+
+    /// Like [find_all](Self::find_all) but collects up to `batch_size` objects and passes
+    /// them to the callback together. Only the last batch may contain fewer objects. The objects
+    /// of a batch are valid for the lifetime of the transaction.
+    pub fn find_all_batched<F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        batch_size: usize,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&[(i64, IsarObject<'txn>)]),
+    {
+        if batch_size == 0 {
+            return illegal_arg("Batch size must be greater than 0.");
+        }
+        let mut batch = Vec::with_capacity(batch_size);
+        self.find_all(txn, |id, object| {
+            batch.push((id, object));
+            if batch.len() == batch_size {
+                callback(&batch);
+                batch.clear();
+            }
+            true
+        })?;
+        if !batch.is_empty() {
+            callback(&batch);
+        }
+        Ok(())
+    }
+
     pub fn find_all_vec(&self, txn: &'txn mut IsarTxn) -> Result<Vec<(i64, IsarObject<'txn>)>> {
         let mut results = vec![];
-        self.find_while(txn, |id, object| {
+        self.find_all(txn, |id, object| {
             results.push((id, object));
             true
         })?;
         Ok(results)
     }
 
+    /// Returns the first object of the query results. Unsorted queries stop at the first match.
+    /// Sorted queries still have to collect and sort all matching objects before the first
+    /// object in sort order can be returned.
+    pub fn find_first(&self, txn: &'txn mut IsarTxn) -> Result<Option<(i64, IsarObject<'txn>)>> {
+        let mut result = None;
+        self.find_while(txn, |id, object| {
+            result = Some((id, object));
+            false
+        })?;
+        Ok(result)
+    }
+
+    /// Returns up to `limit` objects following the object with the id `last_id`. Only queries with
+    /// a single id where clause and without sorting or distinct properties are supported. The
+    /// scan starts right after `last_id` so every page is as fast as the first one. The offset
+    /// and limit of the query are ignored.
+    pub fn find_after(
+        &self,
+        txn: &'txn mut IsarTxn,
+        last_id: i64,
+        limit: usize,
+    ) -> Result<Vec<(i64, IsarObject<'txn>)>> {
+        let wc = match self.where_clauses.as_slice() {
+            [WhereClause::Id(wc)] if self.sort.is_empty() && self.distinct.is_empty() => wc,
+            _ => return illegal_arg("Keyset pagination requires an id ordered query."),
+        };
+        let query = Query::new(
+            self.instance_id,
+            wc.after(last_id).map(WhereClause::Id).into_iter().collect(),
+            self.filter.clone(),
+            vec![],
+            vec![],
+            0,
+            limit,
+            usize::MAX,
+        );
+        query.find_all_vec(txn)
+    }
+
+    /// Checks whether any object matches the query. Stops at the first match and ignores the
+    /// sort order.
+    pub fn any(&self, txn: &mut IsarTxn) -> Result<bool> {
+        let mut found = false;
+        txn.read(self.instance_id, |cursors| {
+            self.find_while_internal(cursors, true, None, |_, _| {
+                found = true;
+                Ok(false)
+            })
+        })?;
+        Ok(found)
+    }
+
     pub fn count(&self, txn: &mut IsarTxn) -> Result<u32> {
         let mut counter = 0;
-        self.find_while(txn, |_, _| {
-            counter += 1;
-            true
+        txn.read(self.instance_id, |cursors| {
+            let callback = |_, _| {
+                counter += 1;
+                Ok(true)
+            };
+            if !self.distinct.is_empty() {
+                let callback = self.add_distinct_unsorted(callback);
+                self.execute_raw(cursors, None, callback)
+            } else {
+                self.execute_raw(cursors, None, callback)
+            }
         })?;
         Ok(counter)
     }
 
+    /// Deletes all objects matching the query including their index entries and links. The ids
+    /// are collected before the first object is deleted. Returns the number of deleted objects.
+    pub fn delete_all(&self, txn: &mut IsarTxn, collection: &IsarCollection) -> Result<u64> {
+        let mut ids = vec![];
+        self.find_while(txn, |id, _| {
+            ids.push(id);
+            true
+        })?;
+        let mut count = 0;
+        for id in ids {
+            if collection.delete(txn, id)? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
     pub fn export_json(
         &self,
         txn: &mut IsarTxn,
@@ -2,12 +2,15 @@ use super::index_where_clause::IndexWhereClause;
 use crate::collection::IsarCollection;
 use crate::error::{illegal_arg, Result};
 use crate::index::index_key::IndexKey;
-use crate::object::isar_object::Property;
+use crate::link::IsarLink;
+use crate::object::data_type::DataType;
+use crate::object::isar_object::{IsarObject, Property};
 use crate::query::filter::Filter;
 use crate::query::id_where_clause::IdWhereClause;
 use crate::query::link_where_clause::LinkWhereClause;
 use crate::query::where_clause::WhereClause;
 use crate::query::{Query, Sort};
+use crate::schema::index_schema::IndexType;
 
 pub struct QueryBuilder<'a> {
     collection: &'a IsarCollection,
@@ -17,6 +20,7 @@ pub struct QueryBuilder<'a> {
     distinct: Vec<(Property, bool)>,
     offset: usize,
     limit: usize,
+    max_results: usize,
 }
 
 impl<'a> QueryBuilder<'a> {
@@ -29,6 +33,7 @@ impl<'a> QueryBuilder<'a> {
             distinct: vec![],
             offset: 0,
             limit: usize::MAX,
+            max_results: usize::MAX,
         }
     }
 
@@ -38,13 +43,22 @@ impl<'a> QueryBuilder<'a> {
         }
     }
 
+    /// Adds a where clause for the ids between `start` and `end`. If `start` is greater than
+    /// `end`, the objects are returned in descending order.
     pub fn add_id_where_clause(&mut self, start: i64, end: i64) -> Result<()> {
-        self.init_where_clauses();
         let (lower, upper, sort) = if start > end {
             (end, start, Sort::Descending)
         } else {
             (start, end, Sort::Ascending)
         };
+        self.add_sorted_id_where_clause(lower, upper, sort)
+    }
+
+    /// Adds a where clause for the ids between `lower` and `upper` that returns the objects in
+    /// the given id order. Descending where clauses return the newest objects first without
+    /// buffering and sorting the results.
+    pub fn add_sorted_id_where_clause(&mut self, lower: i64, upper: i64, sort: Sort) -> Result<()> {
+        self.init_where_clauses();
         let wc = IdWhereClause::new(self.collection.db, lower, upper, sort);
         if !wc.is_empty() {
             self.where_clauses
@@ -73,14 +87,34 @@ impl<'a> QueryBuilder<'a> {
 
         self.init_where_clauses();
 
+        let is_range = lower != upper;
+        let mut full_keys = if lower.is_truncated() || upper.is_truncated() {
+            Some((lower.to_untruncated(), upper.to_untruncated()))
+        } else {
+            None
+        };
+        if let Some((full_lower, full_upper)) = &mut full_keys {
+            if (!include_lower && !full_lower.increase())
+                || (!include_upper && !full_upper.decrease())
+            {
+                return Ok(());
+            }
+        }
         if (!include_lower && !lower.increase()) || (!include_upper && !upper.decrease()) {
             return Ok(());
         }
+        if full_keys.is_some() && is_range {
+            // truncated strings are ordered by their hash so ranges have to include all strings
+            // sharing the prefix and are filtered using the full strings
+            lower.clamp_truncated(false);
+            upper.clamp_truncated(true);
+        }
         let wc = IndexWhereClause::new(
             self.collection.db,
             index.clone(),
             lower,
             upper,
+            full_keys,
             skip_duplicates,
             sort,
         )?;
@@ -92,26 +126,86 @@ impl<'a> QueryBuilder<'a> {
         Ok(())
     }
 
-    pub fn add_link_where_clause(&mut self, link_index: usize, id: i64) -> Result<()> {
-        self.add_link_where_clause_internal(self.collection, link_index, id)
+    /// Adds a where clause for the objects whose leading index properties are equal to `prefix`
+    /// and whose following index property is between `start` and `end`. `start` and `end` only
+    /// contain the component of that property and are encoded like any other [IndexKey]. If
+    /// `start` is greater than `end`, the objects are returned in descending order.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_index_prefix_where_clause(
+        &mut self,
+        index_index: usize,
+        prefix: &IndexKey,
+        start: IndexKey,
+        include_start: bool,
+        end: IndexKey,
+        include_end: bool,
+        skip_duplicates: bool,
+    ) -> Result<()> {
+        let mut lower = prefix.clone();
+        lower.append(&start);
+        let mut upper = prefix.clone();
+        upper.append(&end);
+        self.add_index_where_clause(
+            index_index,
+            lower,
+            include_start,
+            upper,
+            include_end,
+            skip_duplicates,
+        )
     }
 
-    pub fn add_backlink_where_clause(
+    /// Adds a where clause matching all objects whose indexed string starts with `prefix`. The
+    /// first property of the index has to be a non-hashed, ascending string and the prefix must
+    /// not be longer than the prefix length of the index.
+    pub fn add_string_prefix_where_clause(
         &mut self,
-        col: &IsarCollection,
-        link_index: usize,
-        id: i64,
+        index_index: usize,
+        prefix: &str,
+        skip_duplicates: bool,
     ) -> Result<()> {
-        self.add_link_where_clause_internal(col, link_index, id)
+        let index = self.collection.get_index_by_index(index_index)?;
+        let property = index.properties.first().unwrap();
+        if property.property.data_type != DataType::String
+            || property.index_type != IndexType::Value
+            || property.descending
+        {
+            return illegal_arg("Index does not support prefix where clauses.");
+        }
+        let prefix = property.collation.apply(prefix);
+        let prefix_len = if property.case_sensitive {
+            prefix.len()
+        } else {
+            prefix.to_lowercase().len()
+        };
+        if prefix_len > property.prefix_len {
+            return illegal_arg("The prefix is longer than the prefix length of the index.");
+        }
+
+        let mut lower = IndexKey::new();
+        lower.add_string_prefix(&prefix, property.case_sensitive);
+        // 0xFF never occurs in valid UTF-8 so it is greater than any continuation of the prefix
+        let mut upper = lower.clone();
+        upper.add_byte(0xFF);
+        self.add_index_where_clause(index_index, lower, true, upper, true, skip_duplicates)
+    }
+
+    pub fn add_link_where_clause(&mut self, link_index: usize, id: i64) -> Result<()> {
+        let link = self.collection.get_link(link_index)?;
+        self.add_link_where_clause_internal(link, id)
     }
 
-    fn add_link_where_clause_internal(
+    pub fn add_backlink_where_clause(
         &mut self,
         col: &IsarCollection,
         link_index: usize,
         id: i64,
     ) -> Result<()> {
         let link = col.get_link(link_index)?;
+        self.add_link_where_clause_internal(link.to_backlink(), id)
+    }
+
+    fn add_link_where_clause_internal(&mut self, link: IsarLink, id: i64) -> Result<()> {
         self.init_where_clauses();
         let wc = LinkWhereClause::new(link, id)?;
         self.where_clauses
@@ -121,6 +215,38 @@ impl<'a> QueryBuilder<'a> {
         Ok(())
     }
 
+    /// Uses a hash elements index to find the objects if the filter only matches string lists
+    /// containing a single value. The filter is still applied to rule out hash collisions.
+    fn add_list_contains_where_clause(&mut self) -> bool {
+        let list_contains = self.filter.as_ref().and_then(|f| f.get_list_contains());
+        let (property, value, case_sensitive) = match list_contains {
+            Some(list_contains) => list_contains,
+            None => return false,
+        };
+        let value = match value.map(std::str::from_utf8) {
+            Some(Ok(value)) => Some(value),
+            Some(Err(_)) => return false,
+            None => None,
+        };
+        let hash = IsarObject::hash_string(value, case_sensitive, 0);
+        let index_index = self.collection.indexes.iter().position(|(_, index)| {
+            let first = index.properties.first().unwrap();
+            !index.is_removed()
+                && index.properties.len() == 1
+                && first.property == property
+                && first.index_type == IndexType::HashElements
+                && first.case_sensitive == case_sensitive
+        });
+        if let Some(index_index) = index_index {
+            let mut key = IndexKey::new();
+            key.add_hash(hash);
+            self.add_index_where_clause(index_index, key.clone(), true, key, true, false)
+                .is_ok()
+        } else {
+            false
+        }
+    }
+
     pub fn set_filter(&mut self, filter: Filter) {
         self.filter = Some(filter);
     }
@@ -146,8 +272,20 @@ impl<'a> QueryBuilder<'a> {
         self.limit = limit;
     }
 
+    /// Makes the `find_all` methods of the query fail with
+    /// [ResultTooLarge](crate::error::IsarError::ResultTooLarge) instead of returning more than
+    /// `max_results` objects. Unlike the limit, this never silently truncates the results.
+    /// Counting, deleting, exporting and aggregating are not affected.
+    pub fn set_max_results(&mut self, max_results: usize) {
+        self.max_results = max_results;
+    }
+
+    pub(crate) fn into_where_clauses(self) -> Vec<WhereClause> {
+        self.where_clauses.unwrap_or_default()
+    }
+
     pub fn build(mut self) -> Query {
-        if self.where_clauses.is_none() {
+        if self.where_clauses.is_none() && !self.add_list_contains_where_clause() {
             self.add_id_where_clause(i64::MIN, i64::MAX).unwrap();
         }
         Query::new(
@@ -158,6 +296,7 @@ impl<'a> QueryBuilder<'a> {
             self.distinct,
             self.offset,
             self.limit,
+            self.max_results,
         )
     }
 }
@@ -0,0 +1,72 @@
+use crate::schema::index_schema::IndexType;
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WhereClausePlan {
+    /// Iterates all objects of the collection in id order.
+    #[serde(rename_all = "camelCase")]
+    PrimaryScan { ascending: bool },
+    #[serde(rename_all = "camelCase")]
+    Id {
+        lower: i64,
+        upper: i64,
+        ascending: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Index {
+        properties: Vec<IndexPropertyPlan>,
+        unique: bool,
+        lower_key: Vec<u8>,
+        upper_key: Vec<u8>,
+        skip_duplicates: bool,
+        ascending: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Link { id: i64 },
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexPropertyPlan {
+    pub offset: usize,
+    pub index_type: IndexType,
+    pub case_sensitive: bool,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SortPlan {
+    pub offset: usize,
+    pub ascending: bool,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DistinctPlan {
+    pub offset: usize,
+    pub case_sensitive: bool,
+}
+
+/// Describes how a query will be executed. Properties are identified by their offset.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPlan {
+    pub where_clauses: Vec<WhereClausePlan>,
+    /// Whether results of the where clauses have to be deduplicated by id.
+    pub dedup_ids: bool,
+    /// Whether a filter is evaluated for every object returned by the where clauses.
+    pub post_filter: bool,
+    /// Whether all results are collected and sorted in memory.
+    pub sort_buffer: bool,
+    pub sort: Vec<SortPlan>,
+    pub distinct: Vec<DistinctPlan>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl QueryPlan {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
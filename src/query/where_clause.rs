@@ -5,6 +5,7 @@ use crate::object::isar_object::IsarObject;
 use crate::query::id_where_clause::IdWhereClause;
 use crate::query::index_where_clause::IndexWhereClause;
 use crate::query::link_where_clause::LinkWhereClause;
+use crate::query::query_plan::WhereClausePlan;
 use intmap::IntMap;
 
 #[derive(Clone)]
@@ -39,10 +40,19 @@ impl WhereClause {
         }
     }
 
+    pub(crate) fn explain(&self) -> WhereClausePlan {
+        match self {
+            WhereClause::Id(wc) => wc.explain(),
+            WhereClause::Index(wc) => wc.explain(),
+            WhereClause::Link(wc) => wc.explain(),
+        }
+    }
+
     pub(crate) fn is_overlapping(&self, other: &Self) -> bool {
         match (self, other) {
             (WhereClause::Id(wc1), WhereClause::Id(wc2)) => wc1.is_overlapping(wc2),
             (WhereClause::Index(wc1), WhereClause::Index(wc2)) => wc1.is_overlapping(wc2),
+            // clauses of different kinds may always return the same objects
             _ => true,
         }
     }
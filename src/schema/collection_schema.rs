@@ -1,11 +1,13 @@
-use crate::error::{schema_error, IsarError, Result};
+use crate::error::{schema_error, Result};
+use crate::index::IsarIndex;
 use crate::object::data_type::DataType;
 use crate::object::isar_object::Property;
-use crate::schema::index_schema::{IndexSchema, IndexType};
+use crate::schema::index_schema::{Collation, IndexSchema, IndexType};
 use crate::schema::link_schema::LinkSchema;
 use crate::schema::property_schema::PropertySchema;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::xxh3_64;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash)]
 pub struct CollectionSchema {
@@ -18,6 +20,26 @@ pub struct CollectionSchema {
     pub(crate) links: Vec<LinkSchema>,
 }
 
+/// A property of an opened collection and its position in the serialized objects.
+#[derive(Serialize, Clone, Eq, PartialEq, Debug)]
+pub struct PropertyInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: DataType,
+    pub offset: usize,
+    pub scale: u8,
+}
+
+/// The properties, indexes and links of an opened collection. Hidden properties are not
+/// included.
+#[derive(Serialize, Clone, Eq, PartialEq, Debug)]
+pub struct CollectionSchemaInfo {
+    pub name: String,
+    pub properties: Vec<PropertyInfo>,
+    pub indexes: Vec<IndexSchema>,
+    pub links: Vec<LinkSchema>,
+}
+
 impl PartialEq for CollectionSchema {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
@@ -55,6 +77,13 @@ impl CollectionSchema {
 
         for property in &self.properties {
             Self::verify_name(&property.name)?;
+            if property.data_type == DataType::Decimal {
+                if property.scale > 18 {
+                    schema_error("Decimal properties support a scale of at most 18.")?;
+                }
+            } else if property.scale != 0 {
+                schema_error("Only decimal properties may have a scale.")?;
+            }
         }
 
         for link in &self.links {
@@ -137,6 +166,29 @@ impl CollectionSchema {
                 {
                     schema_error("Only String and StringList indexes may be case sensitive.")?;
                 }
+                if index_property.descending && index_property.index_type != IndexType::Value {
+                    schema_error("Hashed index properties cannot be descending.")?;
+                }
+                if let Some(prefix_len) = index_property.prefix_len {
+                    if (property.data_type != DataType::String
+                        && property.data_type != DataType::StringList)
+                        || index_property.index_type != IndexType::Value
+                    {
+                        schema_error("Only non-hashed string indexes may have a prefix length.")?;
+                    } else if prefix_len == 0 || prefix_len > IsarIndex::MAX_STRING_INDEX_SIZE {
+                        schema_error(&format!(
+                            "The prefix length of string indexes must be between 1 and {}.",
+                            IsarIndex::MAX_STRING_INDEX_SIZE
+                        ))?;
+                    }
+                }
+                if index_property.collation != Collation::Raw
+                    && ((property.data_type != DataType::String
+                        && property.data_type != DataType::StringList)
+                        || index_property.index_type != IndexType::Value)
+                {
+                    schema_error("Only non-hashed string indexes may have a collation.")?;
+                }
             }
         }
 
@@ -147,25 +199,53 @@ impl CollectionSchema {
         Ok(())
     }
 
-    pub(crate) fn merge_properties(&mut self, existing: &Self) -> Result<()> {
-        let mut properties = existing.properties.clone();
+    /// Stable hash of the name, properties, indexes and links as declared. Unlike
+    /// [Schema::get_hash](crate::schema::Schema::get_hash) it does not depend on the Rust version
+    /// and can be persisted.
+    pub(crate) fn get_hash(&self) -> u64 {
+        let bytes = serde_json::to_vec(self).unwrap();
+        xxh3_64(&bytes)
+    }
+
+    /// Checks that the data stored with the `existing` schema can be read using this schema and
+    /// reports the first property whose type changed.
+    pub(crate) fn verify_compatible(&self, existing: &Self) -> Result<()> {
         for property in &self.properties {
             let existing_property = existing.properties.iter().find(|p| p.name == property.name);
             if let Some(existing_property) = existing_property {
-                if property.data_type != existing_property.data_type {
-                    return Err(IsarError::SchemaError {
-                        message: format!(
-                            "Property \"{}\" already exists but has a different type",
-                            property.name
-                        ),
-                    });
+                // bools used to be stored as bytes and have the same layout
+                let bool_upgrade = existing_property.data_type == DataType::Byte
+                    && property.data_type == DataType::Bool;
+                if !bool_upgrade
+                    && (property.data_type != existing_property.data_type
+                        || property.scale != existing_property.scale)
+                {
+                    return schema_error(&format!(
+                        "Property \"{}\" of collection \"{}\" is stored as {} but is now {}.",
+                        property.name,
+                        self.name,
+                        existing_property.describe_type(),
+                        property.describe_type()
+                    ));
                 }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn merge_properties(&mut self, existing: &Self) -> Result<()> {
+        self.verify_compatible(existing)?;
+        let mut properties = existing.properties.clone();
+        for property in &self.properties {
+            let existing_property = properties.iter_mut().find(|p| p.name == property.name);
+            if let Some(existing_property) = existing_property {
+                existing_property.data_type = property.data_type;
             } else {
                 properties.push(property.clone());
             }
         }
         for property in &existing.properties {
-            if !self.properties.contains(property) {
+            if !self.properties.iter().any(|p| p.name == property.name) {
                 self.hidden_properties.push(property.name.clone())
             }
         }
@@ -174,12 +254,53 @@ impl CollectionSchema {
         Ok(())
     }
 
+    pub(crate) fn rename_property(&mut self, old_name: &str, new_name: &str) -> Result<()> {
+        Self::verify_name(new_name)?;
+        if self.properties.iter().any(|p| p.name == new_name) {
+            return schema_error("A property with the new name already exists.");
+        }
+        let property = self.properties.iter_mut().find(|p| p.name == old_name);
+        if let Some(property) = property {
+            property.name = new_name.to_string();
+        } else {
+            return schema_error("Property does not exist.");
+        }
+        for index in &mut self.indexes {
+            for index_property in &mut index.properties {
+                if index_property.name == old_name {
+                    index_property.name = new_name.to_string();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn as_info(&self) -> CollectionSchemaInfo {
+        let properties = self
+            .get_properties()
+            .into_iter()
+            .map(|(name, property)| PropertyInfo {
+                name,
+                data_type: property.data_type,
+                offset: property.offset,
+                scale: property.scale,
+            })
+            .collect();
+        CollectionSchemaInfo {
+            name: self.name.clone(),
+            properties,
+            indexes: self.indexes.clone(),
+            links: self.links.clone(),
+        }
+    }
+
     pub(crate) fn get_properties(&self) -> Vec<(String, Property)> {
         let mut properties = vec![];
         let mut offset = 2;
         for property_schema in &self.properties {
             if !self.hidden_properties.contains(&property_schema.name) {
-                let property = Property::new(property_schema.data_type, offset);
+                let mut property = Property::new(property_schema.data_type, offset);
+                property.scale = property_schema.scale;
                 properties.push((property_schema.name.clone(), property));
             }
             offset += property_schema.data_type.get_static_size();
@@ -3,6 +3,9 @@ use crate::mdbx::db::Db;
 use crate::object::isar_object::Property;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum IndexType {
@@ -11,6 +14,33 @@ pub enum IndexType {
     HashElements,
 }
 
+/// Determines the order of strings in non-hashed string indexes.
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Collation {
+    /// Strings are ordered by their UTF-8 bytes.
+    Raw,
+    /// Strings are decomposed and stripped of combining marks before they are added to the index
+    /// so accented characters sort with their base character, e.g. "é" with "e".
+    Folded,
+}
+
+impl Default for Collation {
+    fn default() -> Self {
+        Collation::Raw
+    }
+}
+
+impl Collation {
+    pub(crate) fn apply<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        match self {
+            Collation::Raw => Cow::Borrowed(value),
+            Collation::Folded => {
+                Cow::Owned(value.nfd().filter(|c| !is_combining_mark(*c)).collect())
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct IndexPropertySchema {
     pub(crate) name: String,
@@ -18,6 +48,20 @@ pub struct IndexPropertySchema {
     pub(crate) index_type: IndexType,
     #[serde(rename = "caseSensitive")]
     pub(crate) case_sensitive: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub(crate) descending: bool,
+    #[serde(rename = "prefixLen", default, skip_serializing_if = "Option::is_none")]
+    pub(crate) prefix_len: Option<usize>,
+    #[serde(default, skip_serializing_if = "is_raw")]
+    pub(crate) collation: Collation,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_raw(collation: &Collation) -> bool {
+    *collation == Collation::Raw
 }
 
 impl IndexPropertySchema {
@@ -26,8 +70,33 @@ impl IndexPropertySchema {
             name: name.to_string(),
             index_type,
             case_sensitive,
+            descending: false,
+            prefix_len: None,
+            collation: Collation::Raw,
         }
     }
+
+    /// Stores the index entries of this property in descending order so a cursor moving forward
+    /// yields the highest values first.
+    pub fn descending(mut self) -> IndexPropertySchema {
+        self.descending = true;
+        self
+    }
+
+    /// Only indexes the first `prefix_len` bytes of strings instead of the first 1024 bytes.
+    /// Longer strings are distinguished by a hash of the full string so they are only sorted by
+    /// their prefix.
+    pub fn prefix_len(mut self, prefix_len: usize) -> IndexPropertySchema {
+        self.prefix_len = Some(prefix_len);
+        self
+    }
+
+    /// Orders the strings of this property using `collation` instead of their UTF-8 bytes. Where
+    /// clauses apply the same collation to their keys.
+    pub fn collation(mut self, collation: Collation) -> IndexPropertySchema {
+        self.collation = collation;
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
@@ -52,7 +121,14 @@ impl IndexSchema {
             .iter()
             .map(|p| {
                 let (_, property) = properties.iter().find(|(n, _)| &p.name == n).unwrap();
-                IndexProperty::new(*property, p.index_type, p.case_sensitive)
+                let mut index_property =
+                    IndexProperty::new(*property, p.index_type, p.case_sensitive);
+                index_property.descending = p.descending;
+                if let Some(prefix_len) = p.prefix_len {
+                    index_property.prefix_len = prefix_len;
+                }
+                index_property.collation = p.collation;
+                index_property
             })
             .collect_vec();
         IsarIndex::new(db, index_properties, self.unique)
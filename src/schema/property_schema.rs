@@ -6,6 +6,12 @@ pub struct PropertySchema {
     pub(crate) name: String,
     #[serde(rename = "type")]
     pub(crate) data_type: DataType,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub(crate) scale: u8,
+}
+
+fn is_zero(scale: &u8) -> bool {
+    *scale == 0
 }
 
 impl PropertySchema {
@@ -13,6 +19,24 @@ impl PropertySchema {
         PropertySchema {
             name: name.to_string(),
             data_type,
+            scale: 0,
+        }
+    }
+
+    /// Creates a [DataType::Decimal] property with `scale` decimal places.
+    pub fn decimal(name: &str, scale: u8) -> PropertySchema {
+        PropertySchema {
+            name: name.to_string(),
+            data_type: DataType::Decimal,
+            scale,
+        }
+    }
+
+    pub(crate) fn describe_type(&self) -> String {
+        if self.data_type == DataType::Decimal {
+            format!("Decimal with scale {}", self.scale)
+        } else {
+            format!("{:?}", self.data_type)
         }
     }
 }
@@ -16,12 +16,14 @@ use std::convert::TryInto;
 const ISAR_VERSION: u64 = 1;
 const INFO_VERSION_KEY: &[u8] = b"version";
 const INFO_SCHEMA_KEY: &[u8] = b"schema";
+const INFO_SCHEMA_HASH_PREFIX: &str = "schema_hash_";
 
 pub(crate) struct SchemaManger<'a> {
     instance_id: u64,
     txn: &'a Txn<'a>,
     info_cursor: Cursor<'a>,
     new_indexes: HashMap<String, Vec<usize>>,
+    previous_hashes: HashMap<String, Option<u64>>,
 }
 
 impl<'a> SchemaManger<'a> {
@@ -33,6 +35,7 @@ impl<'a> SchemaManger<'a> {
             txn,
             info_cursor: info_cursor.bind(txn, info_db)?,
             new_indexes: HashMap::new(),
+            previous_hashes: HashMap::new(),
         };
         manager.check_isar_version()?;
         Ok(manager)
@@ -52,7 +55,7 @@ impl<'a> SchemaManger<'a> {
         Ok(())
     }
 
-    fn get_existing_schema(&mut self) -> Result<Schema> {
+    pub fn get_existing_schema(&mut self) -> Result<Schema> {
         let existing_schema_bytes = self.info_cursor.move_to(INFO_SCHEMA_KEY)?;
 
         if let Some((_, existing_schema_bytes)) = existing_schema_bytes {
@@ -64,6 +67,25 @@ impl<'a> SchemaManger<'a> {
         }
     }
 
+    fn get_schema_hash(&mut self, col_name: &str) -> Result<Option<u64>> {
+        let key = format!("{}{}", INFO_SCHEMA_HASH_PREFIX, col_name);
+        let hash = self.info_cursor.move_to(key.as_bytes())?;
+        Ok(hash.map(|(_, hash)| u64::from_le_bytes(hash.try_into().unwrap())))
+    }
+
+    fn save_schema_hash(&mut self, col_name: &str, hash: u64) -> Result<()> {
+        let key = format!("{}{}", INFO_SCHEMA_HASH_PREFIX, col_name);
+        self.info_cursor.put(key.as_bytes(), &hash.to_le_bytes())
+    }
+
+    fn delete_schema_hash(&mut self, col_name: &str) -> Result<()> {
+        let key = format!("{}{}", INFO_SCHEMA_HASH_PREFIX, col_name);
+        if self.info_cursor.move_to(key.as_bytes())?.is_some() {
+            self.info_cursor.delete_current()?;
+        }
+        Ok(())
+    }
+
     fn open_collection_db(&mut self, col: &CollectionSchema) -> Result<Db> {
         Db::open(self.txn, Some(&col.name), true, false, false)
     }
@@ -90,7 +112,7 @@ impl<'a> SchemaManger<'a> {
         for link in &col.links {
             self.delete_link(col, link)?;
         }
-        Ok(())
+        self.delete_schema_hash(&col.name)
     }
 
     fn delete_index(&mut self, col: &CollectionSchema, index: &IndexSchema) -> Result<()> {
@@ -107,12 +129,26 @@ impl<'a> SchemaManger<'a> {
     pub fn perform_migration(&mut self, schema: &mut Schema) -> Result<()> {
         let existing_schema = self.get_existing_schema()?;
 
+        // fail before anything is deleted if the stored data cannot be read with the new schema
+        for col in &schema.collections {
+            if let Some(existing_col) = existing_schema.get_collection(&col.name) {
+                col.verify_compatible(existing_col)?;
+            }
+        }
+
         let deleted_cols = get_added(&schema.collections, &existing_schema.collections);
         for col in deleted_cols {
             self.delete_collection(col)?;
         }
 
         for col in schema.collections.iter_mut() {
+            let hash = col.get_hash();
+            let previous_hash = self.get_schema_hash(&col.name)?;
+            if previous_hash != Some(hash) {
+                self.save_schema_hash(&col.name, hash)?;
+            }
+            self.previous_hashes.insert(col.name.clone(), previous_hash);
+
             let existing_col = existing_schema.get_collection(&col.name);
             if let Some(existing_col) = existing_col {
                 col.merge_properties(existing_col)?;
@@ -142,6 +178,15 @@ impl<'a> SchemaManger<'a> {
         Ok(())
     }
 
+    pub fn update_schema<F>(&mut self, job: F) -> Result<()>
+    where
+        F: FnOnce(&mut Schema) -> Result<()>,
+    {
+        let mut schema = self.get_existing_schema()?;
+        job(&mut schema)?;
+        self.save_schema(&schema)
+    }
+
     fn save_schema(&mut self, schema: &Schema) -> Result<()> {
         let bytes = serde_json::to_vec(schema).map_err(|_| IsarError::SchemaError {
             message: "Could not serialize schema.".to_string(),
@@ -151,7 +196,7 @@ impl<'a> SchemaManger<'a> {
     }
 
     pub fn open_collections(&mut self, schema: &Schema) -> Result<Vec<IsarCollection>> {
-        let cursors = IsarCursors::new(self.txn, vec![]);
+        let cursors = IsarCursors::new(self.txn, vec![], &[]);
         let mut cols = vec![];
         for col_schema in &schema.collections {
             let col = self.open_collection(schema, col_schema)?;
@@ -170,6 +215,13 @@ impl<'a> SchemaManger<'a> {
         col_schema: &CollectionSchema,
     ) -> Result<IsarCollection> {
         let db = self.open_collection_db(col_schema)?;
+        let schema_hash = self
+            .get_schema_hash(&col_schema.name)?
+            .unwrap_or_else(|| col_schema.get_hash());
+        let previous_schema_hash = match self.previous_hashes.get(&col_schema.name) {
+            Some(previous_hash) => *previous_hash,
+            None => Some(schema_hash),
+        };
         let mut properties = col_schema.get_properties();
         properties.sort_by(|(a, _), (b, _)| a.cmp(b));
 
@@ -195,6 +247,9 @@ impl<'a> SchemaManger<'a> {
             db,
             self.instance_id,
             col_schema.name.clone(),
+            schema_hash,
+            previous_schema_hash,
+            col_schema.as_info(),
             properties,
             indexes,
             links,
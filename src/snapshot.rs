@@ -0,0 +1,41 @@
+use crate::instance::IsarInstance;
+use crate::txn::IsarTxn;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// A read transaction that owns a reference to its instance. See
+/// [`IsarInstance::begin_snapshot`].
+pub struct IsarSnapshot {
+    // the transaction has to be dropped before the instance that owns the environment
+    txn: IsarTxn<'static>,
+    instance: Arc<IsarInstance>,
+}
+
+impl IsarSnapshot {
+    pub(crate) fn new(txn: IsarTxn<'static>, instance: Arc<IsarInstance>) -> Self {
+        IsarSnapshot { txn, instance }
+    }
+
+    /// Ends the snapshot and releases its reader slot.
+    pub fn close(self) {}
+}
+
+impl Deref for IsarSnapshot {
+    type Target = IsarTxn<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.txn
+    }
+}
+
+impl DerefMut for IsarSnapshot {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.txn
+    }
+}
+
+impl Drop for IsarSnapshot {
+    fn drop(&mut self) {
+        self.instance.release_snapshot();
+    }
+}
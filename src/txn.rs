@@ -1,17 +1,37 @@
 use crate::cursor::IsarCursors;
-use crate::error::{IsarError, Result};
+use crate::error::{illegal_arg, IsarError, Result};
 use crate::mdbx::cursor::UnboundCursor;
 use crate::mdbx::db::Db;
+use crate::mdbx::env::Durability;
 use crate::mdbx::txn::Txn;
+use crate::metrics::TxnTracker;
+use crate::schema::schema_manager::SchemaManger;
+use crate::schema::Schema;
 use crate::watch::change_set::ChangeSet;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+/// A transaction of an [IsarInstance](crate::instance::IsarInstance). It is not bound to a
+/// collection: all collections of the instance can be used with the same transaction and their
+/// changes are committed or aborted atomically.
 pub struct IsarTxn<'env> {
     instance_id: u64,
+    // nested transactions have to be dropped before their parent, the number of removed
+    // indexes is restored when a savepoint is rolled back
+    savepoints: Vec<(Txn<'env>, usize)>,
+    savepoint_failed: Cell<bool>,
     txn: Txn<'env>,
     write: bool,
+    // the durability the transaction was started with and the one used for the commit
+    begin_durability: Durability,
+    durability: Durability,
     change_set: RefCell<Option<ChangeSet<'env>>>,
     unbound_cursors: RefCell<Option<Vec<UnboundCursor>>>,
+    // flags of the indexes removed by this transaction which are set once it is committed
+    removed_indexes: Vec<Arc<AtomicBool>>,
+    tracker: TxnTracker<'env>,
 }
 
 impl<'env> IsarTxn<'env> {
@@ -19,14 +39,22 @@ impl<'env> IsarTxn<'env> {
         instance_id: u64,
         txn: Txn<'env>,
         write: bool,
+        durability: Durability,
         change_set: Option<ChangeSet<'env>>,
+        tracker: TxnTracker<'env>,
     ) -> Result<Self> {
         Ok(IsarTxn {
             instance_id,
+            savepoints: vec![],
+            savepoint_failed: Cell::new(false),
             txn,
             write,
+            begin_durability: durability,
+            durability,
             change_set: RefCell::new(change_set),
             unbound_cursors: RefCell::new(Some(vec![])),
+            removed_indexes: vec![],
+            tracker,
         })
     }
 
@@ -34,6 +62,84 @@ impl<'env> IsarTxn<'env> {
         self.unbound_cursors.borrow().is_some()
     }
 
+    /// Overrides the durability of this transaction. The sync mode is fixed when a transaction
+    /// begins so a transaction may only be more durable than its instance. A stronger durability
+    /// is achieved by flushing the database after the commit, which allows a bulk import to run
+    /// with [Durability::NoSync] and flush everything with its final transaction.
+    pub fn set_durability(&mut self, durability: Durability) -> Result<()> {
+        if !self.write {
+            return Err(IsarError::WriteTxnRequired {});
+        }
+        if durability.is_weaker_than(self.begin_durability) {
+            return illegal_arg("A transaction cannot be less durable than its instance.");
+        }
+        self.durability = durability;
+        Ok(())
+    }
+
+    fn current_txn(&self) -> Result<&Txn<'env>> {
+        if self.savepoint_failed.get() {
+            Err(IsarError::TransactionClosed {})
+        } else {
+            Ok(self
+                .savepoints
+                .last()
+                .map(|(txn, _)| txn)
+                .unwrap_or(&self.txn))
+        }
+    }
+
+    /// Starts a savepoint backed by a nested transaction. Changes made through the returned guard
+    /// are discarded unless it is committed. If an operation fails inside a savepoint, only the
+    /// savepoint has to be aborted and the outer transaction stays usable.
+    ///
+    /// Watchers may be notified about changes of aborted savepoints.
+    pub fn savepoint(&mut self) -> Result<IsarSavepoint<'_, 'env>> {
+        if !self.write {
+            return Err(IsarError::WriteTxnRequired {});
+        }
+        if !self.is_active() {
+            return Err(IsarError::TransactionClosed {});
+        }
+        let txn = self.current_txn()?.nested()?;
+        self.savepoints.push((txn, self.removed_indexes.len()));
+        Ok(IsarSavepoint {
+            txn: self,
+            finished: false,
+        })
+    }
+
+    /// Runs `job` in a savepoint that is always rolled back. `job` can write and query its own
+    /// changes but none of them are visible once it returns, even if it succeeded. Watchers are
+    /// not notified about the discarded changes. Returns the result of `job`.
+    pub fn with_preview<T, F>(&mut self, job: F) -> Result<T>
+    where
+        F: FnOnce(&mut IsarTxn<'env>) -> Result<T>,
+    {
+        let mut savepoint = self.savepoint()?;
+        let change_set = savepoint.change_set.take();
+        let result = job(&mut savepoint);
+        *savepoint.change_set.borrow_mut() = change_set;
+        savepoint.abort();
+        result
+    }
+
+    fn release_savepoint(&mut self) -> Result<()> {
+        let (txn, _) = self.savepoints.pop().unwrap();
+        if self.savepoint_failed.replace(false) {
+            Err(IsarError::TransactionClosed {})
+        } else {
+            txn.commit()
+        }
+    }
+
+    fn rollback_savepoint(&mut self) {
+        if let Some((_, removed_indexes)) = self.savepoints.pop() {
+            self.removed_indexes.truncate(removed_indexes);
+        }
+        self.savepoint_failed.set(false);
+    }
+
     fn verify_instance_id(&self, instance_id: u64) -> Result<()> {
         if self.instance_id != instance_id {
             Err(IsarError::InstanceMismatch {})
@@ -47,8 +153,9 @@ impl<'env> IsarTxn<'env> {
         F: FnOnce(&IsarCursors<'txn, 'env>) -> Result<T>,
     {
         self.verify_instance_id(instance_id)?;
+        let txn = self.current_txn()?;
         if let Some(unbound_cursors) = self.unbound_cursors.take() {
-            let cursors = IsarCursors::new(&self.txn, unbound_cursors);
+            let cursors = IsarCursors::new(txn, unbound_cursors, &self.removed_indexes);
             let result = job(&cursors);
             self.unbound_cursors.borrow_mut().replace(cursors.close());
             result
@@ -65,12 +172,17 @@ impl<'env> IsarTxn<'env> {
         if !self.write {
             return Err(IsarError::WriteTxnRequired {});
         }
+        let txn = self.current_txn()?;
         if let Some(unbound_cursors) = self.unbound_cursors.take() {
             let mut change_set = self.change_set.take();
-            let cursors = IsarCursors::new(&self.txn, unbound_cursors);
+            let cursors = IsarCursors::new(txn, unbound_cursors, &self.removed_indexes);
             let result = job(&cursors, change_set.as_mut());
             let unbounded_cursors = cursors.close();
-            if result.is_ok() {
+            if result.is_err() && !self.savepoints.is_empty() {
+                // only the savepoint is unusable, the outer transaction can continue
+                self.savepoint_failed.set(true);
+            }
+            if result.is_ok() || self.savepoint_failed.get() {
                 self.unbound_cursors.borrow_mut().replace(unbounded_cursors);
                 if let Some(change_set) = change_set {
                     self.change_set.borrow_mut().replace(change_set);
@@ -82,11 +194,19 @@ impl<'env> IsarTxn<'env> {
         }
     }
 
+    pub(crate) fn count_db(&mut self, instance_id: u64, db: Db) -> Result<u64> {
+        self.verify_instance_id(instance_id)?;
+        if !self.is_active() {
+            return Err(IsarError::TransactionClosed {});
+        }
+        db.count(self.current_txn()?)
+    }
+
     pub(crate) fn clear_db(&mut self, db: Db) -> Result<()> {
         if !self.write {
             return Err(IsarError::WriteTxnRequired {});
         }
-        db.clear(&self.txn)
+        db.clear(self.current_txn()?)
     }
 
     pub(crate) fn register_all_changed(&mut self, col_id: u64) -> Result<()> {
@@ -99,13 +219,47 @@ impl<'env> IsarTxn<'env> {
         Ok(())
     }
 
-    pub fn commit(self) -> Result<()> {
+    pub(crate) fn remove_index(&mut self, removed: Arc<AtomicBool>) -> Result<()> {
+        if !self.write {
+            return Err(IsarError::WriteTxnRequired {});
+        }
+        self.removed_indexes.push(removed);
+        Ok(())
+    }
+
+    pub(crate) fn update_schema<F>(&mut self, instance_id: u64, job: F) -> Result<()>
+    where
+        F: FnOnce(&mut Schema) -> Result<()>,
+    {
+        self.verify_instance_id(instance_id)?;
+        if !self.write {
+            return Err(IsarError::WriteTxnRequired {});
+        }
+        if !self.is_active() {
+            return Err(IsarError::TransactionClosed {});
+        }
+        let mut manager = SchemaManger::create(instance_id, self.current_txn()?)?;
+        manager.update_schema(job)
+    }
+
+    pub fn commit(mut self) -> Result<()> {
         if !self.is_active() {
             return Err(IsarError::TransactionClosed {});
         }
 
+        // savepoints that have been leaked are discarded
+        self.savepoints.clear();
+
         if self.write {
-            self.txn.commit()?;
+            if self.durability != self.begin_durability {
+                self.txn.commit_and_sync()?;
+            } else {
+                self.txn.commit()?;
+            }
+            self.tracker.set_committed();
+            for removed in self.removed_indexes.drain(..) {
+                removed.store(true, Ordering::Release);
+            }
             if let Some(change_set) = self.change_set.take() {
                 change_set.notify_watchers();
             }
@@ -113,14 +267,16 @@ impl<'env> IsarTxn<'env> {
         Ok(())
     }
 
-    pub fn abort(self) {
+    pub fn abort(mut self) {
+        self.savepoints.clear();
         self.txn.abort()
     }
 
     pub(crate) fn debug_db_names(&mut self) -> Result<Vec<String>> {
-        let unnamed_db = Db::open(&self.txn, None, false, false, false)?;
+        let txn = self.current_txn()?;
+        let unnamed_db = Db::open(txn, None, false, false, false)?;
         let cursor = UnboundCursor::new();
-        let mut cursor = cursor.bind(&self.txn, unnamed_db)?;
+        let mut cursor = cursor.bind(txn, unnamed_db)?;
 
         let mut names = vec![];
         cursor.iter_between(&[], &[255], false, false, true, |_, name, _| {
@@ -130,3 +286,43 @@ impl<'env> IsarTxn<'env> {
         Ok(names)
     }
 }
+
+pub struct IsarSavepoint<'a, 'env> {
+    txn: &'a mut IsarTxn<'env>,
+    finished: bool,
+}
+
+impl<'a, 'env> IsarSavepoint<'a, 'env> {
+    /// Merges the changes of the savepoint into the outer transaction.
+    pub fn commit(mut self) -> Result<()> {
+        self.finished = true;
+        self.txn.release_savepoint()
+    }
+
+    pub fn abort(mut self) {
+        self.finished = true;
+        self.txn.rollback_savepoint()
+    }
+}
+
+impl<'a, 'env> Deref for IsarSavepoint<'a, 'env> {
+    type Target = IsarTxn<'env>;
+
+    fn deref(&self) -> &Self::Target {
+        self.txn
+    }
+}
+
+impl<'a, 'env> DerefMut for IsarSavepoint<'a, 'env> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.txn
+    }
+}
+
+impl<'a, 'env> Drop for IsarSavepoint<'a, 'env> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.txn.rollback_savepoint()
+        }
+    }
+}
@@ -82,7 +82,11 @@ pub fn verify_isar(
             assert_eq!(col.debug_dump(cur), entries);
 
             for (i, (_, index)) in col.indexes.iter().enumerate() {
-                assert_eq!(index.debug_dump(cur), index_entries[i]);
+                if index.is_removed_in(cur) {
+                    assert!(index.debug_dump(cur).is_empty());
+                } else {
+                    assert_eq!(index.debug_dump(cur), index_entries[i]);
+                }
             }
 
             for (name, link) in &col.links {
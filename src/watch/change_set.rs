@@ -6,7 +6,7 @@ use std::sync::{Arc, MutexGuard};
 
 pub(crate) struct ChangeSet<'a> {
     watchers: MutexGuard<'a, IsarWatchers>,
-    changed_watchers: IntMap<Arc<Watcher>>,
+    changed_watchers: IntMap<(Arc<Watcher>, bool)>,
 }
 
 impl<'a> ChangeSet<'a> {
@@ -17,24 +17,54 @@ impl<'a> ChangeSet<'a> {
         }
     }
 
-    fn register_watchers(changed_watchers: &mut IntMap<Arc<Watcher>>, watchers: &[Arc<Watcher>]) {
+    fn register_watchers(
+        changed_watchers: &mut IntMap<(Arc<Watcher>, bool)>,
+        watchers: &[Arc<Watcher>],
+    ) {
+        // query watchers share their id with the watchers of linked collections so a registered
+        // id does not imply that the following watchers are registered too
         for w in watchers {
-            let registered = changed_watchers.contains_key(w.get_id());
-            if !registered {
-                changed_watchers.insert(w.get_id(), w.clone());
-            } else {
-                break;
+            if !changed_watchers.contains_key(w.get_id()) {
+                changed_watchers.insert(w.get_id(), (w.clone(), false));
             }
         }
     }
 
+    fn register_object_watchers(
+        changed_watchers: &mut IntMap<(Arc<Watcher>, bool)>,
+        watchers: &[Arc<Watcher>],
+        deleted: bool,
+    ) {
+        for w in watchers {
+            changed_watchers.insert(w.get_id(), (w.clone(), deleted));
+        }
+    }
+
     pub fn register_change(&mut self, col_id: u64, oid: Option<i64>, object: Option<IsarObject>) {
+        self.register_change_internal(col_id, oid, object, false)
+    }
+
+    pub fn register_delete(&mut self, col_id: u64, oid: i64, object: IsarObject) {
+        self.register_change_internal(col_id, Some(oid), Some(object), true)
+    }
+
+    fn register_change_internal(
+        &mut self,
+        col_id: u64,
+        oid: Option<i64>,
+        object: Option<IsarObject>,
+        deleted: bool,
+    ) {
         let cw = self.watchers.get_col_watchers(col_id);
         Self::register_watchers(&mut self.changed_watchers, &cw.watchers);
         if let Some(oid) = oid {
             let oid_u = unsafe { std::mem::transmute(oid) };
             if let Some(object_watchers) = cw.object_watchers.get(oid_u) {
-                Self::register_watchers(&mut self.changed_watchers, object_watchers);
+                Self::register_object_watchers(
+                    &mut self.changed_watchers,
+                    object_watchers,
+                    deleted,
+                );
             }
 
             if let Some(object) = object {
@@ -42,27 +72,39 @@ impl<'a> ChangeSet<'a> {
                     if !self.changed_watchers.contains_key(w.get_id())
                         && q.maybe_matches_wc_filter(oid, object)
                     {
-                        self.changed_watchers.insert(w.get_id(), w.clone());
+                        self.changed_watchers.insert(w.get_id(), (w.clone(), false));
                     }
                 }
             }
         }
     }
 
+    /// Registers a changed link of the collection. Only query watchers whose query uses links
+    /// are notified.
+    pub fn register_link_change(&mut self, col_id: u64) {
+        let cw = self.watchers.get_col_watchers(col_id);
+        Self::register_watchers(&mut self.changed_watchers, &cw.watchers);
+        for (q, w) in &cw.query_watchers {
+            if q.uses_links() {
+                self.changed_watchers.insert(w.get_id(), (w.clone(), false));
+            }
+        }
+    }
+
     pub fn register_all(&mut self, col_id: u64) {
         let cw = self.watchers.get_col_watchers(col_id);
         Self::register_watchers(&mut self.changed_watchers, &cw.watchers);
         for watchers in cw.object_watchers.values() {
-            Self::register_watchers(&mut self.changed_watchers, watchers)
+            Self::register_object_watchers(&mut self.changed_watchers, watchers, true)
         }
         for (_, w) in &cw.query_watchers {
-            self.changed_watchers.insert(w.get_id(), w.clone());
+            self.changed_watchers.insert(w.get_id(), (w.clone(), false));
         }
     }
 
     pub fn notify_watchers(self) {
-        for watcher in self.changed_watchers.values() {
-            watcher.notify();
+        for (watcher, deleted) in self.changed_watchers.values() {
+            watcher.notify(*deleted);
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::query::Query;
-use crate::watch::watcher::{Watcher, WatcherCallback};
+use crate::watch::watcher::{ObjectWatcherCallback, Watcher, WatcherCallback};
 use crossbeam_channel::Receiver;
 use intmap::IntMap;
 use itertools::Itertools;
@@ -65,9 +65,14 @@ impl IsarCollectionWatchers {
         self.watchers.remove(position);
     }
 
-    pub fn add_object_watcher(&mut self, watcher_id: u64, oid: i64, callback: WatcherCallback) {
+    pub fn add_object_watcher(
+        &mut self,
+        watcher_id: u64,
+        oid: i64,
+        callback: ObjectWatcherCallback,
+    ) {
         let oid = unsafe { std::mem::transmute(oid) };
-        let watcher = Arc::new(Watcher::new(watcher_id, callback));
+        let watcher = Arc::new(Watcher::new_object(watcher_id, callback));
         if let Some(object_watchers) = self.object_watchers.get_mut(oid) {
             object_watchers.push(watcher);
         } else {
@@ -1,12 +1,21 @@
 pub type WatcherCallback = Box<dyn Fn() + Send + Sync + 'static>;
 
+pub type ObjectWatcherCallback = Box<dyn Fn(bool) + Send + Sync + 'static>;
+
 pub(super) struct Watcher {
     id: u64,
-    callback: WatcherCallback,
+    callback: ObjectWatcherCallback,
 }
 
 impl Watcher {
     pub fn new(id: u64, callback: WatcherCallback) -> Self {
+        Watcher {
+            id,
+            callback: Box::new(move |_| callback()),
+        }
+    }
+
+    pub fn new_object(id: u64, callback: ObjectWatcherCallback) -> Self {
         Watcher { id, callback }
     }
 
@@ -14,7 +23,7 @@ impl Watcher {
         self.id
     }
 
-    pub fn notify(&self) {
-        (*self.callback)()
+    pub fn notify(&self, deleted: bool) {
+        (*self.callback)(deleted)
     }
 }
@@ -2,7 +2,7 @@
 
 use std::vec;
 
-use isar_core::collection::IsarCollection;
+use isar_core::collection::{IsarCollection, OnConflict};
 use isar_core::object::data_type::DataType;
 use isar_core::object::isar_object::{IsarObject, Property};
 use isar_core::schema::collection_schema::CollectionSchema;
@@ -227,6 +227,9 @@ impl TestObj {
                         .map(|l| l.iter().map(|e| e.as_deref()).collect_vec());
                     builder.write_string_list(string_list.as_deref());
                 }
+                DataType::Bool | DataType::DateTime | DataType::Decimal => {
+                    builder.write_null(prop.data_type)
+                }
             }
         }
         builder.finish().as_bytes().to_vec()
@@ -239,8 +242,13 @@ impl TestObj {
 
     pub fn save(&self, txn: &mut IsarTxn, col: &IsarCollection) {
         let bytes = self.to_bytes(col);
-        col.put(txn, Some(self.id), IsarObject::from_bytes(&bytes), false)
-            .unwrap();
+        col.put(
+            txn,
+            Some(self.id),
+            IsarObject::from_bytes(&bytes),
+            OnConflict::Abort,
+        )
+        .unwrap();
     }
 
     pub fn from_object(col: &IsarCollection, item: IsarObject) -> Self {
@@ -27,7 +27,8 @@ macro_rules! isar (
         let schema = isar_core::schema::Schema::new(vec![]).unwrap();
         let path = $path.to_string();
         let name = xxhash_rust::xxh3::xxh3_64(path.as_bytes()).to_string();
-        let $isar = isar_core::instance::IsarInstance::open(&name, &path, false, schema).unwrap();
+        let durability = isar_core::instance::Durability::SafeSync;
+        let $isar = isar_core::instance::IsarInstance::open(&name, &path, durability, None, schema).unwrap();
     };
 
     ($path:expr, $isar:ident, $($col:ident => $schema:expr),+) => {
@@ -35,7 +36,8 @@ macro_rules! isar (
         let schema = isar_core::schema::Schema::new(col_schemas).unwrap();
         let path = $path.to_string();
         let name = xxhash_rust::xxh3::xxh3_64(path.as_bytes()).to_string();
-        let $isar = isar_core::instance::IsarInstance::open(&name, &path, false, schema).unwrap();
+        let durability = isar_core::instance::Durability::SafeSync;
+        let $isar = isar_core::instance::IsarInstance::open(&name, &path, durability, None, schema).unwrap();
         isar!(col $isar, 0, $($col),+)
     };
 
@@ -1,4 +1,6 @@
 use crossbeam_channel::unbounded;
+use isar_core::index::index_key::IndexKey;
+use isar_core::schema::index_schema::IndexSchema;
 use isar_core::schema::link_schema::LinkSchema;
 
 use crate::common::test_obj::TestObj;
@@ -35,9 +37,9 @@ fn test_delete_clears_links() {
 
     // put new objects
     put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3);
-    col.link(&mut txn, 0, false, 1, 2).unwrap();
-    col.link(&mut txn, 0, false, 2, 3).unwrap();
-    col.link(&mut txn, 0, false, 3, 1).unwrap();
+    col.link(&mut txn, 0, 1, 2).unwrap();
+    col.link(&mut txn, 0, 2, 3).unwrap();
+    col.link(&mut txn, 0, 3, 1).unwrap();
     verify!(txn, col, obj1, obj2, obj3; "link", 1 => 2, 2 => 3, 3 => 1);
 
     // delete obj 1
@@ -52,6 +54,53 @@ fn test_delete_clears_links() {
     isar.close();
 }
 
+#[test]
+fn test_clear_reset_ids() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, id, _obj1 => 1, _obj2 => 2);
+    col.clear(&mut txn, false).unwrap();
+    assert_eq!(col.count(&mut txn).unwrap(), 0);
+    assert_eq!(col.auto_increment(&mut txn).unwrap(), 3);
+
+    put!(col, txn, id, _obj3 => 4);
+    col.clear(&mut txn, true).unwrap();
+    assert_eq!(col.count(&mut txn).unwrap(), 0);
+    assert_eq!(col.auto_increment(&mut txn).unwrap(), 1);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_clear() {
+    let link_schema = LinkSchema::new("link", "obj1");
+    let indexes = TestObj::default_indexes();
+    let schema1 = TestObj::schema("obj1", &indexes, &[link_schema]);
+    let schema2 = TestObj::schema("obj2", &indexes, &[]);
+    isar!(isar, col1 => schema1, col2 => schema2);
+
+    txn!(isar, txn);
+    put!(id: col1, txn, _obj1 => 1, _obj2 => 2);
+    col1.link(&mut txn, 0, 1, 2).unwrap();
+    put!(id: col2, txn, obj3 => 1, obj4 => 2);
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    col1.clear(&mut txn, false).unwrap();
+    txn.commit().unwrap();
+
+    // the objects, index entries and links are gone and the other collection is untouched
+    txn!(isar, txn);
+    verify!(txn, col!(col1); col!(col2, obj3, obj4));
+    let stats = col1.get_stats(&mut txn).unwrap();
+    assert_eq!(stats.data.entries, 0);
+    assert!(stats.indexes.iter().all(|(_, s)| s.entries == 0));
+    txn.abort();
+    isar.close();
+}
+
 #[test]
 fn test_delete_calls_notifiers() {
     isar!(isar, col => TestObj::default_schema());
@@ -63,7 +112,16 @@ fn test_delete_calls_notifiers() {
 
     // watch object 1
     let (tx, rx) = unbounded();
-    let handle = isar.watch_object(col, 1, Box::new(move || tx.send(true).unwrap()));
+    let handle = isar.watch_object(col, 1, Box::new(move |deleted| tx.send(deleted).unwrap()));
+
+    // update object with id 1
+    txn!(isar, txn);
+    obj1.save(&mut txn, col);
+    txn.commit().unwrap();
+
+    // assert that the rx channel has received false
+    assert_eq!(rx.len(), 1);
+    assert!(!rx.try_recv().unwrap());
 
     // delete object with id 2
     let mut txn = isar.begin_txn(true, false).unwrap();
@@ -84,7 +142,7 @@ fn test_delete_calls_notifiers() {
 
     // clear the collection
     let mut txn = isar.begin_txn(true, false).unwrap();
-    col.clear(&mut txn).unwrap();
+    col.clear(&mut txn, false).unwrap();
     txn.commit().unwrap();
 
     // assert that the rx channel has received true
@@ -94,3 +152,63 @@ fn test_delete_calls_notifiers() {
     handle.stop();
     isar.close();
 }
+
+#[test]
+fn test_delete_all_range() {
+    let link_schema = LinkSchema::new("link", "obj");
+    let index = IndexSchema::new("int", vec![TestObj::int_index()], false);
+    let schema = TestObj::schema("obj", &[index], &[link_schema]);
+    isar!(isar, col => schema);
+    txn!(isar, txn);
+
+    put!(col, txn, int, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4, obj5 => 5, obj6 => 6);
+    col.link(&mut txn, 0, obj1.id, obj2.id).unwrap();
+    col.link(&mut txn, 0, obj4.id, obj5.id).unwrap();
+
+    // delete the objects with an int in [2, 4)
+    let (mut lower, mut upper) = (IndexKey::new(), IndexKey::new());
+    lower.add_int(2);
+    upper.add_int(4);
+    let count = col
+        .delete_all_index_range(&mut txn, 0, lower, true, upper, false)
+        .unwrap();
+    assert_eq!(count, 2);
+    verify!(txn, col, obj1, obj4, obj5, obj6; "link", obj4.id => obj5.id);
+
+    let count = col
+        .delete_all_id_range(&mut txn, obj5.id, i64::MAX)
+        .unwrap();
+    assert_eq!(count, 2);
+    verify!(txn, col, obj1, obj4);
+
+    let count = col
+        .delete_all_id_range(&mut txn, obj5.id, i64::MAX)
+        .unwrap();
+    assert_eq!(count, 0);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_delete_all_index_range_duplicate_keys() {
+    let index = IndexSchema::new("int", vec![TestObj::int_index()], false);
+    let schema = TestObj::schema("obj", &[index], &[]);
+    isar!(isar, col => schema);
+    txn!(isar, txn);
+
+    put!(col, txn, int, _obj1 => 1, _obj2 => 1, _obj3 => 1, _obj4 => 2, _obj5 => 2, obj6 => 3);
+
+    // delete all objects with an int in [1, 2]
+    let (mut lower, mut upper) = (IndexKey::new(), IndexKey::new());
+    lower.add_int(1);
+    upper.add_int(2);
+    let count = col
+        .delete_all_index_range(&mut txn, 0, lower, true, upper, true)
+        .unwrap();
+    assert_eq!(count, 5);
+    verify!(txn, col, obj6);
+
+    txn.abort();
+    isar.close();
+}
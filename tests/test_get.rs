@@ -1 +1,192 @@
+mod common;
 
+use crate::common::test_obj::TestObj;
+use isar_core::index::index_key::IndexKey;
+use isar_core::schema::index_schema::IndexSchema;
+
+#[test]
+fn test_get_all() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 5);
+
+    let ids = [5, 3, 1, 2, 1, i64::MIN];
+    let mut results = vec![];
+    col.get_all(&mut txn, &ids, |id, object| {
+        results.push((id, object.map(|o| o.as_bytes().to_vec())));
+        true
+    })
+    .unwrap();
+
+    assert_eq!(results.len(), ids.len());
+    for (id, bytes) in results {
+        let expected = col
+            .get(&mut txn, id)
+            .unwrap()
+            .map(|o| o.as_bytes().to_vec());
+        assert_eq!(bytes, expected);
+    }
+    assert_eq!(
+        col.get(&mut txn, 2).unwrap().unwrap().as_bytes(),
+        &obj2.to_bytes(col)[..]
+    );
+
+    let mut count = 0;
+    col.get_all(&mut txn, &ids, |_, _| {
+        count += 1;
+        count < 2
+    })
+    .unwrap();
+    assert_eq!(count, 2);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_get_bytes_does_not_copy() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 1);
+
+    let ptr = col.get(&mut txn, 1).unwrap().unwrap().as_bytes().as_ptr();
+    for _ in 0..1000 {
+        let bytes = col.get_bytes(&mut txn, 1).unwrap().unwrap();
+        assert_eq!(bytes.as_ptr(), ptr);
+        assert_eq!(bytes, &obj1.to_bytes(col)[..]);
+    }
+    assert_eq!(col.get_bytes(&mut txn, 2).unwrap(), None);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_iter_ids() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 5, obj2 => -3, obj3 => 1, obj4 => i64::MAX);
+
+    let mut ids = vec![];
+    col.iter_ids(&mut txn, |id| {
+        ids.push(id);
+        Ok(true)
+    })
+    .unwrap();
+    assert_eq!(ids, vec![-3, 1, 5, i64::MAX]);
+
+    let mut ids = vec![];
+    col.iter_ids(&mut txn, |id| {
+        ids.push(id);
+        Ok(ids.len() < 2)
+    })
+    .unwrap();
+    assert_eq!(ids, vec![-3, 1]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_exists() {
+    let schema1 = TestObj::schema("obj1", &TestObj::default_indexes(), &[]);
+    let schema2 = TestObj::schema("obj2", &TestObj::default_indexes(), &[]);
+    isar!(isar, col1 => schema1, col2 => schema2);
+    txn!(isar, txn);
+
+    put!(col1, txn, byte, obj1 => 5);
+    put!(id: col2, txn, obj2 => 7);
+
+    assert!(col1.exists(&mut txn, obj1.id).unwrap());
+    assert!(!col1.exists(&mut txn, obj1.id + 1).unwrap());
+    // ids are scoped to their collection
+    assert!(!col1.exists(&mut txn, obj2.id).unwrap());
+    assert!(col2.exists(&mut txn, obj2.id).unwrap());
+
+    let mut key = IndexKey::new();
+    key.add_byte(5);
+    assert!(col1.exists_by_index(&mut txn, 0, &key).unwrap());
+    assert!(!col2.exists_by_index(&mut txn, 0, &key).unwrap());
+    let mut key = IndexKey::new();
+    key.add_byte(6);
+    assert!(!col1.exists_by_index(&mut txn, 0, &key).unwrap());
+
+    col1.delete(&mut txn, obj1.id).unwrap();
+    assert!(!col1.exists(&mut txn, obj1.id).unwrap());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_get_all_by_index_key() {
+    let indexes = vec![IndexSchema::new("int", vec![TestObj::int_index()], false)];
+    isar!(isar, col => TestObj::schema("obj", &indexes, &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, int, obj1 => 5, obj2 => 5, _obj3 => 7, obj4 => 5);
+
+    let mut key = IndexKey::new();
+    key.add_int(5);
+    let mut results = vec![];
+    col.get_all_by_index_key(&mut txn, 0, &key, |id, object| {
+        results.push(TestObj::from_object(col, object));
+        assert_eq!(id, results.last().unwrap().id);
+        true
+    })
+    .unwrap();
+    assert_eq!(results, vec![obj1, obj2, obj4]);
+
+    let mut count = 0;
+    col.get_all_by_index_key(&mut txn, 0, &key, |_, _| {
+        count += 1;
+        false
+    })
+    .unwrap();
+    assert_eq!(count, 1);
+
+    let mut key = IndexKey::new();
+    key.add_int(6);
+    col.get_all_by_index_key(&mut txn, 0, &key, |_, _| panic!())
+        .unwrap();
+    let result = col.get_all_by_index_key(&mut txn, 1, &key, |_, _| true);
+    assert!(result.is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_get_by_checked_index_key() {
+    let indexes = vec![IndexSchema::new(
+        "long_string",
+        vec![TestObj::long_index(), TestObj::string_index(false, false)],
+        true,
+    )];
+    isar!(isar, col => TestObj::schema("obj", &indexes, &[]));
+    txn!(isar, txn);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.string = Some("abc".to_string());
+    obj1.save(&mut txn, col);
+    let mut obj2 = TestObj::default(2);
+    obj2.string = Some("abc".to_string());
+    obj2.save(&mut txn, col);
+
+    let mut key = col.new_index_key(0).unwrap();
+    key.add_long(2).unwrap();
+    key.add_string(Some("ABC")).unwrap();
+    let key = key.finish();
+    let (id, _) = col.get_by_index(&mut txn, 0, &key).unwrap().unwrap();
+    assert_eq!(id, 2);
+
+    let mut key = col.new_index_key(0).unwrap();
+    assert!(key.add_string(Some("abc")).is_err());
+    assert!(col.new_index_key(1).is_err());
+
+    txn.abort();
+    isar.close();
+}
@@ -1,7 +1,19 @@
 use crate::common::test_obj::TestObj;
-use isar_core::schema::index_schema::IndexSchema;
+use isar_core::collection::OnConflict;
+use isar_core::error::{IsarError, Result};
+use isar_core::index::index_key::IndexKey;
+use isar_core::instance::{Durability, IsarInstance};
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
 use isar_core::schema::link_schema::LinkSchema;
+use isar_core::schema::property_schema::PropertySchema;
+use isar_core::schema::Schema;
+use isar_core::txn::IsarTxn;
 use isar_core::verify::verify_isar;
+use std::cmp::Ordering;
+use std::ops::Range;
 
 mod common;
 
@@ -42,7 +54,7 @@ fn test_open_instance_added_collection() {
     isar!(path, isar, col1 => schema1);
     txn!(isar, txn);
     put!(id: col1, txn, obj1 => 1, obj2 => 2);
-    col1.link(&mut txn, 0, false, 1, 2).unwrap();
+    col1.link(&mut txn, 0, 1, 2).unwrap();
     verify!(txn, col1, obj1, obj2; "testlink", 1 => 2);
     txn.commit().unwrap();
     isar.close();
@@ -70,8 +82,8 @@ fn test_open_instance_removed_collection() {
     txn!(isar, txn);
     put!(id: col1, txn, obj1 => 1, obj2 => 2);
     put!(id: col2, txn, obj3 => 3, obj4 => 4);
-    col1.link(&mut txn, 0, false, 1, 2).unwrap();
-    col2.link(&mut txn, 0, false, 3, 4).unwrap();
+    col1.link(&mut txn, 0, 1, 2).unwrap();
+    col2.link(&mut txn, 0, 3, 4).unwrap();
     verify!(txn, col!(col1, obj1, obj2; "testlink1", 1 => 2); col!(col2, obj3, obj4; "testlink2", 3 => 4));
     txn.commit().unwrap();
     isar.close();
@@ -113,3 +125,604 @@ fn test_open_instance_added_index() {
 
 #[test]
 fn test_open_instance_removed_index() {}
+
+#[test]
+fn test_get_db_stats() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    put!(col, txn, int, obj1 => 1, obj2 => 2, obj3 => 3);
+    txn.commit().unwrap();
+
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    let stats = isar.get_db_stats(&mut txn).unwrap();
+    assert_eq!(stats.len(), 1);
+
+    let col_stats = &stats[0];
+    assert_eq!(col_stats.name, "obj");
+    assert_eq!(col_stats.data.entries, 3);
+    assert!(col_stats.data.pages > 0);
+    assert!(col_stats.data.depth > 0);
+
+    assert_eq!(col_stats.indexes.len(), TestObj::default_indexes().len());
+    let (name, int_stats) = &col_stats.indexes[1];
+    assert_eq!(name, "int");
+    assert_eq!(int_stats.entries, 3);
+    assert!(col_stats.links.is_empty());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_collection_get_size() {
+    let indexes = TestObj::default_indexes();
+    let link = LinkSchema::new("link", "obj");
+    let schema = TestObj::schema("obj", &indexes, &[link]);
+    isar!(isar, col => schema);
+    txn!(isar, txn);
+    put!(id: col, txn, _obj1 => 1, _obj2 => 2);
+    col.link(&mut txn, 0, 1, 2).unwrap();
+
+    let data = col.get_size(&mut txn, false, false).unwrap();
+    let with_indexes = col.get_size(&mut txn, true, false).unwrap();
+    let with_links = col.get_size(&mut txn, false, true).unwrap();
+    let all = col.get_size(&mut txn, true, true).unwrap();
+    assert!(data > 0);
+    assert!(with_indexes > data);
+    assert!(with_links > data);
+    assert_eq!(all, with_indexes + with_links - data);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_compact() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    for i in 0..5000i64 {
+        let mut obj = TestObj::default(i);
+        obj.string = Some("some string to fill up the database".to_string());
+        obj.save(&mut txn, col);
+    }
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    for i in 10..5000i64 {
+        col.delete(&mut txn, i).unwrap();
+    }
+
+    let mut target = std::env::temp_dir();
+    target.push(format!("{}.compact", rand::random::<u64>()));
+    let target = target.to_str().unwrap().to_string();
+
+    // compacting is not possible during a write txn
+    assert_eq!(isar.compact(&target), Err(IsarError::WriteTxnActive {}));
+    txn.commit().unwrap();
+
+    let mut db_file = std::path::PathBuf::from(&isar.dir);
+    db_file.push(&isar.name);
+    db_file.push("mdbx.dat");
+    let original_size = std::fs::metadata(db_file).unwrap().len();
+
+    let compacted_size = isar.compact(&target).unwrap();
+    assert_eq!(std::fs::metadata(&target).unwrap().len(), compacted_size);
+    assert!(compacted_size < original_size);
+
+    // the write lock is released after compacting
+    txn!(isar, txn);
+    col.delete(&mut txn, 0).unwrap();
+    txn.commit().unwrap();
+
+    std::fs::remove_file(&target).unwrap();
+    isar.close();
+}
+
+#[test]
+fn test_write_txn() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let obj = TestObj::default(5);
+    let count = isar
+        .write_txn(false, |txn| {
+            obj.save(txn, col);
+            col.count(txn)
+        })
+        .unwrap();
+    assert_eq!(count, 1);
+
+    // errors abort the txn
+    let result = isar.write_txn(false, |txn| {
+        TestObj::default(6).save(txn, col);
+        col.delete_by_index(txn, 100, &IndexKey::new())
+    });
+    assert_eq!(result, Err(IsarError::UnknownIndex {}));
+
+    txn!(isar, txn);
+    verify!(txn, col, obj);
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_write_txn_grows_max_size() {
+    const MB: u64 = 1 << 20;
+    isar!(isar, col => TestObj::default_schema());
+    isar.set_max_size(8 * MB).unwrap();
+    isar.set_max_size_limit(16 * MB);
+
+    let put_objects = |txn: &mut IsarTxn, ids: Range<i64>| -> Result<()> {
+        for id in ids {
+            let mut obj = TestObj::default(id);
+            obj.byte_list = Some(vec![id as u8; MB as usize]);
+            let bytes = obj.to_bytes(col);
+            let object = IsarObject::from_bytes(&bytes);
+            col.put(txn, Some(id), object, OnConflict::Abort)?;
+        }
+        Ok(())
+    };
+
+    // the first attempt exceeds 8 MB and is retried with 16 MB
+    isar.write_txn(false, |txn| put_objects(txn, 1..11))
+        .unwrap();
+    txn!(isar, txn);
+    assert_eq!(col.count(&mut txn).unwrap(), 10);
+    txn.abort();
+
+    let result = isar.write_txn(false, |txn| put_objects(txn, 11..21));
+    assert_eq!(result, Err(IsarError::MapSizeLimitReached {}));
+    txn!(isar, txn);
+    assert_eq!(col.count(&mut txn).unwrap(), 10);
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_migrate_add_property() {
+    let a_index = IndexSchema::new(
+        "a",
+        vec![IndexPropertySchema::new("a", IndexType::Value, false)],
+        false,
+    );
+    let properties = vec![
+        PropertySchema::new("a", DataType::Int),
+        PropertySchema::new("b", DataType::Long),
+    ];
+    let schema1 = CollectionSchema::new("col", properties.clone(), vec![a_index.clone()], vec![]);
+
+    let mut properties = properties;
+    properties.push(PropertySchema::new("c", DataType::String));
+    let schema2 = CollectionSchema::new("col", properties, vec![a_index], vec![]);
+
+    isar!(isar, col => schema1);
+    let path = isar.dir.clone();
+    txn!(isar, txn);
+    for i in 1..=2 {
+        let mut ob = col.new_object_builder(None);
+        ob.write_int(i * 10);
+        ob.write_long(i as i64);
+        col.put(&mut txn, Some(i as i64), ob.finish(), OnConflict::Abort)
+            .unwrap();
+    }
+    txn.commit().unwrap();
+    isar.close();
+
+    isar!(path, isar, col => schema2);
+    txn!(isar, txn);
+    let mut ob = col.new_object_builder(None);
+    ob.write_null(DataType::Int);
+    ob.write_null(DataType::Long);
+    ob.write_string(Some("default"));
+    let default = ob.finish();
+    assert_eq!(col.migrate_add_property(&mut txn, "c", default).unwrap(), 2);
+    assert_eq!(col.migrate_add_property(&mut txn, "c", default).unwrap(), 0);
+
+    let (_, a) = col.properties[0];
+    let (_, b) = col.properties[1];
+    let (_, c) = col.properties[2];
+    for i in 1..=2 {
+        let mut key = IndexKey::new();
+        key.add_int(i * 10);
+        let (id, object) = col.get_by_index(&mut txn, 0, &key).unwrap().unwrap();
+        assert_eq!(id, i as i64);
+        assert_eq!(object.read_int(a), i * 10);
+        assert_eq!(object.read_long(b), i as i64);
+        assert_eq!(object.read_string(c), Some("default"));
+    }
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_migrate_remove_property() {
+    let properties = vec![
+        PropertySchema::new("a", DataType::Int),
+        PropertySchema::new("b", DataType::String),
+        PropertySchema::new("c", DataType::LongList),
+    ];
+    let indexes = vec![
+        IndexSchema::new(
+            "a",
+            vec![IndexPropertySchema::new("a", IndexType::Value, false)],
+            false,
+        ),
+        IndexSchema::new(
+            "b",
+            vec![IndexPropertySchema::new("b", IndexType::Hash, true)],
+            true,
+        ),
+        IndexSchema::new(
+            "ba",
+            vec![
+                IndexPropertySchema::new("b", IndexType::Value, true),
+                IndexPropertySchema::new("a", IndexType::Value, false),
+            ],
+            false,
+        ),
+    ];
+    let schema = CollectionSchema::new("col", properties.clone(), indexes.clone(), vec![]);
+
+    isar!(isar, col => schema);
+    let path = isar.dir.clone();
+    let (_, a) = col.properties[0];
+    let (_, b) = col.properties[1];
+    let (_, c) = col.properties[2];
+
+    txn!(isar, txn);
+    let mut originals = vec![];
+    for i in 1..=3 {
+        let mut ob = col.new_object_builder(None);
+        ob.write_int(i);
+        ob.write_string(Some(&format!("value {}", i)));
+        ob.write_long_list(Some(&[i as i64, -1]));
+        col.put(&mut txn, Some(i as i64), ob.finish(), OnConflict::Abort)
+            .unwrap();
+        originals.push(ob.finish().as_bytes().to_vec());
+    }
+
+    assert!(col.migrate_remove_property(&mut txn, 1, false).is_err());
+    txn.commit().unwrap();
+
+    // a query built before the index is removed
+    let mut key = IndexKey::new();
+    key.add_string(Some("value 1"), true);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(2, key.clone(), true, key, true, false)
+        .unwrap();
+    let query = qb.build();
+
+    // the removal is discarded if the transaction is aborted
+    txn!(isar, txn);
+    assert_eq!(col.migrate_remove_property(&mut txn, 1, true).unwrap(), 3);
+    assert_eq!(
+        query.find_all_vec(&mut txn).err(),
+        Some(IsarError::UnknownIndex {})
+    );
+    txn.abort();
+    txn!(isar, txn);
+    assert_eq!(query.find_all_vec(&mut txn).unwrap().len(), 1);
+    assert!(isar.verify(&mut txn).unwrap().is_empty());
+    txn.abort();
+
+    txn!(isar, txn);
+    assert_eq!(col.migrate_remove_property(&mut txn, 1, true).unwrap(), 3);
+    assert_eq!(col.migrate_remove_property(&mut txn, 1, true).unwrap(), 0);
+
+    for (i, original) in originals.iter().enumerate() {
+        let original = IsarObject::from_bytes(original);
+        let object = col.get(&mut txn, i as i64 + 1).unwrap().unwrap();
+        assert!(object.as_bytes().len() < original.as_bytes().len());
+        assert!(object.is_null(b));
+        assert_eq!(object.compare_property(&original, a), Ordering::Equal);
+        assert_eq!(object.compare_property(&original, c), Ordering::Equal);
+    }
+
+    let stats = col.get_stats(&mut txn).unwrap();
+    assert_eq!(stats.indexes[0].1.entries, 3);
+    assert_eq!(stats.indexes[1].1.entries, 0);
+    assert_eq!(stats.indexes[2].1.entries, 0);
+
+    for i in 4..=5 {
+        let mut ob = col.new_object_builder(None);
+        ob.write_int(i);
+        ob.write_string(None);
+        ob.write_long_list(None);
+        col.put(&mut txn, Some(i as i64), ob.finish(), OnConflict::Abort)
+            .unwrap();
+    }
+    assert!(isar.verify(&mut txn).unwrap().is_empty());
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    for index_index in 1..=2 {
+        let mut qb = col.new_query_builder();
+        let key = IndexKey::new();
+        let result = qb.add_index_where_clause(index_index, key.clone(), true, key, true, false);
+        assert!(matches!(result, Err(IsarError::UnknownIndex {})));
+    }
+    assert_eq!(
+        query.find_all_vec(&mut txn).err(),
+        Some(IsarError::UnknownIndex {})
+    );
+    txn.abort();
+    isar.close();
+
+    // the unique index cannot be created again because all values are null
+    let indexes = vec![indexes[0].clone(), indexes[2].clone()];
+    let schema = CollectionSchema::new("col", properties, indexes, vec![]);
+    isar!(path, isar, col => schema);
+    txn!(isar, txn);
+    let mut key = IndexKey::new();
+    key.add_string(None, true);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(1, key.clone(), true, key, true, false)
+        .unwrap();
+    let ids = qb
+        .build()
+        .find_all_vec(&mut txn)
+        .unwrap()
+        .iter()
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    assert!(isar.verify(&mut txn).unwrap().is_empty());
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_auto_increment_after_reopen() {
+    isar!(isar, col => TestObj::default_schema());
+    let path = isar.dir.clone();
+    txn!(isar, txn);
+    put!(col, txn, id, obj1 => 1, obj2 => 2);
+    TestObj::default(10).save(&mut txn, col);
+    assert_eq!(col.auto_increment(&mut txn).unwrap(), 11);
+    txn.commit().unwrap();
+    isar.close();
+
+    isar!(path, isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    assert_eq!(col.auto_increment(&mut txn).unwrap(), 11);
+    assert_eq!(col.auto_increment(&mut txn).unwrap(), 12);
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_open_with_durability() {
+    for durability in [
+        Durability::SafeSync,
+        Durability::NoMetaSync,
+        Durability::NoSync,
+    ] {
+        let mut dir = std::env::temp_dir();
+        let r: u64 = rand::random();
+        dir.push(&r.to_string());
+        let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+        let isar = IsarInstance::open(
+            &r.to_string(),
+            dir.to_str().unwrap(),
+            durability,
+            None,
+            schema,
+        )
+        .unwrap();
+        let col = &isar.collections[0];
+
+        txn!(isar, txn);
+        put!(id: col, txn, obj1 => 1);
+        txn.commit().unwrap();
+
+        txn!(isar, txn);
+        txn.set_durability(Durability::SafeSync).unwrap();
+        put!(id: col, txn, obj2 => 2);
+        txn.commit().unwrap();
+
+        txn!(isar, txn);
+        verify!(txn, col, obj1, obj2);
+        let result = txn.set_durability(Durability::NoSync);
+        assert_eq!(result.is_err(), durability != Durability::NoSync);
+        txn.abort();
+        isar.close();
+    }
+}
+
+#[test]
+fn test_sync() {
+    let mut dir = std::env::temp_dir();
+    let r: u64 = rand::random();
+    dir.push(&r.to_string());
+    let path = dir.to_str().unwrap();
+    let open = |durability| {
+        let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+        IsarInstance::open(&r.to_string(), path, durability, None, schema).unwrap()
+    };
+
+    let isar = open(Durability::NoSync);
+    let col = &isar.collections[0];
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, obj2 => 2);
+    txn.commit().unwrap();
+    isar.sync().unwrap();
+    isar.close();
+
+    let isar = open(Durability::SafeSync);
+    let col = &isar.collections[0];
+    isar.sync().unwrap();
+    txn!(isar, txn);
+    verify!(txn, col, obj1, obj2);
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_metrics() {
+    isar!(isar, col => TestObj::default_schema());
+    let before = isar.metrics();
+
+    txn!(isar, txn);
+    put!(id: col, txn, _obj => 1);
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    txn.abort();
+
+    let txn = isar.begin_txn(false, false).unwrap();
+    txn.abort();
+
+    let metrics = isar.metrics();
+    assert_eq!(
+        metrics.write_txns_committed,
+        before.write_txns_committed + 1
+    );
+    assert_eq!(metrics.write_txns_aborted, before.write_txns_aborted + 1);
+    assert_eq!(metrics.read_txns_opened, before.read_txns_opened + 1);
+    assert!(metrics.write_lock_micros >= before.write_lock_micros);
+
+    // notifying watchers happens after the write lock has been released
+    let handle = isar.watch_collection(
+        col,
+        Box::new(|| std::thread::sleep(std::time::Duration::from_millis(200))),
+    );
+    txn!(isar, txn);
+    put!(id: col, txn, _obj => 2);
+    txn.commit().unwrap();
+    let micros = isar.metrics().write_lock_micros - metrics.write_lock_micros;
+    assert!(micros < 200_000);
+    handle.stop();
+    isar.close();
+}
+
+#[test]
+fn test_open_incompatible_schema() {
+    fn schema(b_type: DataType) -> CollectionSchema {
+        let properties = vec![
+            PropertySchema::new("a", DataType::Int),
+            PropertySchema::new("b", b_type),
+        ];
+        CollectionSchema::new("col", properties, vec![], vec![])
+    }
+    let other = TestObj::schema("other", &[], &[]);
+
+    isar!(isar, _col => schema(DataType::Long), other_col => other);
+    let path = isar.dir.clone();
+    txn!(isar, txn);
+    put!(id: other_col, txn, obj1 => 1);
+    txn.commit().unwrap();
+    isar.close();
+
+    // the changed type is reported and the removed collection is not deleted
+    let name = xxhash_rust::xxh3::xxh3_64(path.as_bytes()).to_string();
+    let new_schema = Schema::new(vec![schema(DataType::Double)]).unwrap();
+    let result = IsarInstance::open(&name, &path, Durability::SafeSync, None, new_schema);
+    let message = "Property \"b\" of collection \"col\" is stored as Long but is now Double.";
+    assert_eq!(
+        result.err(),
+        Some(IsarError::SchemaError {
+            message: message.to_string()
+        })
+    );
+
+    isar!(path, isar, col => schema(DataType::Long), other_col => other);
+    txn!(isar, txn);
+    verify!(txn, col!(col); col!(other_col, obj1));
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_collection_schema_hash() {
+    let a = PropertySchema::new("a", DataType::Int);
+    let b = PropertySchema::new("b", DataType::Long);
+    let schema1 = CollectionSchema::new("col", vec![a.clone(), b.clone()], vec![], vec![]);
+    let schema2 = CollectionSchema::new("col", vec![b, a], vec![], vec![]);
+
+    isar!(isar, col => schema1);
+    let path = isar.dir.clone();
+    let hash = col.schema_hash();
+    assert_eq!(col.previous_schema_hash(), None);
+    assert!(col.schema_changed());
+    isar.close();
+
+    isar!(path, isar, col => schema1);
+    assert_eq!(col.schema_hash(), hash);
+    assert_eq!(col.previous_schema_hash(), Some(hash));
+    assert!(!col.schema_changed());
+    isar.close();
+
+    isar!(path, isar, col => schema2);
+    assert_ne!(col.schema_hash(), hash);
+    assert_eq!(col.previous_schema_hash(), Some(hash));
+    assert!(col.schema_changed());
+    isar.close();
+}
+
+#[test]
+fn test_collection_get_schema() {
+    let properties = vec![
+        PropertySchema::new("byte", DataType::Byte),
+        PropertySchema::new("int", DataType::Int),
+        PropertySchema::new("string", DataType::String),
+        PropertySchema::new("intList", DataType::IntList),
+        PropertySchema::new("stringList", DataType::StringList),
+        PropertySchema::decimal("decimal", 2),
+    ];
+    let index_property = IndexPropertySchema::new("string", IndexType::Value, true);
+    let index = IndexSchema::new("string", vec![index_property], false);
+    let link = LinkSchema::new("link", "col");
+    let schema = CollectionSchema::new("col", properties, vec![index.clone()], vec![link.clone()]);
+    isar!(isar, col => schema);
+
+    let info = col.get_schema();
+    assert_eq!(info.name, "col");
+    let properties = info
+        .properties
+        .iter()
+        .map(|p| (p.name.as_str(), p.data_type, p.offset, p.scale))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        properties,
+        vec![
+            ("byte", DataType::Byte, 2, 0),
+            ("int", DataType::Int, 3, 0),
+            ("string", DataType::String, 7, 0),
+            ("intList", DataType::IntList, 15, 0),
+            ("stringList", DataType::StringList, 23, 0),
+            ("decimal", DataType::Decimal, 31, 2),
+        ]
+    );
+    assert_eq!(info.indexes, vec![index]);
+    assert_eq!(info.links, vec![link]);
+    isar.close();
+}
+
+#[test]
+fn test_open_read_only() {
+    isar!(isar, col => TestObj::default_schema());
+    let path = isar.dir.clone();
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, obj2 => 2);
+    txn.commit().unwrap();
+    isar.close();
+
+    let name = xxhash_rust::xxh3::xxh3_64(path.as_bytes()).to_string();
+    let isar = IsarInstance::open_read_only(&name, &path, None).unwrap();
+    assert!(isar.is_read_only());
+    let col = isar.collections.get(0).unwrap();
+
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    verify!(txn, col, obj1, obj2);
+    txn.abort();
+
+    let result = isar.begin_txn(true, false);
+    assert_eq!(result.err(), Some(IsarError::ReadOnly {}));
+    let result = isar.write_txn(true, |_| Ok(()));
+    assert_eq!(result, Err(IsarError::ReadOnly {}));
+
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let result = IsarInstance::open(&name, &path, Durability::SafeSync, None, schema);
+    assert_eq!(result.err(), Some(IsarError::ReadOnly {}));
+    assert!(isar.close());
+}
@@ -0,0 +1,431 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::{ImportStats, IsarCollection, OnConflict};
+use isar_core::index::index_key::IndexKey;
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::query::filter::Filter;
+use isar_core::query::query_builder::QueryBuilder;
+use isar_core::query::Sort;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::schema::property_schema::PropertySchema;
+use isar_core::txn::IsarTxn;
+use serde_json::{json, Value};
+use std::io::Read;
+
+mod common;
+
+#[test]
+fn test_export_import_json() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let obj1 = TestObj::new(
+        1,
+        3,
+        -5,
+        1.5,
+        f64::NAN,
+        Some("hello"),
+        Some(&[1, 2]),
+        None,
+        Some(&[i64::MIN, 4]),
+        Some(&[]),
+        None,
+        Some(&[Some("a".to_string()), None]),
+    );
+    obj1.save(&mut txn, col);
+    put!(col, txn, id, obj2 => 2, obj3 => 3);
+
+    let mut bytes = vec![];
+    let mut chunks = 0;
+    col.export_json(&mut txn, "id", |chunk| {
+        bytes.extend_from_slice(chunk);
+        chunks += 1;
+        Ok(true)
+    })
+    .unwrap();
+    assert_eq!(chunks, 5);
+
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 3);
+    assert_eq!(json[0]["id"], 1);
+    assert_eq!(json[0]["string"], "hello");
+    assert_eq!(json[0]["intList"], Value::Null);
+
+    col.clear(&mut txn, false).unwrap();
+    verify!(txn, col);
+
+    col.import_json(&mut txn, Some("id"), json, OnConflict::Abort)
+        .unwrap();
+    verify!(txn, col, obj1, obj2, obj3);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_object_to_json() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let obj = TestObj::new(
+        7,
+        3,
+        -5,
+        1.5,
+        f64::NAN,
+        Some("hello"),
+        Some(&[1, 2]),
+        None,
+        Some(&[i64::MIN, 4]),
+        Some(&[]),
+        None,
+        Some(&[Some("a".to_string()), None]),
+    );
+    obj.save(&mut txn, col);
+
+    let object = col.get(&mut txn, 7).unwrap().unwrap();
+    let json = col.object_to_json(object, "key", 7);
+    assert_eq!(json["key"], 7);
+    assert_eq!(json["string"], "hello");
+    assert_eq!(json["intList"], Value::Null);
+    assert_eq!(json["stringList"], json!(["a", null]));
+
+    col.clear(&mut txn, false).unwrap();
+    col.import_json(&mut txn, Some("key"), json!([json]), OnConflict::Abort)
+        .unwrap();
+    verify!(txn, col, obj);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_rename_property_json() {
+    fn schema(name: &str) -> CollectionSchema {
+        let properties = vec![
+            PropertySchema::new("a", DataType::Int),
+            PropertySchema::new(name, DataType::String),
+        ];
+        let index = IndexSchema::new(
+            "index",
+            vec![IndexPropertySchema::new(name, IndexType::Value, false)],
+            false,
+        );
+        CollectionSchema::new("col", properties, vec![index], vec![])
+    }
+
+    fn export(txn: &mut IsarTxn, col: &IsarCollection) -> Value {
+        let mut bytes = vec![];
+        col.export_json(txn, "id", |chunk| {
+            bytes.extend_from_slice(chunk);
+            Ok(true)
+        })
+        .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    isar!(isar, col => schema("b"));
+    let path = isar.dir.clone();
+    txn!(isar, txn);
+    col.import_json(
+        &mut txn,
+        Some("id"),
+        json!([{"id": 1, "a": 5, "b": "hello"}]),
+        OnConflict::Abort,
+    )
+    .unwrap();
+    let bytes = col.get(&mut txn, 1).unwrap().unwrap().as_bytes().to_vec();
+    assert_eq!(export(&mut txn, col)[0]["b"], "hello");
+
+    assert!(col.rename_property(&mut txn, "b", "a").is_err());
+    assert!(col.rename_property(&mut txn, "x", "c").is_err());
+    col.rename_property(&mut txn, "b", "c").unwrap();
+    txn.commit().unwrap();
+    isar.close();
+
+    isar!(path, isar, col => schema("c"));
+    txn!(isar, txn);
+    assert_eq!(
+        col.get(&mut txn, 1).unwrap().unwrap().as_bytes(),
+        &bytes[..]
+    );
+    let json = export(&mut txn, col);
+    assert_eq!(json[0]["c"], "hello");
+    assert_eq!(json[0].get("b"), None);
+
+    col.import_json(
+        &mut txn,
+        Some("id"),
+        json!([{"id": 2, "a": 6, "c": "world"}]),
+        OnConflict::Abort,
+    )
+    .unwrap();
+    let mut key = IndexKey::new();
+    key.add_string(Some("world"), false);
+    let (id, _) = col.get_by_index(&mut txn, 0, &key).unwrap().unwrap();
+    assert_eq!(id, 2);
+    let stats = col.get_stats(&mut txn).unwrap();
+    assert_eq!(stats.indexes[0].1.entries, 2);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_bool_json() {
+    fn schema(data_type: DataType) -> CollectionSchema {
+        let properties = vec![PropertySchema::new("a", data_type)];
+        CollectionSchema::new("col", properties, vec![], vec![])
+    }
+
+    fn export(txn: &mut IsarTxn, col: &IsarCollection) -> Value {
+        let mut bytes = vec![];
+        col.export_json(txn, "id", |chunk| {
+            bytes.extend_from_slice(chunk);
+            Ok(true)
+        })
+        .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    // bools used to be stored as bytes
+    isar!(isar, col => schema(DataType::Byte));
+    let path = isar.dir.clone();
+    txn!(isar, txn);
+    col.import_json(
+        &mut txn,
+        Some("id"),
+        json!([{"id": 1, "a": true}, {"id": 2, "a": false}, {"id": 3, "a": null}, {"id": 4}]),
+        OnConflict::Abort,
+    )
+    .unwrap();
+    txn.commit().unwrap();
+    isar.close();
+
+    isar!(path, isar, col => schema(DataType::Bool));
+    txn!(isar, txn);
+    let json = export(&mut txn, col);
+    assert_eq!(
+        json,
+        json!([
+            {"id": 1, "a": true},
+            {"id": 2, "a": false},
+            {"id": 3, "a": null},
+            {"id": 4, "a": null}
+        ])
+    );
+
+    col.clear(&mut txn, false).unwrap();
+    col.import_json(&mut txn, Some("id"), json.clone(), OnConflict::Abort)
+        .unwrap();
+    assert_eq!(export(&mut txn, col), json);
+
+    let (_, property) = col.properties[0];
+    for (value, ids) in [
+        (Some(true), vec![1]),
+        (Some(false), vec![2]),
+        (None, vec![3, 4]),
+    ] {
+        let mut qb = col.new_query_builder();
+        qb.set_filter(Filter::bool(property, value).unwrap());
+        let result = qb.build().find_all_vec(&mut txn).unwrap();
+        assert_eq!(result.iter().map(|(id, _)| *id).collect::<Vec<_>>(), ids);
+    }
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(property, IsarObject::FALSE_BYTE, IsarObject::TRUE_BYTE).unwrap());
+    let result = qb.build().find_all_vec(&mut txn).unwrap();
+    assert_eq!(
+        result.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+
+    let result = col.import_json(
+        &mut txn,
+        Some("id"),
+        json!([{"id": 5, "a": 1}]),
+        OnConflict::Abort,
+    );
+    assert!(result.is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_decimal_json() {
+    let properties = vec![PropertySchema::decimal("price", 2)];
+    let index = IndexSchema::new(
+        "price",
+        vec![IndexPropertySchema::new("price", IndexType::Value, false)],
+        false,
+    );
+    let schema = CollectionSchema::new("col", properties, vec![index], vec![]);
+
+    isar!(isar, col => schema);
+    txn!(isar, txn);
+    let json = json!([
+        {"id": 1, "price": "12.34"},
+        {"id": 2, "price": "-10.50"},
+        {"id": 3, "price": "0.00"},
+        {"id": 4, "price": "-0.05"},
+        {"id": 5, "price": null},
+        {"id": 6, "price": "3.00"},
+        {"id": 7, "price": "-100.00"}
+    ]);
+    col.import_json(&mut txn, Some("id"), json.clone(), OnConflict::Abort)
+        .unwrap();
+
+    let mut bytes = vec![];
+    col.export_json(&mut txn, "id", |chunk| {
+        bytes.extend_from_slice(chunk);
+        Ok(true)
+    })
+    .unwrap();
+    let exported: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(exported, json);
+
+    fn ids(txn: &mut IsarTxn, qb: QueryBuilder) -> Vec<i64> {
+        let result = qb.build().find_all_vec(txn).unwrap();
+        result.iter().map(|(id, _)| *id).collect()
+    }
+
+    let mut qb = col.new_query_builder();
+    let (mut start, mut end) = (IndexKey::new(), IndexKey::new());
+    start.add_long(i64::MIN);
+    end.add_long(i64::MAX);
+    qb.add_index_where_clause(0, start, true, end, true, false)
+        .unwrap();
+    assert_eq!(ids(&mut txn, qb), vec![5, 7, 2, 4, 3, 6, 1]);
+
+    let (_, property) = col.properties[0];
+    let mut qb = col.new_query_builder();
+    qb.add_sort(property, Sort::Descending).unwrap();
+    assert_eq!(ids(&mut txn, qb), vec![1, 6, 3, 4, 2, 7, 5]);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::decimal(property, -1050, 0).unwrap());
+    assert_eq!(ids(&mut txn, qb), vec![2, 3, 4]);
+
+    let result = col.import_json(
+        &mut txn,
+        Some("id"),
+        json!([{"id": 8, "price": "1.234"}]),
+        OnConflict::Abort,
+    );
+    assert!(result.is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_import_json_stream() {
+    /// Generates a JSON array of `count` objects on the fly.
+    struct GeneratedJson {
+        count: usize,
+        next: usize,
+        pending: Vec<u8>,
+    }
+
+    impl Read for GeneratedJson {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() {
+                if self.next == 0 {
+                    self.pending.push(b'[');
+                }
+                if self.next < self.count {
+                    if self.next > 0 {
+                        self.pending.push(b',');
+                    }
+                    let obj = json!({"id": self.next as i64 + 1, "long": self.next});
+                    serde_json::to_writer(&mut self.pending, &obj).unwrap();
+                    self.next += 1;
+                } else if self.next == self.count {
+                    self.pending.push(b']');
+                    self.next += 1;
+                }
+            }
+            let len = self.pending.len().min(buf.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            Ok(len)
+        }
+    }
+
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let mut reader = GeneratedJson {
+        count: 50_000,
+        next: 0,
+        pending: vec![],
+    };
+    let stats = col
+        .import_json_stream(&mut txn, Some("id"), &mut reader, OnConflict::Abort)
+        .unwrap();
+    assert_eq!(stats.inserted, 50_000);
+    assert_eq!(col.count(&mut txn).unwrap(), 50_000);
+
+    let long = TestObj::get_prop(col, DataType::Long);
+    let object = col.get(&mut txn, 50_000).unwrap().unwrap();
+    assert_eq!(object.read_long(long), 49_999);
+
+    let result = col.import_json_stream(
+        &mut txn,
+        Some("id"),
+        &b"[{\"id\": 50001}, {\"id\": 1}]"[..],
+        OnConflict::Abort,
+    );
+    assert!(result.is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_import_json_stats() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let json = json!([{"id": 1, "int": 1}, {"id": 2, "int": 2}, {"int": 3}]);
+    let stats = col
+        .import_json(&mut txn, Some("id"), json, OnConflict::Replace)
+        .unwrap();
+    assert_eq!(
+        stats,
+        ImportStats {
+            inserted: 3,
+            replaced: 0
+        }
+    );
+
+    let json = json!([{"id": 2, "int": 4}, {"id": 3, "int": 5}, {"id": 4, "int": 6}]);
+    let stats = col
+        .import_json(&mut txn, Some("id"), json.clone(), OnConflict::Replace)
+        .unwrap();
+    assert_eq!(
+        stats,
+        ImportStats {
+            inserted: 1,
+            replaced: 2
+        }
+    );
+
+    let stats = col
+        .import_json(&mut txn, Some("id"), json, OnConflict::Replace)
+        .unwrap();
+    assert_eq!(
+        stats,
+        ImportStats {
+            inserted: 0,
+            replaced: 3
+        }
+    );
+    assert_eq!(col.count(&mut txn).unwrap(), 4);
+
+    txn.abort();
+    isar.close();
+}
@@ -1,8 +1,11 @@
 mod common;
 
+use crossbeam_channel::unbounded;
 use isar_core::collection::IsarCollection;
+use isar_core::query::filter::Filter;
 use isar_core::schema::link_schema::LinkSchema;
 use isar_core::txn::IsarTxn;
+use itertools::Itertools;
 
 use crate::common::test_obj::TestObj;
 
@@ -16,11 +19,11 @@ fn test_create_aborts_if_object_not_existing() {
     put!(col, txn, id, obj => 1);
 
     // source object does not exist
-    let linked = col.link(&mut txn, 0, false, 5, 1).unwrap();
+    let linked = col.link(&mut txn, 0, 5, 1).unwrap();
     assert!(!linked);
 
     // target object does not exist
-    let linked = col.link(&mut txn, 0, false, 1, 5).unwrap();
+    let linked = col.link(&mut txn, 0, 1, 5).unwrap();
     assert!(!linked);
 
     verify!(txn, col, obj);
@@ -47,15 +50,15 @@ fn test_link() {
     put!(col2, txn, id, obj2a => 3, obj2b => 4);
 
     // same collection same object
-    let linked = col1.link(&mut txn, 0, false, obj1a.id, obj1a.id).unwrap();
+    let linked = col1.link(&mut txn, 0, obj1a.id, obj1a.id).unwrap();
     assert!(linked);
 
     // same collection different object
-    let linked = col1.link(&mut txn, 0, false, obj1a.id, obj1b.id).unwrap();
+    let linked = col1.link(&mut txn, 0, obj1a.id, obj1b.id).unwrap();
     assert!(linked);
 
     // different collection
-    let linked = col1.link(&mut txn, 1, false, obj1a.id, obj2b.id).unwrap();
+    let linked = col1.link(&mut txn, 1, obj1a.id, obj2b.id).unwrap();
     assert!(linked);
 
     verify!(txn,
@@ -71,46 +74,55 @@ fn test_link() {
 }
 
 #[test]
-fn test_link_backlink() {
-    let col1_schema = TestObj::schema(
-        "col1",
-        &[],
-        &[
-            LinkSchema::new("self", "col1"),
-            LinkSchema::new("other", "col2"),
-        ],
-    );
-    let col2_schema = TestObj::schema("col2", &[], &[]);
-    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+fn test_self_link_tree() {
+    let col_schema = TestObj::schema("col", &[], &[LinkSchema::new("children", "col")]);
+    isar!(isar, col => col_schema);
     txn!(isar, txn);
 
-    put!(col1, txn, id, obj1a => 1, obj1b => 2);
-    put!(col2, txn, id, obj2a => 3, obj2b => 4);
-
-    // same collection same object
-    let linked = col1.link(&mut txn, 0, true, obj1a.id, obj1a.id).unwrap();
-    assert!(linked);
+    // 1 -> (2 -> (4, 5), 3 -> 5)
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4, obj5 => 5);
+    for (parent, child) in [(1, 2), (1, 3), (2, 4), (2, 5), (3, 5)] {
+        assert!(col.link_self(&mut txn, 0, parent, child).unwrap());
+    }
 
-    // same collection different object
-    let linked = col1.link(&mut txn, 0, true, obj1a.id, obj1b.id).unwrap();
-    assert!(linked);
+    verify_linked(&mut txn, col, 0, false, 1, vec![2, 3]);
+    verify_linked(&mut txn, col, 0, false, 2, vec![4, 5]);
+    verify_linked(&mut txn, col, 0, false, 5, vec![]);
+    verify_linked(&mut txn, col, 0, true, 1, vec![]);
+    verify_linked(&mut txn, col, 0, true, 4, vec![2]);
+    verify_linked(&mut txn, col, 0, true, 5, vec![2, 3]);
 
-    // different collection
-    let linked = col2.link(&mut txn, 0, true, obj2b.id, obj1a.id).unwrap();
-    assert!(linked);
+    verify!(txn, col, obj1, obj2, obj3, obj4, obj5;
+        "children", 1 => 2, 1 => 3, 2 => 4, 2 => 5, 3 => 5
+    );
 
-    verify!(txn,
-        col!(col1, obj1a, obj1b;
-            "self", obj1a.id => obj1a.id, obj1b.id => obj1a.id;
-            "other", obj1a.id => obj2b.id
-        );
-        col!(col2, obj2a, obj2b)
+    // deleting an object removes the links from and to the object
+    col.delete(&mut txn, 2).unwrap();
+    verify_linked(&mut txn, col, 0, false, 1, vec![3]);
+    verify_linked(&mut txn, col, 0, true, 5, vec![3]);
+    verify!(txn, col, obj1, obj3, obj4, obj5;
+        "children", 1 => 3, 3 => 5
     );
 
     txn.abort();
     isar.close();
 }
 
+#[test]
+fn test_link_self_requires_self_link() {
+    let col1_schema = TestObj::schema("col1", &[], &[LinkSchema::new("other", "col2")]);
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+    txn!(isar, txn);
+
+    put!(id: col1, txn, obj1 => 1);
+    put!(id: col2, txn, obj2 => 2);
+    assert!(col1.link_self(&mut txn, 0, obj1.id, obj2.id).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
 fn verify_linked(
     txn: &mut IsarTxn,
     col: &IsarCollection,
@@ -119,12 +131,19 @@ fn verify_linked(
     id: i64,
     linked_ids: Vec<i64>,
 ) {
-    let mut linked = vec![];
-    col.get_linked_objects(txn, link, bl, id, |id, _| {
-        linked.push(id);
-        true
-    })
-    .unwrap();
+    let mut qb = col.new_query_builder();
+    if bl {
+        qb.add_backlink_where_clause(col, link, id).unwrap();
+    } else {
+        qb.add_link_where_clause(link, id).unwrap();
+    }
+    let linked = qb
+        .build()
+        .find_all_vec(txn)
+        .unwrap()
+        .iter()
+        .map(|(id, _)| *id)
+        .collect_vec();
     assert_eq!(linked, linked_ids);
 }
 
@@ -144,11 +163,11 @@ fn test_get_linked_objects() {
 
     put!(col1, txn, id, obj1a => 1, obj1b => 2);
     put!(col2, txn, id, obj2a => 3, obj2b => 4);
-    col1.link(&mut txn, 0, false, obj1a.id, obj1a.id).unwrap();
-    col1.link(&mut txn, 0, false, obj1a.id, obj1b.id).unwrap();
-    col1.link(&mut txn, 1, false, obj1a.id, obj2a.id).unwrap();
-    col1.link(&mut txn, 1, false, obj1a.id, obj2b.id).unwrap();
-    col1.link(&mut txn, 1, false, obj1b.id, obj2b.id).unwrap();
+    col1.link(&mut txn, 0, obj1a.id, obj1a.id).unwrap();
+    col1.link(&mut txn, 0, obj1a.id, obj1b.id).unwrap();
+    col1.link(&mut txn, 1, obj1a.id, obj2a.id).unwrap();
+    col1.link(&mut txn, 1, obj1a.id, obj2b.id).unwrap();
+    col1.link(&mut txn, 1, obj1b.id, obj2b.id).unwrap();
 
     verify_linked(&mut txn, col1, 0, false, obj1a.id, vec![obj1a.id, obj1b.id]);
     verify_linked(&mut txn, col1, 0, false, obj1b.id, vec![]);
@@ -157,9 +176,224 @@ fn test_get_linked_objects() {
 
     verify_linked(&mut txn, col1, 1, false, obj1a.id, vec![obj2a.id, obj2b.id]);
     verify_linked(&mut txn, col1, 1, false, obj1b.id, vec![obj2b.id]);
-    verify_linked(&mut txn, col2, 0, true, obj2a.id, vec![obj1a.id]);
-    verify_linked(&mut txn, col2, 0, true, obj2b.id, vec![obj1a.id, obj1b.id]);
+    verify_linked(&mut txn, col1, 1, true, obj2a.id, vec![obj1a.id]);
+    verify_linked(&mut txn, col1, 1, true, obj2b.id, vec![obj1a.id, obj1b.id]);
 
     txn.abort();
     isar.close();
 }
+
+#[test]
+fn test_link_count() {
+    let col1_schema = TestObj::schema(
+        "col1",
+        &[],
+        &[
+            LinkSchema::new("a_self", "col1"),
+            LinkSchema::new("b_other", "col2"),
+        ],
+    );
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+    txn!(isar, txn);
+
+    put!(id: col1, txn, obj1a => 1, obj1b => 2);
+    put!(id: col2, txn, obj2a => 3, obj2b => 4);
+    col1.link(&mut txn, 0, obj1a.id, obj1a.id).unwrap();
+    col1.link(&mut txn, 0, obj1a.id, obj1b.id).unwrap();
+    col1.link(&mut txn, 1, obj1a.id, obj2a.id).unwrap();
+    col1.link(&mut txn, 1, obj1a.id, obj2b.id).unwrap();
+    col1.link(&mut txn, 1, obj1b.id, obj2b.id).unwrap();
+
+    assert_eq!(col1.link_count(&mut txn, 0, false, obj1a.id).unwrap(), 2);
+    assert_eq!(col1.link_count(&mut txn, 0, false, obj1b.id).unwrap(), 0);
+    assert_eq!(col1.link_count(&mut txn, 0, true, obj1b.id).unwrap(), 1);
+    assert_eq!(col1.link_count(&mut txn, 1, false, obj1a.id).unwrap(), 2);
+    assert_eq!(col1.link_count(&mut txn, 1, true, obj2a.id).unwrap(), 1);
+    assert_eq!(col1.link_count(&mut txn, 1, true, obj2b.id).unwrap(), 2);
+    assert_eq!(col1.link_count(&mut txn, 1, false, 100).unwrap(), 0);
+    assert!(col1.link_count(&mut txn, 2, false, obj1a.id).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_get_backlinks() {
+    let col1_schema = TestObj::schema("col1", &[], &[LinkSchema::new("other", "col2")]);
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+    txn!(isar, txn);
+
+    put!(id: col1, txn, obj1a => 1, obj1b => 2, obj1c => 3);
+    put!(id: col2, txn, obj2a => 4, obj2b => 5);
+    col1.link(&mut txn, 0, obj1a.id, obj2a.id).unwrap();
+    col1.link(&mut txn, 0, obj1b.id, obj2a.id).unwrap();
+    col1.link(&mut txn, 0, obj1c.id, obj2b.id).unwrap();
+
+    let mut backlinks = vec![];
+    col1.get_backlinks(&mut txn, 0, obj2a.id, |id, object| {
+        backlinks.push((id, object.as_bytes().to_vec()));
+        true
+    })
+    .unwrap();
+    let expected = vec![
+        (obj1a.id, obj1a.to_bytes(col1)),
+        (obj1b.id, obj1b.to_bytes(col1)),
+    ];
+    assert_eq!(backlinks, expected);
+
+    let mut count = 0;
+    col1.get_backlinks(&mut txn, 0, obj2b.id, |id, _| {
+        assert_eq!(id, obj1c.id);
+        count += 1;
+        false
+    })
+    .unwrap();
+    assert_eq!(count, 1);
+    let result = col1.get_backlinks(&mut txn, 1, obj2b.id, |_, _| true);
+    assert!(result.is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_link_all() {
+    let col1_schema = TestObj::schema("col1", &[], &[LinkSchema::new("other", "col2")]);
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+    txn!(isar, txn);
+
+    put!(id: col1, txn, obj1 => 1);
+    put!(id: col2, txn, obj2a => 2, obj2b => 3, obj2c => 4);
+    col1.link(&mut txn, 0, obj1.id, obj2a.id).unwrap();
+
+    // source object does not exist
+    let count = col1.link_all(&mut txn, 0, 5, &[2, 3]).unwrap();
+    assert_eq!(count, 0);
+
+    // existing links and missing targets are skipped
+    let count = col1.link_all(&mut txn, 0, obj1.id, &[2, 3, 5, 4, 3]).unwrap();
+    assert_eq!(count, 2);
+
+    verify!(txn,
+        col!(col1, obj1;
+            "other", obj1.id => obj2a.id, obj1.id => obj2b.id, obj1.id => obj2c.id
+        );
+        col!(col2, obj2a, obj2b, obj2c)
+    );
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_replace_links() {
+    let col1_schema = TestObj::schema("col1", &[], &[LinkSchema::new("other", "col2")]);
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+    txn!(isar, txn);
+
+    put!(id: col1, txn, obj1 => 1);
+    put!(id: col2, txn, obj2a => 1, obj2b => 2, obj2c => 3, obj2d => 4);
+    col1.link_all(&mut txn, 0, obj1.id, &[1, 2, 3]).unwrap();
+
+    let (added, removed) = col1.replace_links(&mut txn, 0, obj1.id, &[2, 3, 4]).unwrap();
+    assert_eq!((added, removed), (1, 1));
+
+    verify!(txn,
+        col!(col1, obj1;
+            "other", obj1.id => obj2b.id, obj1.id => obj2c.id, obj1.id => obj2d.id
+        );
+        col!(col2, obj2a, obj2b, obj2c, obj2d)
+    );
+
+    let (added, removed) = col1.replace_links(&mut txn, 0, obj1.id, &[2, 3, 4]).unwrap();
+    assert_eq!((added, removed), (0, 0));
+
+    let (added, removed) = col1.replace_links(&mut txn, 0, obj1.id, &[]).unwrap();
+    assert_eq!((added, removed), (0, 3));
+    verify!(txn, col!(col1, obj1); col!(col2, obj2a, obj2b, obj2c, obj2d));
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_link_notifies_query_watchers() {
+    let col1_schema = TestObj::schema("col1", &[], &[LinkSchema::new("l1", "col2")]);
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+
+    // query using a link filter
+    let mut qb1 = col1.new_query_builder();
+    qb1.set_filter(Filter::link(col1, 0, Filter::id(3, 3).unwrap()).unwrap());
+    let (tx1, rx1) = unbounded();
+    let handle1 = isar.watch_query(col1, qb1.build(), Box::new(move || tx1.send(true).unwrap()));
+
+    // query using a link where clause
+    let mut qb2 = col2.new_query_builder();
+    qb2.add_backlink_where_clause(col1, 0, 1).unwrap();
+    let (tx2, rx2) = unbounded();
+    let handle2 = isar.watch_query(col2, qb2.build(), Box::new(move || tx2.send(true).unwrap()));
+
+    // query without links
+    let mut qb3 = col1.new_query_builder();
+    qb3.set_filter(Filter::id(1, 2).unwrap());
+    let (tx3, rx3) = unbounded();
+    let handle3 = isar.watch_query(col1, qb3.build(), Box::new(move || tx3.send(true).unwrap()));
+
+    txn!(isar, txn);
+    put!(col1, txn, id, obj1 => 1);
+    put!(col2, txn, id, obj2 => 3);
+    txn.commit().unwrap();
+    assert_eq!((rx1.len(), rx2.len(), rx3.len()), (1, 1, 1));
+    rx1.try_recv().unwrap();
+    rx2.try_recv().unwrap();
+    rx3.try_recv().unwrap();
+
+    txn!(isar, txn);
+    col1.link(&mut txn, 0, obj1.id, obj2.id).unwrap();
+    txn.commit().unwrap();
+    assert_eq!((rx1.len(), rx2.len(), rx3.len()), (1, 1, 0));
+    rx1.try_recv().unwrap();
+    rx2.try_recv().unwrap();
+
+    txn!(isar, txn);
+    col1.unlink(&mut txn, 0, obj1.id, obj2.id).unwrap();
+    txn.commit().unwrap();
+    assert_eq!((rx1.len(), rx2.len(), rx3.len()), (1, 1, 0));
+
+    handle1.stop();
+    handle2.stop();
+    handle3.stop();
+    isar.close();
+}
+
+#[test]
+fn test_linked_collection_notifies_other_watchers() {
+    let col1_schema = TestObj::schema("col1", &[], &[LinkSchema::new("l1", "col2")]);
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+
+    // the query watcher also watches col2 because of the link filter
+    let mut qb = col1.new_query_builder();
+    qb.set_filter(Filter::link(col1, 0, Filter::id(3, 3).unwrap()).unwrap());
+    let (tx1, rx1) = unbounded();
+    let handle1 = isar.watch_query(col1, qb.build(), Box::new(move || tx1.send(true).unwrap()));
+
+    // unrelated watcher of col2 registered after the query watcher
+    let (tx2, rx2) = unbounded();
+    let handle2 = isar.watch_collection(col2, Box::new(move || tx2.send(true).unwrap()));
+
+    txn!(isar, txn);
+    put!(col1, txn, id, _obj1 => 1);
+    put!(col2, txn, id, _obj2 => 3);
+    txn.commit().unwrap();
+    assert_eq!((rx1.len(), rx2.len()), (1, 1));
+
+    handle1.stop();
+    handle2.stop();
+    isar.close();
+}
@@ -1,4 +1,14 @@
 use crate::common::test_obj::TestObj;
+use crate::common::util::assert_find;
+use crossbeam_channel::unbounded;
+use isar_core::collection::OnConflict;
+use isar_core::error::IsarError;
+use isar_core::index::index_key::IndexKey;
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::index_schema::IndexSchema;
+use isar_core::schema::property_schema::PropertySchema;
 
 mod common;
 
@@ -167,3 +177,409 @@ fn test_put_calls_notifiers() {
     isar.close();
 }
 */
+
+#[test]
+fn test_count() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    assert_eq!(col.count(&mut txn).unwrap(), 0);
+
+    put!(col, txn, id, obj1 => 1, obj2 => 2, obj3 => 3);
+    assert_eq!(col.count(&mut txn).unwrap(), 3);
+
+    // replacing an existing object does not change the count
+    obj2.save(&mut txn, col);
+    assert_eq!(col.count(&mut txn).unwrap(), 3);
+
+    col.delete(&mut txn, obj1.id).unwrap();
+    assert_eq!(col.count(&mut txn).unwrap(), 2);
+    verify!(txn, col, obj2, obj3);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_put_all() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let obj1 = TestObj::default(5);
+    let obj2 = TestObj::default(3);
+    let mut obj3 = TestObj::default(6);
+    obj3.int = 3;
+    let bytes1 = obj1.to_bytes(col);
+    let bytes2 = obj2.to_bytes(col);
+    let bytes3 = obj3.to_bytes(col);
+
+    let objects = vec![
+        (Some(5), bytes1.as_slice()),
+        (Some(3), bytes2.as_slice()),
+        (None, bytes3.as_slice()),
+    ];
+    let ids = col.put_all(&mut txn, &objects, OnConflict::Abort).unwrap();
+    assert_eq!(ids, vec![5, 3, 6]);
+    verify!(txn, col, obj1, obj2, obj3);
+
+    // invalid objects are rejected before anything is written
+    let objects = vec![(Some(7), bytes1.as_slice()), (Some(8), &[1u8][..])];
+    let result = col.put_all(&mut txn, &objects, OnConflict::Abort);
+    let error = IsarError::IllegalArg {
+        message: "Object is too small: expected at least 2 bytes but got 1".to_string(),
+    };
+    assert_eq!(result, Err(error));
+    verify!(txn, col, obj1, obj2, obj3);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_put_all_with_progress() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let obj1 = TestObj::default(1);
+    let obj2 = TestObj::default(2);
+    let obj3 = TestObj::default(3);
+    let obj4 = TestObj::default(4);
+    let obj5 = TestObj::default(5);
+    let bytes: Vec<Vec<u8>> = [&obj1, &obj2, &obj3, &obj4, &obj5]
+        .iter()
+        .map(|o| o.to_bytes(col))
+        .collect();
+    let objects: Vec<(Option<i64>, &[u8])> = bytes.iter().map(|b| (None, b.as_slice())).collect();
+
+    let mut counts = vec![];
+    let ids = col
+        .put_all_with_progress(&mut txn, &objects, OnConflict::Abort, 2, |count| {
+            counts.push(count);
+            true
+        })
+        .unwrap();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    assert_eq!(counts, vec![2, 4, 5]);
+    verify!(txn, col, obj1, obj2, obj3, obj4, obj5);
+
+    assert!(col
+        .put_all_with_progress(&mut txn, &objects, OnConflict::Abort, 0, |_| true)
+        .is_err());
+    txn.abort();
+
+    txn!(isar, txn);
+    let result =
+        col.put_all_with_progress(&mut txn, &objects, OnConflict::Abort, 2, |count| count < 4);
+    assert_eq!(result, Err(IsarError::Cancelled {}));
+    assert!(col.count(&mut txn).is_err());
+    txn.abort();
+
+    isar.close();
+}
+
+#[test]
+fn test_put_if_changed() {
+    isar!(isar, col => TestObj::default_schema());
+    let mut obj = TestObj::default(1);
+    obj.int = 1;
+    let bytes = obj.to_bytes(col);
+    txn!(isar, txn);
+    let object = IsarObject::from_bytes(&bytes);
+    assert!(col
+        .put_if_changed(&mut txn, 1, object, OnConflict::Abort)
+        .unwrap());
+    txn.commit().unwrap();
+
+    let (tx, rx) = unbounded();
+    let handle = isar.watch_object(col, 1, Box::new(move |_| tx.send(true).unwrap()));
+
+    // the same bytes are not written again
+    txn!(isar, txn);
+    let object = IsarObject::from_bytes(&bytes);
+    assert!(!col
+        .put_if_changed(&mut txn, 1, object, OnConflict::Abort)
+        .unwrap());
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 0);
+
+    obj.int = 2;
+    let bytes = obj.to_bytes(col);
+    txn!(isar, txn);
+    let object = IsarObject::from_bytes(&bytes);
+    assert!(col
+        .put_if_changed(&mut txn, 1, object, OnConflict::Abort)
+        .unwrap());
+    verify!(txn, col, obj);
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 1);
+
+    handle.stop();
+    isar.close();
+}
+
+#[test]
+fn test_put_by_index() {
+    let indexes = vec![
+        IndexSchema::new("int", vec![TestObj::int_index()], true),
+        IndexSchema::new("byte", vec![TestObj::byte_index()], false),
+    ];
+    isar!(isar, col => TestObj::schema("obj", &indexes, &[]));
+    txn!(isar, txn);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.int = 5;
+    obj1.save(&mut txn, col);
+
+    // existing object with the same index key is replaced
+    let mut obj2 = TestObj::default(1);
+    obj2.int = 5;
+    obj2.string = Some("updated".to_string());
+    let bytes = obj2.to_bytes(col);
+    let id = col
+        .put_by_index(
+            &mut txn,
+            0,
+            IsarObject::from_bytes(&bytes),
+            OnConflict::Abort,
+        )
+        .unwrap();
+    assert_eq!(id, 1);
+    verify!(txn, col, obj2);
+
+    // new index key creates a new object
+    let mut obj3 = TestObj::default(2);
+    obj3.int = 6;
+    let bytes = obj3.to_bytes(col);
+    let id = col
+        .put_by_index(
+            &mut txn,
+            0,
+            IsarObject::from_bytes(&bytes),
+            OnConflict::Abort,
+        )
+        .unwrap();
+    assert_eq!(id, 2);
+    verify!(txn, col, obj2, obj3);
+
+    // non-unique indexes are not supported
+    assert!(col
+        .put_by_index(
+            &mut txn,
+            1,
+            IsarObject::from_bytes(&bytes),
+            OnConflict::Abort
+        )
+        .is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_put_on_conflict() {
+    let indexes = vec![IndexSchema::new("int", vec![TestObj::int_index()], true)];
+    isar!(isar, col => TestObj::schema("obj", &indexes, &[]));
+    txn!(isar, txn);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.int = 5;
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.int = 5;
+    let bytes = obj2.to_bytes(col);
+    let object = IsarObject::from_bytes(&bytes);
+
+    // ignore keeps the existing object
+    let id = col
+        .put(&mut txn, Some(2), object, OnConflict::Ignore)
+        .unwrap();
+    assert_eq!(id, 1);
+    verify!(txn, col, obj1);
+
+    // replace deletes the existing object
+    let id = col
+        .put(&mut txn, Some(2), object, OnConflict::Replace)
+        .unwrap();
+    assert_eq!(id, 2);
+    verify!(txn, col, obj2);
+
+    // abort returns the conflicting object
+    let bytes = obj1.to_bytes(col);
+    let object = IsarObject::from_bytes(&bytes);
+    let result = col.put(&mut txn, Some(1), object, OnConflict::Abort);
+    assert_eq!(
+        result,
+        Err(IsarError::UniqueViolation {
+            index: "int".to_string(),
+            existing_id: 2
+        })
+    );
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_put_unique_case_insensitive() {
+    for hash in [false, true] {
+        let index = IndexSchema::new("string", vec![TestObj::string_index(hash, false)], true);
+        isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+        txn!(isar, txn);
+
+        let mut obj1 = TestObj::default(1);
+        obj1.string = Some("Foo".to_string());
+        obj1.save(&mut txn, col);
+
+        let mut obj2 = TestObj::default(2);
+        obj2.string = Some("foo".to_string());
+        let bytes = obj2.to_bytes(col);
+        let object = IsarObject::from_bytes(&bytes);
+
+        let id = col
+            .put(&mut txn, Some(2), object, OnConflict::Ignore)
+            .unwrap();
+        assert_eq!(id, 1);
+
+        let id = col
+            .put(&mut txn, Some(2), object, OnConflict::Replace)
+            .unwrap();
+        assert_eq!(id, 2);
+        verify!(txn, col, obj2);
+
+        let bytes = obj1.to_bytes(col);
+        let object = IsarObject::from_bytes(&bytes);
+        let result = col.put(&mut txn, Some(1), object, OnConflict::Abort);
+        assert_eq!(
+            result,
+            Err(IsarError::UniqueViolation {
+                index: "string".to_string(),
+                existing_id: 2
+            })
+        );
+
+        txn.abort();
+        isar.close();
+    }
+}
+
+#[test]
+fn test_put_unique_long_strings() {
+    let index = IndexSchema::new("string", vec![TestObj::string_index(false, true)], true);
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    // both strings share the part of the value that fits into the index key
+    let prefix = "a".repeat(1024);
+    let mut obj1 = TestObj::default(1);
+    obj1.string = Some(format!("{}b", prefix));
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.string = Some(format!("{}c", prefix));
+    obj2.save(&mut txn, col);
+    verify!(txn, col, obj1, obj2);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.string = obj1.string.clone();
+    let bytes = obj3.to_bytes(col);
+    let object = IsarObject::from_bytes(&bytes);
+    let result = col.put(&mut txn, Some(3), object, OnConflict::Abort);
+    assert_eq!(
+        result,
+        Err(IsarError::UniqueViolation {
+            index: "string".to_string(),
+            existing_id: 1
+        })
+    );
+
+    let mut key = IndexKey::new();
+    key.add_string(obj2.string.as_deref(), true);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj2]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_put_invalid_object() {
+    fn read_u32(bytes: &[u8], offset: usize) -> usize {
+        let mut value = [0; 4];
+        value.copy_from_slice(&bytes[offset..offset + 4]);
+        u32::from_le_bytes(value) as usize
+    }
+
+    fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let mut obj = TestObj::default(1);
+    obj.string = Some("hello".to_string());
+    obj.int_list = Some(vec![1, 2]);
+    let bytes = obj.to_bytes(col);
+    let string = TestObj::get_prop(col, DataType::String).offset;
+    let int_list = TestObj::get_prop(col, DataType::IntList).offset;
+    let string_offset = read_u32(&bytes, string);
+
+    let mut into_static = bytes.clone();
+    write_u32(&mut into_static, string, 2);
+    let mut out_of_range = bytes.clone();
+    write_u32(&mut out_of_range, int_list + 4, u32::MAX);
+    let mut overlapping = bytes.clone();
+    write_u32(&mut overlapping, int_list, string_offset as u32);
+    let mut invalid_utf8 = bytes.clone();
+    invalid_utf8[string_offset] = 0xFF;
+
+    for invalid in [&into_static, &out_of_range, &overlapping, &invalid_utf8] {
+        let object = IsarObject::from_bytes(invalid);
+        let result = col.put(&mut txn, Some(1), object, OnConflict::Abort);
+        assert!(matches!(result, Err(IsarError::IllegalArg { .. })));
+    }
+    verify!(txn, col);
+
+    let object = IsarObject::from_bytes(&bytes);
+    col.put(&mut txn, Some(1), object, OnConflict::Abort)
+        .unwrap();
+    verify!(txn, col, obj);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_put_object_too_small() {
+    let long = PropertySchema::new("long", DataType::Long);
+    let schema = CollectionSchema::new("col", vec![long], vec![], vec![]);
+    isar!(isar, col => schema);
+    txn!(isar, txn);
+
+    let cases: [(&[u8], &str); 2] = [
+        (
+            &[10, 0, 1, 2, 3, 4],
+            "Object is too small: expected a static size of 10 bytes but got 6 bytes",
+        ),
+        (
+            &[6, 0, 1, 2, 3, 4],
+            "Property exceeds the static section of the object: expected a static size of at \
+            least 10 bytes but got 6 bytes",
+        ),
+    ];
+    for (bytes, message) in cases {
+        let object = IsarObject::from_bytes(bytes);
+        let result = col.put(&mut txn, Some(1), object, OnConflict::Abort);
+        let error = IsarError::IllegalArg {
+            message: message.to_string(),
+        };
+        assert_eq!(result, Err(error));
+    }
+    verify!(txn, col);
+
+    txn.abort();
+    isar.close();
+}
@@ -1,3 +1,148 @@
+use isar_core::error::IsarError;
+use isar_core::index::index_key::IndexKey;
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::{IsarObject, Property};
+use isar_core::query::filter::Filter;
+use isar_core::query::query_plan::WhereClausePlan;
+use isar_core::query::Sort;
+use isar_core::schema::link_schema::LinkSchema;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crate::common::test_obj::TestObj;
+use crate::common::util::assert_find;
+
+mod common;
+
+#[test]
+fn test_sort_multiple_properties() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let int = TestObj::get_prop(col, DataType::Int);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.byte = 2;
+    obj1.int = 1;
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.byte = 1;
+    obj2.int = 1;
+    obj2.save(&mut txn, col);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.byte = 2;
+    obj3.int = 3;
+    obj3.save(&mut txn, col);
+
+    let mut obj4 = TestObj::default(4);
+    obj4.byte = 2;
+    obj4.int = 1;
+    obj4.save(&mut txn, col);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Ascending).unwrap();
+    qb.add_sort(int, Sort::Descending).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj2, &obj3, &obj1, &obj4]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    let q = qb.build();
+    assert_find(&mut txn, col, q.clone(), &[&obj1, &obj3, &obj4, &obj2]);
+    assert_eq!(q.count(&mut txn).unwrap(), 4);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_distinct_multiple_properties() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let string = TestObj::get_prop(col, DataType::String);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.byte = 1;
+    obj1.string = Some("hello".to_string());
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.byte = 1;
+    obj2.string = Some("HELLO".to_string());
+    obj2.save(&mut txn, col);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.byte = 2;
+    obj3.string = Some("hello".to_string());
+    obj3.save(&mut txn, col);
+
+    let mut qb = col.new_query_builder();
+    qb.add_distinct(byte, false);
+    qb.add_distinct(string, true);
+    let q = qb.build();
+    assert_find(&mut txn, col, q.clone(), &[&obj1, &obj2, &obj3]);
+    assert_eq!(q.count(&mut txn).unwrap(), 3);
+
+    let mut qb = col.new_query_builder();
+    qb.add_distinct(byte, false);
+    qb.add_distinct(string, false);
+    let q = qb.build();
+    assert_find(&mut txn, col, q.clone(), &[&obj1, &obj3]);
+    assert_eq!(q.count(&mut txn).unwrap(), 2);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    qb.add_distinct(string, false);
+    assert_find(&mut txn, col, qb.build(), &[&obj3]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_offset_limit() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let int = TestObj::get_prop(col, DataType::Int);
+    put!(col, txn, int, obj1 => 5, obj2 => 4, obj3 => 3, obj4 => 2, obj5 => 1);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::int(int, 2, 5).unwrap());
+    qb.set_offset(1);
+    qb.set_limit(2);
+    let q = qb.build();
+    assert_find(&mut txn, col, q.clone(), &[&obj2, &obj3]);
+    assert_eq!(q.count(&mut txn).unwrap(), 4);
+
+    // aggregations count the objects passed to find_while which applies the offset and limit
+    let mut count = 0;
+    q.find_while(&mut txn, |_, _| {
+        count += 1;
+        true
+    })
+    .unwrap();
+    assert_eq!(count, 2);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::int(int, 2, 5).unwrap());
+    qb.add_sort(int, Sort::Ascending).unwrap();
+    qb.set_offset(1);
+    qb.set_limit(2);
+    assert_find(&mut txn, col, qb.build(), &[&obj3, &obj2]);
+
+    let mut qb = col.new_query_builder();
+    qb.set_offset(10);
+    assert_find(&mut txn, col, qb.build(), &[]);
+
+    txn.abort();
+    isar.close();
+}
+
 /*#[test]
 fn test_filter_unsorted() -> Result<()> {
     let isar = fill_int_col(vec![5, 4, 4, 3, 2, 2, 1], false);
@@ -86,3 +231,352 @@ fn test_distinct_sorted() -> Result<()> {
     Ok(())
 }
 */
+
+#[test]
+fn test_find_first() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    put!(col, txn, byte, obj1 => 3, obj2 => 1, obj3 => 2);
+
+    let q = col.new_query_builder().build();
+    let (id, object) = q.find_first(&mut txn).unwrap().unwrap();
+    assert_eq!(id, obj1.id);
+    assert_eq!(TestObj::from_object(col, object), obj1);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Ascending).unwrap();
+    let (id, _) = qb.build().find_first(&mut txn).unwrap().unwrap();
+    assert_eq!(id, obj2.id);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 2, 3).unwrap());
+    qb.add_sort(byte, Sort::Ascending).unwrap();
+    let (id, _) = qb.build().find_first(&mut txn).unwrap().unwrap();
+    assert_eq!(id, obj3.id);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 5, 6).unwrap());
+    assert!(qb.build().find_first(&mut txn).unwrap().is_none());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_find_all_property() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let string = TestObj::get_prop(col, DataType::String);
+    let mut obj1 = TestObj::default(1);
+    obj1.string = Some("a".to_string());
+    obj1.save(&mut txn, col);
+    let obj2 = TestObj::default(2);
+    obj2.save(&mut txn, col);
+    let mut obj3 = TestObj::default(3);
+    obj3.string = Some("c".to_string());
+    obj3.save(&mut txn, col);
+
+    let projected = Property::new(DataType::String, 2);
+    let mut values = vec![];
+    let q = col.new_query_builder().build();
+    q.find_all_property(&mut txn, string, |id, object| {
+        let value = object.read_string(projected).map(|s| s.to_string());
+        values.push((id, value));
+        id < 2
+    })
+    .unwrap();
+    assert_eq!(values, vec![(1, Some("a".to_string())), (2, None)]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_delete_all() {
+    let col_schema = TestObj::schema(
+        "obj",
+        &TestObj::default_indexes(),
+        &[LinkSchema::new("link", "obj")],
+    );
+    isar!(isar, col => col_schema);
+    txn!(isar, txn);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    put!(col, txn, byte, obj1 => 1, obj2 => 2, obj3 => 2, obj4 => 3);
+    col.link_all(&mut txn, 0, obj1.id, &[obj2.id, obj4.id]).unwrap();
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 2, 2).unwrap());
+    let q = qb.build();
+    assert_eq!(q.delete_all(&mut txn, col).unwrap(), 2);
+    assert_eq!(q.delete_all(&mut txn, col).unwrap(), 0);
+
+    verify!(txn, col, obj1, obj4; "link", obj1.id => obj4.id);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_explain() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let q = col.new_query_builder().build();
+    let plan = q.explain();
+    assert_eq!(
+        plan.where_clauses,
+        vec![WhereClausePlan::PrimaryScan { ascending: true }]
+    );
+    assert!(!plan.post_filter);
+    assert!(!plan.sort_buffer);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let mut key = IndexKey::new();
+    key.add_byte(5);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+        .unwrap();
+    qb.set_filter(Filter::byte(byte, 1, 2).unwrap());
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    let plan = qb.build().explain();
+    assert_eq!(plan.where_clauses.len(), 1);
+    assert!(matches!(
+        &plan.where_clauses[0],
+        WhereClausePlan::Index { lower_key, upper_key, .. } if lower_key == &vec![5] && upper_key == &vec![5]
+    ));
+    assert!(plan.post_filter);
+    assert!(plan.sort_buffer);
+    assert!(plan.to_json().contains("\"type\":\"index\""));
+
+    isar.close();
+}
+
+#[test]
+fn test_find_after() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    put!(id: col, txn, _o1 => 1, _o2 => 2, _o3 => 3, _o4 => 4, _o5 => 5, _o6 => 6);
+
+    fn ids(results: Vec<(i64, IsarObject)>) -> Vec<i64> {
+        results.into_iter().map(|(id, _)| id).collect()
+    }
+
+    let q = col.new_query_builder().build();
+    assert_eq!(ids(q.find_after(&mut txn, i64::MIN, 2).unwrap()), vec![1, 2]);
+    assert_eq!(ids(q.find_after(&mut txn, 2, 2).unwrap()), vec![3, 4]);
+    assert_eq!(ids(q.find_after(&mut txn, 4, 5).unwrap()), vec![5, 6]);
+    assert!(q.find_after(&mut txn, 6, 2).unwrap().is_empty());
+    assert!(q.find_after(&mut txn, i64::MAX, 2).unwrap().is_empty());
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(5, 2).unwrap();
+    let q = qb.build();
+    assert_eq!(ids(q.find_after(&mut txn, i64::MAX, 2).unwrap()), vec![5, 4]);
+    assert_eq!(ids(q.find_after(&mut txn, 4, 2).unwrap()), vec![3, 2]);
+    assert!(q.find_after(&mut txn, 2, 2).unwrap().is_empty());
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::not(Filter::id(3, 4).unwrap()));
+    let q = qb.build();
+    assert_eq!(ids(q.find_after(&mut txn, 1, 2).unwrap()), vec![2, 5]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Ascending).unwrap();
+    assert!(qb.build().find_after(&mut txn, 1, 2).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_any() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 2, 2).unwrap());
+    let q = qb.build();
+    assert!(!q.any(&mut txn).unwrap());
+    assert!(!col.new_query_builder().build().any(&mut txn).unwrap());
+
+    put!(col, txn, byte, _obj1 => 1, _obj2 => 2, _obj3 => 3);
+    assert!(q.any(&mut txn).unwrap());
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 5, 10).unwrap());
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    assert!(!qb.build().any(&mut txn).unwrap());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_find_all_cancellable() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    put!(id: col, txn, _obj1 => 1, _obj2 => 2, _obj3 => 3, _obj4 => 4);
+
+    let q = col.new_query_builder().build();
+    let cancel = AtomicBool::new(false);
+    let mut ids = vec![];
+    q.find_all_cancellable(&mut txn, &cancel, |id, _| {
+        ids.push(id);
+        true
+    })
+    .unwrap();
+    assert_eq!(ids, vec![1, 2, 3, 4]);
+
+    let mut ids = vec![];
+    let result = q.find_all_cancellable(&mut txn, &cancel, |id, _| {
+        ids.push(id);
+        if id == 2 {
+            cancel.store(true, Ordering::SeqCst);
+        }
+        true
+    });
+    assert_eq!(result, Err(IsarError::Cancelled {}));
+    assert_eq!(ids, vec![1, 2]);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    let result = qb.build().find_all_cancellable(&mut txn, &cancel, |_, _| true);
+    assert_eq!(result, Err(IsarError::Cancelled {}));
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_find_all_batched() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    for id in 1..=10_000 {
+        TestObj::default(id).save(&mut txn, col);
+    }
+
+    let q = col.new_query_builder().build();
+    for (batch_size, calls) in vec![(1, 10_000), (1000, 10), (3000, 4), (20_000, 1)] {
+        let mut call_count = 0;
+        let mut ids = vec![];
+        q.find_all_batched(&mut txn, batch_size, |batch| {
+            call_count += 1;
+            assert!(!batch.is_empty() && batch.len() <= batch_size);
+            ids.extend(batch.iter().map(|(id, _)| *id));
+        })
+        .unwrap();
+        assert_eq!(call_count, calls);
+        assert_eq!(ids, (1..=10_000).collect::<Vec<_>>());
+    }
+
+    let mut called = false;
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 1, 1).unwrap());
+    qb.build()
+        .find_all_batched(&mut txn, 10, |_| called = true)
+        .unwrap();
+    assert!(!called);
+
+    let result = q.find_all_batched(&mut txn, 0, |_| {});
+    assert!(result.is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_max_results() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let int = TestObj::get_prop(col, DataType::Int);
+    put!(col, txn, int, obj1 => 5, obj2 => 4, obj3 => 3, obj4 => 2, _obj5 => 1);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::int(int, 2, 5).unwrap());
+    qb.set_max_results(3);
+    let q = qb.build();
+    let result = q.find_all_vec(&mut txn);
+    assert_eq!(result, Err(IsarError::ResultTooLarge { max_results: 3 }));
+    assert_eq!(q.count(&mut txn).unwrap(), 4);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::int(int, 2, 5).unwrap());
+    qb.add_sort(int, Sort::Ascending).unwrap();
+    qb.set_max_results(3);
+    let result = qb.build().find_all_vec(&mut txn);
+    assert_eq!(result, Err(IsarError::ResultTooLarge { max_results: 3 }));
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::int(int, 2, 5).unwrap());
+    qb.set_max_results(4);
+    assert_find(&mut txn, col, qb.build(), &[&obj1, &obj2, &obj3, &obj4]);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::int(int, 2, 5).unwrap());
+    qb.set_limit(3);
+    qb.set_max_results(3);
+    assert_find(&mut txn, col, qb.build(), &[&obj1, &obj2, &obj3]);
+
+    // only the find_all methods check the max results
+    let mut count = 0;
+    q.find_while(&mut txn, |_, _| {
+        count += 1;
+        true
+    })
+    .unwrap();
+    assert_eq!(count, 4);
+    assert_eq!(q.delete_all(&mut txn, col).unwrap(), 4);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_reuse_query() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    let int = TestObj::get_prop(col, DataType::Int);
+    put!(col, txn, int, _obj1 => 1, _obj2 => 2, _obj3 => 3);
+    txn.commit().unwrap();
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::int(int, 2, 5).unwrap());
+    let q = qb.build();
+
+    for _ in 0..3 {
+        let mut txn = isar.begin_txn(false, false).unwrap();
+        assert_eq!(q.count(&mut txn).unwrap(), 2);
+        txn.abort();
+    }
+
+    txn!(isar, txn);
+    put!(col, txn, int, _obj4 => 4);
+    txn.commit().unwrap();
+
+    let handles = (0..3)
+        .map(|_| {
+            let isar = isar.clone();
+            let q = q.clone();
+            thread::spawn(move || {
+                let mut txn = isar.begin_txn(false, false).unwrap();
+                let count = q.count(&mut txn).unwrap();
+                txn.abort();
+                count
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 3);
+    }
+
+    isar.close();
+}
@@ -2,8 +2,11 @@ use std::vec;
 
 use isar_core::collection::IsarCollection;
 use isar_core::object::data_type::DataType;
-use isar_core::query::filter::Filter;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::query::filter::{Filter, ListElementValue};
+use isar_core::schema::link_schema::LinkSchema;
 use isar_core::txn::IsarTxn;
+use itertools::Itertools;
 
 use crate::common::test_obj::TestObj;
 
@@ -175,6 +178,271 @@ fn test_double_filter() {
     isar.close();
 }
 
+#[test]
+fn test_greater_less_than_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let int = TestObj::get_prop(col, DataType::Int);
+    let long = TestObj::get_prop(col, DataType::Long);
+    let double = TestObj::get_prop(col, DataType::Double);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.int = 1;
+    obj1.double = 1.0;
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.int = 2;
+    obj2.double = 2.0;
+    obj2.save(&mut txn, col);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.int = 3;
+    obj3.double = 3.0;
+    obj3.save(&mut txn, col);
+
+    expect_filter(&mut txn, col, Filter::int_gt(int, 2).unwrap(), &[&obj3]);
+    expect_filter(&mut txn, col, Filter::int_lt(int, 2).unwrap(), &[&obj1]);
+    expect_filter(&mut txn, col, Filter::int_gt(int, i32::MAX).unwrap(), &[]);
+    expect_filter(&mut txn, col, Filter::int_lt(int, i32::MIN).unwrap(), &[]);
+
+    expect_filter(&mut txn, col, Filter::long_gt(long, 1).unwrap(), &[&obj2, &obj3]);
+    expect_filter(&mut txn, col, Filter::long_lt(long, 3).unwrap(), &[&obj1, &obj2]);
+    expect_filter(&mut txn, col, Filter::long_gt(long, i64::MAX).unwrap(), &[]);
+
+    expect_filter(&mut txn, col, Filter::double_gt(double, 2.0).unwrap(), &[&obj3]);
+    expect_filter(&mut txn, col, Filter::double_lt(double, 2.0).unwrap(), &[&obj1]);
+
+    let string = TestObj::get_prop(col, DataType::String);
+    assert!(Filter::int_gt(string, i32::MAX).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_float_double_between_bounds_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let float = TestObj::get_prop(col, DataType::Float);
+    let double = TestObj::get_prop(col, DataType::Double);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.float = 0.0;
+    obj1.double = 0.0;
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.float = 0.5;
+    obj2.double = 0.5;
+    obj2.save(&mut txn, col);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.float = 1.0;
+    obj3.double = 1.0;
+    obj3.save(&mut txn, col);
+
+    let results = vec![
+        (false, false, vec![&obj2]),
+        (true, false, vec![&obj1, &obj2]),
+        (false, true, vec![&obj2, &obj3]),
+        (true, true, vec![&obj1, &obj2, &obj3]),
+    ];
+    for (include_lower, include_upper, objects) in results {
+        expect_filter(
+            &mut txn,
+            col,
+            Filter::float_between(float, 0.0, include_lower, 1.0, include_upper).unwrap(),
+            &objects,
+        );
+        expect_filter(
+            &mut txn,
+            col,
+            Filter::double_between(double, 0.0, include_lower, 1.0, include_upper).unwrap(),
+            &objects,
+        );
+    }
+
+    expect_filter(
+        &mut txn,
+        col,
+        Filter::double_between(double, 1.0, true, 1.0, true).unwrap(),
+        &[&obj3],
+    );
+    expect_filter(
+        &mut txn,
+        col,
+        Filter::double_between(double, 1.0, false, 1.0, true).unwrap(),
+        &[],
+    );
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_float_double_is_nan_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let float = TestObj::get_prop(col, DataType::Float);
+    let double = TestObj::get_prop(col, DataType::Double);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.float = -f32::NAN;
+    obj1.double = -f64::NAN;
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.float = IsarObject::NULL_FLOAT;
+    obj2.double = IsarObject::NULL_DOUBLE;
+    obj2.save(&mut txn, col);
+
+    TestObj::default(3).save(&mut txn, col);
+
+    let mut find_ids = |filter: Filter| {
+        let mut qb = col.new_query_builder();
+        qb.set_filter(filter);
+        let result = qb.build().find_all_vec(&mut txn).unwrap();
+        result
+            .into_iter()
+            .map(|(_, o)| TestObj::from_object(col, o).id)
+            .collect_vec()
+    };
+
+    assert_eq!(find_ids(Filter::float_is_nan(float).unwrap()), vec![1]);
+    assert_eq!(find_ids(Filter::double_is_nan(double).unwrap()), vec![1]);
+    assert_eq!(find_ids(Filter::null(float)), vec![1, 2]);
+    assert_eq!(find_ids(Filter::null(double)), vec![1, 2]);
+    assert!(Filter::double_is_nan(float).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_int_in_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::Int);
+
+    put!(col, txn, int, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => i32::MIN);
+
+    let results = vec![
+        (vec![], vec![]),
+        (vec![5], vec![]),
+        (vec![3, 1, 3], vec![&obj1, &obj3]),
+        (vec![i32::MIN, 2], vec![&obj2, &obj4]),
+    ];
+    for (values, objects) in results {
+        expect_filter(&mut txn, col, Filter::int_in(p, values).unwrap(), &objects);
+    }
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_string_in_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::String);
+
+    put!(col, txn, string,
+        obj1 => None,
+        obj2 => Some("a".to_string()),
+        obj3 => Some("B".to_string()),
+        obj4 => Some("c".to_string())
+    );
+
+    let results = vec![
+        (vec![], false, vec![]),
+        (vec![Some("b")], true, vec![]),
+        (vec![Some("b")], false, vec![&obj3]),
+        (vec![None, Some("C"), Some("a")], false, vec![&obj1, &obj2, &obj4]),
+        (vec![None, Some("C"), Some("a")], true, vec![&obj1, &obj2]),
+    ];
+    for (values, case_sensitive, objects) in results {
+        expect_filter(
+            &mut txn,
+            col,
+            Filter::string_in(p, values, case_sensitive).unwrap(),
+            &objects,
+        );
+    }
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_int_not_in_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::Int);
+
+    put!(col, txn, int, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => i32::MIN);
+
+    let null = IsarObject::NULL_INT;
+    let large = (-10_000..10_000).filter(|v| *v != 2).collect_vec();
+    let results = vec![
+        (vec![], false, vec![&obj1, &obj2, &obj3]),
+        (vec![], true, vec![&obj1, &obj2, &obj3, &obj4]),
+        (vec![3, 1, 3], false, vec![&obj2]),
+        (vec![null, 2], false, vec![&obj1, &obj3]),
+        (vec![null, 2], true, vec![&obj1, &obj3, &obj4]),
+        (large, false, vec![&obj2]),
+    ];
+    for (values, include_null, objects) in results {
+        let filter = Filter::int_not_in(p, values, include_null).unwrap();
+        expect_filter(&mut txn, col, filter, &objects);
+    }
+
+    assert!(Filter::long_not_in(p, vec![1], false).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_string_not_in_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::String);
+
+    put!(col, txn, string,
+        obj1 => None,
+        obj2 => Some("a".to_string()),
+        obj3 => Some("B".to_string()),
+        obj4 => Some("c".to_string())
+    );
+
+    let large = (0..10_000).map(|i| i.to_string()).collect_vec();
+    let mut large = large.iter().map(|s| s.as_str()).collect_vec();
+    large.push("a");
+    let results = vec![
+        (vec![], false, false, vec![&obj2, &obj3, &obj4]),
+        (vec!["b"], true, false, vec![&obj2, &obj3, &obj4]),
+        (vec!["b"], false, true, vec![&obj1, &obj2, &obj4]),
+        (vec!["C", "a"], false, false, vec![&obj3]),
+        (vec!["C", "a"], true, true, vec![&obj1, &obj3, &obj4]),
+        (large, true, false, vec![&obj3, &obj4]),
+    ];
+    for (values, case_sensitive, include_null, objects) in results {
+        let filter = Filter::string_not_in(p, values, case_sensitive, include_null).unwrap();
+        expect_filter(&mut txn, col, filter, &objects);
+    }
+
+    txn.abort();
+    isar.close();
+}
+
 #[test]
 fn test_string_filter() {
     isar!(isar, col =>TestObj::default_schema());
@@ -295,6 +563,46 @@ fn test_string_starts_ends_with_filter() {
     isar.close();
 }
 
+#[test]
+fn test_string_contains_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::String);
+
+    put!(col, txn, string,
+        obj1 => None,
+        obj2 => Some("hello".to_string()),
+        obj3 => Some("hello World".to_string()),
+        obj4 => Some("Hello WORLD".to_string())
+    );
+
+    let contains_result = vec![
+        ("", false, vec![&obj2, &obj3, &obj4]),
+        ("", true, vec![&obj2, &obj3, &obj4]),
+        (" ", false, vec![&obj3, &obj4]),
+        ("ell", true, vec![&obj2, &obj3, &obj4]),
+        ("ELL", false, vec![&obj2, &obj3, &obj4]),
+        ("ELL", true, vec![]),
+        ("o w", false, vec![&obj3, &obj4]),
+        ("o W", true, vec![&obj3, &obj4]),
+        ("World", true, vec![&obj3]),
+        ("world!", false, vec![]),
+    ];
+
+    for (value, case_sensitive, objects) in contains_result {
+        expect_filter(
+            &mut txn,
+            col,
+            Filter::string_contains(p, value, case_sensitive).unwrap(),
+            &objects,
+        );
+    }
+
+    txn.abort();
+    isar.close();
+}
+
 #[test]
 fn test_string_matches_filter() {
     isar!(isar, col =>TestObj::default_schema());
@@ -335,6 +643,186 @@ fn test_string_matches_filter() {
     isar.close();
 }
 
+#[test]
+fn test_string_matches_regex_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::String);
+
+    put!(col, txn, string,
+        obj1 => None,
+        obj2 => Some("ab12abc".to_string()),
+        obj3 => Some("ÄBB11".to_string()),
+        obj4 => Some("äbbaa".to_string())
+    );
+
+    let regex_result = vec![
+        ("^ab", false, vec![&obj2]),
+        ("[0-9]+$", true, vec![&obj3]),
+        ("\\d{2}", true, vec![&obj2, &obj3]),
+        ("^äbb", true, vec![&obj4]),
+        ("^äbb", false, vec![&obj3, &obj4]),
+        ("x", false, vec![]),
+    ];
+
+    for (value, case_sensitive, objects) in regex_result {
+        expect_filter(
+            &mut txn,
+            col,
+            Filter::string_matches_regex(p, value, case_sensitive).unwrap(),
+            &objects,
+        );
+    }
+
+    assert!(Filter::string_matches_regex(p, "(ab", true).is_err());
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    assert!(Filter::string_matches_regex(byte, "ab", true).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_null_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.int = IsarObject::NULL_INT;
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.int = 5;
+    obj2.string = Some("hello".to_string());
+    obj2.int_list = Some(vec![]);
+    obj2.save(&mut txn, col);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.int = IsarObject::NULL_INT;
+    obj3.string = Some("".to_string());
+    obj3.int_list = Some(vec![IsarObject::NULL_INT]);
+    obj3.save(&mut txn, col);
+
+    let int = TestObj::get_prop(col, DataType::Int);
+    let string = TestObj::get_prop(col, DataType::String);
+    let int_list = TestObj::get_prop(col, DataType::IntList);
+
+    let results = vec![
+        (int, vec![&obj1, &obj3], vec![&obj2]),
+        (string, vec![&obj1], vec![&obj2, &obj3]),
+        (int_list, vec![&obj1], vec![&obj2, &obj3]),
+    ];
+    for (property, null, not_null) in results {
+        expect_filter(&mut txn, col, Filter::null(property), &null);
+        expect_filter(&mut txn, col, Filter::not_null(property), &not_null);
+    }
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_list_element_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let int_list = TestObj::get_prop(col, DataType::IntList);
+    let string_list = TestObj::get_prop(col, DataType::StringList);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.int_list = Some(vec![1, 2, 3]);
+    obj1.string_list = Some(vec![Some("a".to_string()), None, Some("C".to_string())]);
+    obj1.save(&mut txn, col);
+    let mut obj2 = TestObj::default(2);
+    obj2.int_list = Some(vec![3, 2]);
+    obj2.string_list = Some(vec![Some("c".to_string())]);
+    obj2.save(&mut txn, col);
+    let obj3 = TestObj::default(3);
+    obj3.save(&mut txn, col);
+
+    let results = vec![
+        (0, 1, vec![&obj1]),
+        (1, 2, vec![&obj1, &obj2]),
+        (2, 3, vec![&obj1]),
+        (2, 2, vec![]),
+        (3, 3, vec![]),
+    ];
+    for (index, value, objects) in results {
+        let value = ListElementValue::Int(value);
+        let filter = Filter::list_element(int_list, index, value, false).unwrap();
+        expect_filter(&mut txn, col, filter, &objects);
+    }
+
+    let results = vec![
+        (0, Some("C"), false, vec![&obj2]),
+        (2, Some("c"), false, vec![&obj1]),
+        (2, Some("c"), true, vec![]),
+        (1, None, true, vec![&obj1]),
+        (5, None, true, vec![]),
+    ];
+    for (index, value, case_sensitive, objects) in results {
+        let value = ListElementValue::String(value.map(|v| v.to_string()));
+        let filter = Filter::list_element(string_list, index, value, case_sensitive).unwrap();
+        expect_filter(&mut txn, col, filter, &objects);
+    }
+
+    let value = ListElementValue::Long(1);
+    assert!(Filter::list_element(int_list, 0, value, false).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_list_any_all_between_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let int_list = TestObj::get_prop(col, DataType::IntList);
+    let long_list = TestObj::get_prop(col, DataType::LongList);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.int_list = Some(vec![]);
+    obj1.long_list = Some(vec![]);
+    obj1.save(&mut txn, col);
+    let mut obj2 = TestObj::default(2);
+    obj2.int_list = Some(vec![2, 3, 4]);
+    obj2.long_list = Some(vec![2, 3, 4]);
+    obj2.save(&mut txn, col);
+    let mut obj3 = TestObj::default(3);
+    obj3.int_list = Some(vec![1, 3, 9]);
+    obj3.long_list = Some(vec![1, 3, 9]);
+    obj3.save(&mut txn, col);
+    let obj4 = TestObj::default(4);
+    obj4.save(&mut txn, col);
+
+    let results = vec![
+        (2, 4, vec![&obj2, &obj3], vec![&obj1, &obj2]),
+        (5, 8, vec![], vec![&obj1]),
+        (0, 10, vec![&obj2, &obj3], vec![&obj1, &obj2, &obj3]),
+        (4, 2, vec![], vec![&obj1]),
+    ];
+    for (lower, upper, any, all) in results {
+        let filter = Filter::int_list_any(int_list, lower, upper).unwrap();
+        expect_filter(&mut txn, col, filter, &any);
+        let filter = Filter::int_list_all(int_list, lower, upper).unwrap();
+        expect_filter(&mut txn, col, filter, &all);
+
+        let (lower, upper) = (lower as i64, upper as i64);
+        let filter = Filter::long_list_any(long_list, lower, upper).unwrap();
+        expect_filter(&mut txn, col, filter, &any);
+        let filter = Filter::long_list_all(long_list, lower, upper).unwrap();
+        expect_filter(&mut txn, col, filter, &all);
+    }
+
+    assert!(Filter::int_list_all(long_list, 0, 1).is_err());
+    assert!(Filter::long_list_any(int_list, 0, 1).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
 #[test]
 fn test_and_filter() {
     isar!(isar, col =>TestObj::default_schema());
@@ -434,3 +922,28 @@ fn test_static_filter() {
     txn.abort();
     isar.close();
 }
+
+#[test]
+fn test_link_length_filter() {
+    let col_schema = TestObj::schema("col", &[], &[LinkSchema::new("link", "col")]);
+    isar!(isar, col => col_schema);
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3);
+    col.link_all(&mut txn, 0, obj1.id, &[1, 2, 3]).unwrap();
+    col.link(&mut txn, 0, obj2.id, obj3.id).unwrap();
+
+    let has_any = Filter::link_length(col, 0, 1, u32::MAX).unwrap();
+    expect_filter(&mut txn, col, has_any, &[&obj1, &obj2]);
+
+    let has_none = Filter::link_length(col, 0, 0, 0).unwrap();
+    expect_filter(&mut txn, col, has_none, &[&obj3]);
+
+    let between = Filter::link_length(col, 0, 2, 3).unwrap();
+    expect_filter(&mut txn, col, between, &[&obj1]);
+
+    assert!(Filter::link_length(col, 1, 0, 0).is_err());
+
+    txn.abort();
+    isar.close();
+}
@@ -3,6 +3,16 @@ mod common;
 use std::vec;
 
 use isar_core::index::index_key::IndexKey;
+use isar_core::index::index_key_decoder::IndexKeyValue;
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::query::filter::Filter;
+use isar_core::query::query_builder::QueryBuilder;
+use isar_core::query::query_plan::WhereClausePlan;
+use isar_core::query::Sort;
+use isar_core::schema::index_schema::{Collation, IndexSchema};
+use isar_core::schema::Schema;
+use isar_core::txn::IsarTxn;
 use itertools::Itertools;
 
 use crate::common::test_obj::TestObj;
@@ -41,6 +51,149 @@ fn test_single_id_where_clause() {
     isar.close();
 }
 
+#[test]
+fn test_negative_id_where_clause() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj0 => -5, obj1 => -1, obj2 => 0, obj3 => 1, obj4 => 5);
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(-2, 2).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj1, &obj2, &obj3]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(i64::MIN, -1).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj0, &obj1]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(5, -5).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj4, &obj3, &obj2, &obj1, &obj0]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_overlapping_id_where_clauses() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4, obj5 => 5);
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(1, 3).unwrap();
+    qb.add_id_where_clause(2, 4).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj1, &obj2, &obj3, &obj4]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(1, 2).unwrap();
+    qb.add_id_where_clause(4, 5).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj1, &obj2, &obj4, &obj5]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_sorted_id_where_clause() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sorted_id_where_clause(i64::MIN, i64::MAX, Sort::Descending)
+        .unwrap();
+    let q = qb.build();
+    let plan = q.explain();
+    assert_eq!(
+        plan.where_clauses,
+        vec![WhereClausePlan::PrimaryScan { ascending: false }]
+    );
+    assert!(!plan.sort_buffer);
+    assert_find(&mut txn, col, q, &[&obj4, &obj3, &obj2, &obj1]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sorted_id_where_clause(2, 2, Sort::Descending)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj2]);
+
+    // overlapping clauses return every object once regardless of their direction
+    let mut qb = col.new_query_builder();
+    qb.add_sorted_id_where_clause(2, 4, Sort::Descending)
+        .unwrap();
+    qb.add_sorted_id_where_clause(1, 3, Sort::Ascending)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj4, &obj3, &obj2, &obj1]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_mixed_where_clauses() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, byte, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4);
+
+    let mut lower = IndexKey::new();
+    lower.add_byte(2);
+    let mut upper = IndexKey::new();
+    upper.add_byte(4);
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(obj1.id, obj3.id).unwrap();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj1, &obj2, &obj3, &obj4]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_where_clauses_of_different_indexes() {
+    let indexes = vec![
+        IndexSchema::new("byte", vec![TestObj::byte_index()], false),
+        IndexSchema::new("int", vec![TestObj::int_index()], false),
+    ];
+    isar!(isar, col => TestObj::schema("obj", &indexes, &[]));
+    txn!(isar, txn);
+
+    let mut objects = vec![];
+    for i in 1..=4 {
+        let mut obj = TestObj::default(i);
+        obj.byte = i as u8;
+        obj.int = i as i32 * 10;
+        obj.save(&mut txn, col);
+        objects.push(obj);
+    }
+
+    let byte_key = |value: u8| {
+        let mut key = col.new_index_key(0).unwrap();
+        key.add_byte(value).unwrap();
+        key.finish()
+    };
+    let int_key = |value: i32| {
+        let mut key = col.new_index_key(1).unwrap();
+        key.add_int(value).unwrap();
+        key.finish()
+    };
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, byte_key(1), true, byte_key(2), true, false)
+        .unwrap();
+    qb.add_index_where_clause(1, int_key(20), true, int_key(30), true, false)
+        .unwrap();
+    let q = qb.build();
+    assert_find(&mut txn, &col, q, &[&objects[0], &objects[1], &objects[2]]);
+
+    txn.abort();
+    isar.close();
+}
+
 #[test]
 fn test_single_index_where_clause() {
     isar!(isar, col =>TestObj::default_schema());
@@ -94,3 +247,489 @@ fn test_single_index_where_clause() {
     txn.abort();
     isar.close();
 }
+
+#[test]
+fn test_composite_index_where_clause() {
+    let index = IndexSchema::new(
+        "composite",
+        vec![
+            TestObj::byte_index(),
+            TestObj::int_index(),
+            TestObj::string_index(false, true),
+        ],
+        false,
+    );
+    let schema = TestObj::schema("obj", &[index], &[]);
+    isar!(isar, col => schema);
+    txn!(isar, txn);
+
+    let mut objects = vec![];
+    for (id, (byte, int, string)) in [
+        (2, -1, "b"),
+        (1, 5, "a"),
+        (2, -1, "a"),
+        (1, -3, "z"),
+        (2, 10, "a"),
+        (1, 5, "aa"),
+    ]
+    .iter()
+    .enumerate()
+    {
+        let mut obj = TestObj::default(id as i64 + 1);
+        obj.byte = *byte;
+        obj.int = *int;
+        obj.string = Some(string.to_string());
+        obj.save(&mut txn, col);
+        objects.push(obj);
+    }
+
+    let mut sorted = objects.iter().collect_vec();
+    sorted.sort_by(|o1, o2| {
+        (o1.byte, o1.int, &o1.string).cmp(&(o2.byte, o2.int, &o2.string))
+    });
+
+    let mut lower = IndexKey::new();
+    lower.add_byte(0);
+    let mut upper = IndexKey::new();
+    upper.add_byte(255);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower.clone(), true, upper.clone(), true, false)
+        .unwrap();
+    assert_find(&mut txn, &col, qb.build(), &sorted);
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, upper, true, lower, true, false)
+        .unwrap();
+    let reversed = sorted.iter().rev().copied().collect_vec();
+    assert_find(&mut txn, &col, qb.build(), &reversed);
+
+    let mut prefix = IndexKey::new();
+    prefix.add_byte(1);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, prefix.clone(), true, prefix.clone(), true, false)
+        .unwrap();
+    assert_find(&mut txn, &col, qb.build(), &sorted[0..3]);
+
+    prefix.add_int(5);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, prefix.clone(), true, prefix, true, false)
+        .unwrap();
+    assert_find(&mut txn, &col, qb.build(), &sorted[1..3]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_composite_index_prefix_where_clause() {
+    let index = IndexSchema::new(
+        "category_price",
+        vec![TestObj::int_index(), TestObj::double_index()],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    let mut objects = vec![];
+    for (id, (category, price)) in [(1, 5.0), (2, 3.0), (1, 2.5), (2, 7.5), (1, 9.0), (1, 7.0)]
+        .iter()
+        .enumerate()
+    {
+        let mut obj = TestObj::default(id as i64 + 1);
+        obj.int = *category;
+        obj.double = *price;
+        obj.save(&mut txn, col);
+        objects.push(obj);
+    }
+
+    let mut prefix = IndexKey::new();
+    prefix.add_int(1);
+    let price_key = |price: f64| {
+        let mut key = IndexKey::new();
+        key.add_double(price);
+        key
+    };
+
+    let mut qb = col.new_query_builder();
+    let (start, end) = (price_key(5.0), price_key(7.0));
+    qb.add_index_prefix_where_clause(0, &prefix, start, true, end, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&objects[0], &objects[5]]);
+
+    let mut qb = col.new_query_builder();
+    let (start, end) = (price_key(5.0), price_key(9.0));
+    qb.add_index_prefix_where_clause(0, &prefix, start, false, end, false, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&objects[5]]);
+
+    let mut qb = col.new_query_builder();
+    let (start, end) = (price_key(10.0), price_key(0.0));
+    qb.add_index_prefix_where_clause(0, &prefix, start, true, end, true, false)
+        .unwrap();
+    assert_find(
+        &mut txn,
+        col,
+        qb.build(),
+        &[&objects[4], &objects[5], &objects[0], &objects[2]],
+    );
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_string_list_hash_elements_where_clause() {
+    let index = IndexSchema::new(
+        "stringList",
+        vec![TestObj::string_list_index(false, true, false)],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    let lists = vec![
+        Some(vec!["a", "b"]),
+        Some(vec!["b", "c"]),
+        None,
+        Some(vec!["B"]),
+        Some(vec!["b", "B"]),
+    ];
+    let mut objects = vec![];
+    for (i, list) in lists.into_iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.string_list = list.map(|l| l.iter().map(|s| Some(s.to_string())).collect());
+        obj.save(&mut txn, col);
+        objects.push(obj);
+    }
+
+    // every element is hashed independently so the index can be used for contains lookups
+    let mut key = IndexKey::new();
+    key.add_hash(IsarObject::hash_string(Some("b"), false, 0));
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+        .unwrap();
+    assert_find(
+        &mut txn,
+        col,
+        qb.build(),
+        &[&objects[0], &objects[1], &objects[3], &objects[4]],
+    );
+
+    let mut key = IndexKey::new();
+    key.add_hash(IsarObject::hash_string(Some("d"), false, 0));
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[]);
+
+    // list contains filters are answered through the index
+    let string_list = TestObj::get_prop(col, DataType::StringList);
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::string(string_list, Some("B"), Some("B"), false).unwrap());
+    let q = qb.build();
+    let plan = q.explain();
+    assert!(matches!(
+        plan.where_clauses.as_slice(),
+        [WhereClausePlan::Index { .. }]
+    ));
+    assert_find(
+        &mut txn,
+        col,
+        q,
+        &[&objects[0], &objects[1], &objects[3], &objects[4]],
+    );
+
+    // the case sensitivity has to match the index
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::string(string_list, Some("B"), Some("B"), true).unwrap());
+    let q = qb.build();
+    assert_eq!(
+        q.explain().where_clauses,
+        vec![WhereClausePlan::PrimaryScan { ascending: true }]
+    );
+    assert_find(&mut txn, col, q, &[&objects[3], &objects[4]]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_string_prefix_where_clause() {
+    let index = IndexSchema::new("string", vec![TestObj::string_index(false, false)], false);
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    let strings = vec![Some("hello"), Some("Help"), Some("world"), None, Some("he")];
+    let mut objects = vec![];
+    for (i, string) in strings.into_iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.string = string.map(|s| s.to_string());
+        obj.save(&mut txn, col);
+        objects.push(obj);
+    }
+
+    let mut qb = col.new_query_builder();
+    qb.add_string_prefix_where_clause(0, "HEL", false).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&objects[0], &objects[1]]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_string_prefix_where_clause(0, "he", false).unwrap();
+    assert_find(
+        &mut txn,
+        col,
+        qb.build(),
+        &[&objects[4], &objects[0], &objects[1]],
+    );
+
+    let mut qb = col.new_query_builder();
+    qb.add_string_prefix_where_clause(0, "x", false).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[]);
+
+    let mut qb = col.new_query_builder();
+    assert!(qb.add_string_prefix_where_clause(1, "he", false).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_descending_index_where_clause() {
+    let index = IndexSchema::new("int", vec![TestObj::int_index().descending()], false);
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    let scores = vec![5, -3, 12, 0, 7];
+    let mut objects = vec![];
+    for (i, score) in scores.into_iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.int = score;
+        obj.save(&mut txn, col);
+        objects.push(obj);
+    }
+
+    let key = |value: i32| {
+        let mut key = col.new_index_key(0).unwrap();
+        key.add_int(value).unwrap();
+        key.finish()
+    };
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key(i32::MAX), true, key(i32::MIN), true, false)
+        .unwrap();
+    assert_find(
+        &mut txn,
+        col,
+        qb.build(),
+        &[
+            &objects[2],
+            &objects[4],
+            &objects[0],
+            &objects[3],
+            &objects[1],
+        ],
+    );
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key(7), true, key(0), false, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&objects[4], &objects[0]]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key(0), true, key(7), true, false)
+        .unwrap();
+    assert_find(
+        &mut txn,
+        col,
+        qb.build(),
+        &[&objects[3], &objects[0], &objects[4]],
+    );
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_string_index_prefix_len() {
+    fn find_ids(txn: &mut IsarTxn, qb: QueryBuilder) -> Vec<i64> {
+        let result = qb.build().find_all_vec(txn).unwrap();
+        result.iter().map(|(id, _)| *id).sorted().collect_vec()
+    }
+
+    let index_property = TestObj::string_index(false, true).prefix_len(4);
+    let index = IndexSchema::new("string", vec![index_property], false);
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    let strings = vec!["abcdefgh", "abcdxyz", "abc", "bcdefg"];
+    for (i, string) in strings.into_iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.string = Some(string.to_string());
+        obj.save(&mut txn, col);
+    }
+
+    let key = |value: &str| {
+        let mut key = col.new_index_key(0).unwrap();
+        key.add_string(Some(value)).unwrap();
+        key.finish()
+    };
+
+    // long strings with the same prefix are still distinguished by their hash
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key("abcdefgh"), true, key("abcdefgh"), true, false)
+        .unwrap();
+    assert_eq!(find_ids(&mut txn, qb), vec![1]);
+
+    // both long strings share the indexed prefix
+    let mut qb = col.new_query_builder();
+    qb.add_string_prefix_where_clause(0, "abcd", false).unwrap();
+    assert_eq!(find_ids(&mut txn, qb), vec![1, 2]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key("abc"), true, key("b"), true, false)
+        .unwrap();
+    assert_eq!(find_ids(&mut txn, qb), vec![1, 2, 3]);
+
+    // bounds that only differ after the prefix are compared with the full strings
+    for (lower, upper, ids) in [
+        ("abcde", "abcdz", vec![1, 2]),
+        ("abcdf", "abcdz", vec![2]),
+        ("abcda", "abcdf", vec![1]),
+        ("abcdz", "abcde", vec![1, 2]),
+        ("abcdefgh", "abcdxyz", vec![1, 2]),
+    ] {
+        let mut qb = col.new_query_builder();
+        qb.add_index_where_clause(0, key(lower), true, key(upper), true, false)
+            .unwrap();
+        assert_eq!(find_ids(&mut txn, qb), ids);
+    }
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key("abcdefgh"), false, key("abcdxyz"), false, false)
+        .unwrap();
+    assert!(find_ids(&mut txn, qb).is_empty());
+
+    let mut qb = col.new_query_builder();
+    let result = qb.add_string_prefix_where_clause(0, "abcde", false);
+    assert!(result.is_err());
+
+    txn.abort();
+    isar.close();
+
+    let index = IndexSchema::new("int", vec![TestObj::int_index().prefix_len(4)], false);
+    assert!(Schema::new(vec![TestObj::schema("obj", &[index], &[])]).is_err());
+    let index_property = TestObj::string_index(false, true).prefix_len(0);
+    let index = IndexSchema::new("string", vec![index_property], false);
+    assert!(Schema::new(vec![TestObj::schema("obj", &[index], &[])]).is_err());
+}
+
+#[test]
+fn test_string_index_collation() {
+    let strings = vec!["zebra", "éclair", "apple", "ecole", "fig"];
+    let query = |collation: Collation| {
+        let index_property = TestObj::string_index(false, true).collation(collation);
+        let index = IndexSchema::new("string", vec![index_property], false);
+        isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+        txn!(isar, txn);
+        for (i, string) in strings.iter().enumerate() {
+            let mut obj = TestObj::default(i as i64 + 1);
+            obj.string = Some(string.to_string());
+            obj.save(&mut txn, col);
+        }
+
+        let mut upper = IndexKey::new();
+        upper.add_byte(0xFF);
+        let mut qb = col.new_query_builder();
+        qb.add_index_where_clause(0, IndexKey::new(), true, upper, true, false)
+            .unwrap();
+        let result = qb.build().find_all_vec(&mut txn).unwrap();
+        let sorted = result
+            .into_iter()
+            .map(|(_, object)| TestObj::from_object(col, object).string.unwrap())
+            .collect_vec();
+
+        // where clauses use the collation of the index
+        let mut key = col.new_index_key(0).unwrap();
+        key.add_string(Some("eclair")).unwrap();
+        let key = key.finish();
+        let mut qb = col.new_query_builder();
+        qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+            .unwrap();
+        let exact_matches = qb.build().find_all_vec(&mut txn).unwrap().len();
+        let mut qb = col.new_query_builder();
+        qb.add_string_prefix_where_clause(0, "é", false).unwrap();
+        let prefix_matches = qb.build().find_all_vec(&mut txn).unwrap().len();
+
+        txn.abort();
+        isar.close();
+        (sorted, exact_matches, prefix_matches)
+    };
+
+    let (sorted, exact_matches, prefix_matches) = query(Collation::Raw);
+    assert_eq!(sorted, vec!["apple", "ecole", "fig", "zebra", "éclair"]);
+    assert_eq!((exact_matches, prefix_matches), (0, 1));
+
+    let (sorted, exact_matches, prefix_matches) = query(Collation::Folded);
+    assert_eq!(sorted, vec!["apple", "éclair", "ecole", "fig", "zebra"]);
+    assert_eq!((exact_matches, prefix_matches), (1, 2));
+
+    let index_property = TestObj::int_index().collation(Collation::Folded);
+    let index = IndexSchema::new("int", vec![index_property], false);
+    assert!(Schema::new(vec![TestObj::schema("obj", &[index], &[])]).is_err());
+}
+
+#[test]
+fn test_iter_index_keys() {
+    let index = IndexSchema::new("string", vec![TestObj::string_index(false, false)], false);
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    let strings = vec![
+        Some("Berlin"),
+        Some("paris"),
+        None,
+        Some("berlin"),
+        Some("Paris"),
+        Some("Rome"),
+    ];
+    for (i, string) in strings.into_iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.string = string.map(|s| s.to_string());
+        obj.save(&mut txn, col);
+    }
+
+    let keys = |txn: &mut IsarTxn, lower: &IndexKey, skip_duplicates: bool| {
+        let mut upper = IndexKey::new();
+        upper.add_byte(0xFF);
+        let mut keys = vec![];
+        col.iter_index_keys(txn, 0, lower, &upper, skip_duplicates, |key| {
+            keys.push(key);
+            Ok(true)
+        })
+        .unwrap();
+        keys
+    };
+    let string = |value: Option<&str>| vec![IndexKeyValue::String(value.map(|s| s.to_string()))];
+
+    assert_eq!(
+        keys(&mut txn, &IndexKey::new(), true),
+        vec![
+            string(None),
+            string(Some("berlin")),
+            string(Some("paris")),
+            string(Some("rome")),
+        ]
+    );
+
+    let mut lower = IndexKey::new();
+    lower.add_string(Some("c"), false);
+    assert_eq!(
+        keys(&mut txn, &lower, false),
+        vec![
+            string(Some("paris")),
+            string(Some("paris")),
+            string(Some("rome")),
+        ]
+    );
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,200 @@
+use crate::common::test_obj::TestObj;
+use crossbeam_channel::unbounded;
+use isar_core::collection::OnConflict;
+use isar_core::error::IsarError;
+use isar_core::instance::IsarInstance;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::schema::index_schema::IndexSchema;
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_savepoint() {
+    let index = IndexSchema::new("byte", vec![TestObj::byte_index()], true);
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+    put!(col, txn, byte, obj1 => 1);
+
+    let mut savepoint = txn.savepoint().unwrap();
+    put!(col, savepoint, byte, obj2 => 2);
+    verify!(savepoint, col, obj1, obj2);
+    savepoint.abort();
+    verify!(txn, col, obj1);
+
+    // a failed operation only invalidates the savepoint
+    let mut savepoint = txn.savepoint().unwrap();
+    put!(col, savepoint, byte, obj3 => 3);
+    let mut obj4 = TestObj::default(10);
+    obj4.byte = 1;
+    let bytes = obj4.to_bytes(col);
+    let object = IsarObject::from_bytes(&bytes);
+    assert!(col
+        .put(&mut savepoint, Some(10), object, OnConflict::Abort)
+        .is_err());
+    assert_eq!(
+        col.get(&mut savepoint, obj1.id),
+        Err(IsarError::TransactionClosed {})
+    );
+    savepoint.abort();
+    verify!(txn, col, obj1);
+
+    let mut savepoint = txn.savepoint().unwrap();
+    put!(col, savepoint, byte, obj5 => 5);
+    let mut nested = savepoint.savepoint().unwrap();
+    put!(col, nested, byte, obj6 => 6);
+    nested.abort();
+    savepoint.commit().unwrap();
+    verify!(txn, col, obj1, obj5);
+
+    txn.commit().unwrap();
+    txn!(isar, txn);
+    verify!(txn, col, obj1, obj5);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_savepoint_requires_write_txn() {
+    isar!(isar);
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    assert!(txn.savepoint().is_err());
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_with_preview() {
+    isar!(isar, col => TestObj::default_schema());
+    let (tx, rx) = unbounded();
+    let handle = isar.watch_object(col, 2, Box::new(move |_| tx.send(true).unwrap()));
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1);
+    let count = txn
+        .with_preview(|txn| {
+            put!(id: col, txn, obj2 => 2);
+            verify!(txn, col, obj1, obj2);
+            col.count(txn)
+        })
+        .unwrap();
+    assert_eq!(count, 2);
+    verify!(txn, col, obj1);
+
+    // the changes are discarded even if the preview fails
+    let result = txn.with_preview(|txn| {
+        put!(id: col, txn, _obj2 => 2);
+        let object = IsarObject::from_bytes(&[1, 0]);
+        col.put(txn, Some(3), object, OnConflict::Abort)
+    });
+    assert!(result.is_err());
+    verify!(txn, col, obj1);
+
+    txn.commit().unwrap();
+    txn!(isar, txn);
+    verify!(txn, col, obj1);
+    txn.abort();
+
+    // watchers are not notified about discarded changes
+    assert_eq!(rx.len(), 0);
+    handle.stop();
+    isar.close();
+}
+
+#[test]
+fn test_snapshot() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1);
+    txn.commit().unwrap();
+
+    let mut snapshot = isar.begin_snapshot().unwrap();
+    txn!(isar, txn);
+    put!(id: col, txn, obj2 => 2);
+    txn.commit().unwrap();
+
+    // the snapshot does not see changes committed after it was started
+    verify!(snapshot, col, obj1);
+    snapshot.close();
+
+    txn!(isar, txn);
+    verify!(txn, col, obj1, obj2);
+    txn.abort();
+
+    let snapshots = (0..IsarInstance::MAX_SNAPSHOTS)
+        .map(|_| isar.begin_snapshot().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        isar.begin_snapshot().err(),
+        Some(IsarError::TooManySnapshots {})
+    );
+    drop(snapshots);
+    isar.begin_snapshot().unwrap().close();
+
+    isar.close();
+}
+
+#[test]
+fn test_begin_txn_timeout() {
+    isar!(isar);
+    let txn = isar.begin_txn(true, false).unwrap();
+
+    let contender = isar.clone();
+    thread::spawn(move || {
+        let timeout = Duration::from_millis(50);
+        for silent in &[false, true] {
+            assert_eq!(
+                contender.begin_txn_timeout(true, *silent, timeout).err(),
+                Some(IsarError::Timeout {})
+            );
+        }
+        // read transactions do not wait for the write lock
+        let read_txn = contender.begin_txn_timeout(false, false, timeout).unwrap();
+        read_txn.abort();
+    })
+    .join()
+    .unwrap();
+
+    let contender = isar.clone();
+    let handle = thread::spawn(move || {
+        let timeout = Duration::from_secs(10);
+        let txn = contender.begin_txn_timeout(true, false, timeout).unwrap();
+        txn.abort();
+    });
+    thread::sleep(Duration::from_millis(20));
+    txn.abort();
+    handle.join().unwrap();
+
+    isar.close();
+}
+
+#[test]
+fn test_cross_collection_txn() {
+    let schema1 = TestObj::schema("obj1", &[], &[]);
+    let schema2 = TestObj::schema("obj2", &[], &[]);
+    isar!(isar, col1 => schema1, col2 => schema2);
+    txn!(isar, txn);
+    put!(id: col1, txn, obj1 => 1);
+    put!(id: col2, txn, obj2 => 2, obj3 => 3);
+    txn.commit().unwrap();
+
+    // a put into one collection and a delete from another are rolled back together
+    txn!(isar, txn);
+    put!(id: col1, txn, obj4 => 4);
+    assert!(col2.delete(&mut txn, 2).unwrap());
+    verify!(txn, col!(col1, obj1, obj4); col!(col2, obj3));
+    txn.abort();
+
+    txn!(isar, txn);
+    verify!(txn, col!(col1, obj1); col!(col2, obj2, obj3));
+    put!(id: col1, txn, obj5 => 5);
+    assert!(col2.delete(&mut txn, 3).unwrap());
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    verify!(txn, col!(col1, obj1, obj5); col!(col2, obj2));
+    txn.abort();
+    isar.close();
+}